@@ -409,8 +409,8 @@ impl AdaptiveEngine {
         self.runtime.set_clean_output(clean);
     }
     
-    /// Register function parameter names
-    pub fn register_function_params(&mut self, func_name: String, params: Vec<String>) {
+    /// Register function parameters
+    pub fn register_function_params(&mut self, func_name: String, params: Vec<super::ir::ParamSpec>) {
         self.runtime.register_function_params(func_name, params);
     }
     
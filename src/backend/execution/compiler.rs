@@ -6,9 +6,24 @@ use std::fs;
 use std::path::Path;
 use crate::frontend::parser::ast::{Program, Statement, Expression, InterpolatedString, InterpolationPart, UseStatement};
 use crate::frontend::parser::parse_source_with_name;
-use super::ir::IR;
+use super::ir::{ParamSpec, IR};
 use super::runtime::Runtime;
 
+/// One projection step from a match subject to the value a sub-pattern tests
+#[derive(Debug, Clone)]
+enum PatternStep {
+    Index(usize),
+    Field(String),
+    Payload(usize),
+}
+
+/// One step from a variable to the field or element an assignment stores into
+#[derive(Debug, Clone)]
+enum PlaceStep {
+    Field(String),
+    Index(Expression),
+}
+
 /// Symbol table for variable and function tracking
 #[derive(Debug, Clone)]
 struct SymbolTable {
@@ -88,12 +103,17 @@ pub struct Compiler {
     pub ir: Vec<IR>,
     symbol_table: SymbolTable,
     function_table: FunctionTable,
-    pub function_param_names: HashMap<String, Vec<String>>,
+    pub function_param_names: HashMap<String, Vec<ParamSpec>>,
     current_function: Option<String>,
     break_stack: Vec<Vec<usize>>,
     continue_stack: Vec<Vec<usize>>,
     loop_stack: Vec<(String, String)>, // (continue_label, break_label)
+    loop_labels: Vec<(Option<String>, bool)>, // label of each enclosing loop and whether its break yields a value
     continue_positions: Vec<usize>, // Store actual continue positions
+    try_depth: usize, // Number of enclosing try blocks in the current function
+    loop_try_depths: Vec<usize>, // try_depth at the start of each enclosing loop
+    block_depth: usize, // Number of enclosing runtime block scopes in the current function
+    loop_block_depths: Vec<usize>, // block_depth at the start of each enclosing loop
     label_counter: usize,
     clean_output: bool,
     pub errors: Vec<String>,
@@ -102,6 +122,8 @@ pub struct Compiler {
     current_module_name: Option<String>, // Track current module being compiled
     module_level_vars: HashMap<String, Vec<String>>, // module_name -> [var1, var2, ...]
     type_aliases: HashMap<String, crate::frontend::parser::ast::TypeAnnotation>, // type_name -> target_type
+    enum_variants: HashMap<String, Vec<String>>, // enum_name -> variant names
+    trait_defaults: HashMap<String, Vec<crate::frontend::parser::ast::TraitMethod>>, // trait_name -> methods with default bodies
 }
 
 impl Compiler {
@@ -115,7 +137,12 @@ impl Compiler {
             break_stack: Vec::new(),
             continue_stack: Vec::new(),
             loop_stack: Vec::new(),
+            loop_labels: Vec::new(),
             continue_positions: Vec::new(),
+            try_depth: 0,
+            loop_try_depths: Vec::new(),
+            block_depth: 0,
+            loop_block_depths: Vec::new(),
             label_counter: 0,
             clean_output: false,
             errors: Vec::new(),
@@ -124,6 +151,8 @@ impl Compiler {
             current_module_name: None,
             module_level_vars: HashMap::new(),
             type_aliases: HashMap::new(),
+            enum_variants: HashMap::new(),
+            trait_defaults: HashMap::new(),
         }
     }
 
@@ -185,6 +214,30 @@ impl Compiler {
         }
     }
 
+    /// Enter a scope that also exists at runtime, so its declarations end with it
+    fn enter_block_scope(&mut self) {
+        self.enter_scope();
+        self.emit(IR::EnterScope);
+        self.block_depth += 1;
+    }
+
+    fn leave_block_scope(&mut self) {
+        self.block_depth -= 1;
+        self.emit(IR::ExitScope);
+        self.leave_scope();
+    }
+
+    /// Bind the value on top of the stack to a new variable in the current scope
+    fn emit_declare(&mut self, name: String) {
+        self.symbol_table.define(&name);
+        if self.block_depth > 0 {
+            self.emit(IR::DeclareVar(name));
+        } else {
+            // Function and module level variables live as long as their frame
+            self.emit(IR::StoreVar(name));
+        }
+    }
+
     /// Resolve a type annotation, expanding type aliases if needed
     #[allow(dead_code)]
     fn resolve_type(&self, type_annotation: &crate::frontend::parser::ast::TypeAnnotation) -> crate::frontend::parser::ast::TypeAnnotation {
@@ -252,7 +305,7 @@ impl Compiler {
             }
         }
 
-        // Second pass: register all functions (including imported ones)
+        // Second pass: register all functions (including imported ones), enum variants and trait defaults
         let mut function_count = 0;
         for stmt in &program.statements {
             if let Statement::FunctionDeclaration(func_decl) = stmt {
@@ -261,6 +314,10 @@ impl Compiler {
                 if !self.clean_output {
                     println!("Found function: {}", func_decl.name.name);
                 }
+            } else if let Statement::EnumDeclaration(enum_decl) = stmt {
+                self.register_enum_variants(enum_decl);
+            } else if let Statement::TraitDeclaration(trait_decl) = stmt {
+                self.register_trait_defaults(trait_decl);
             }
         }
         
@@ -341,15 +398,13 @@ impl Compiler {
                                     // Check if function is public
                                     if func_decl.is_public {
                                         let qualified_name = format!("{}.{}", module_name, func_decl.name.name);
-                                        let parameters: Vec<String> = func_decl.parameters.iter()
-                                            .map(|p| p.name.name.clone()).collect();
                                         
                                         // Set module context before compiling function
                                         self.current_module_name = Some(module_name.clone());
                                         
                                         // Compile the function with qualified name
                                         let ir_start = self.ir.len();
-                                        self.compile_function_declaration(qualified_name.clone(), parameters, func_decl.body.statements);
+                                        self.compile_function_declaration(qualified_name.clone(), func_decl.parameters, func_decl.body.statements);
                                         let ir_end = self.ir.len();
                                         
                                         // Clear module context after compiling
@@ -375,7 +430,7 @@ impl Compiler {
                                         
                                         // Compile the constant value and store it
                                         self.compile_expression(const_decl.initializer);
-                                        self.emit(IR::StoreVar(qualified_name.clone()));
+                                        self.emit(IR::DeclareConst(qualified_name.clone()));
                                         
                                         if !self.clean_output {
                                             println!("Imported constant: {} -> {}", const_decl.name.name, qualified_name);
@@ -444,23 +499,27 @@ impl Compiler {
 
     fn compile_statement(&mut self, stmt: Statement) {
         match stmt {
+            Statement::VariableDeclaration(var_decl) if !var_decl.destructure.is_empty() => {
+                // var (a, b) = expr
+                if let Some(expr) = var_decl.initializer {
+                    self.compile_expression(expr);
+                } else {
+                    self.emit(IR::PushNull);
+                }
+                self.emit_destructure(&var_decl.destructure);
+            },
             Statement::VariableDeclaration(var_decl) => {
-                self.symbol_table.define(&var_decl.name.name);
-                
                 if let Some(expr) = var_decl.initializer {
                     self.compile_expression(expr);
                 } else {
                     self.emit(IR::PushNull);
                 }
                 
-                self.emit(IR::StoreVar(var_decl.name.name));
+                self.emit_declare(var_decl.name.name);
             },
             Statement::FunctionDeclaration(func_decl) => {
                 let name = func_decl.name.name;
-                let parameters: Vec<String> = func_decl.parameters.iter()
-                    .map(|p| p.name.name.clone()).collect();
-                
-                self.compile_function_declaration(name, parameters, func_decl.body.statements);
+                self.compile_function_declaration(name, func_decl.parameters, func_decl.body.statements);
             },
             Statement::ReturnStatement(ret_stmt) => {
                 if let Some(expr) = ret_stmt.value {
@@ -471,15 +530,21 @@ impl Compiler {
                 self.emit(IR::Return);
             },
             Statement::ExpressionStatement(expr_stmt) => {
+                // Assignments store their value without leaving a result behind, so a Pop here would
+                // discard a value owned by the enclosing code, e.g. the array a spread is building
+                // while an iterator's next() runs
+                let leaves_value = !matches!(expr_stmt.expression, Expression::AssignmentExpression(_));
                 self.compile_expression(expr_stmt.expression);
-                self.emit(IR::Pop); // Discard result
+                if leaves_value {
+                    self.emit(IR::Pop); // Discard result
+                }
             },
             Statement::BlockStatement(block_stmt) => {
-                self.enter_scope();
+                self.enter_block_scope();
                 for stmt in block_stmt.statements {
                     self.compile_statement(stmt);
                 }
-                self.leave_scope();
+                self.leave_block_scope();
             },
             Statement::IfStatement(if_stmt) => {
                 self.compile_if_elif_else_statement(if_stmt);
@@ -491,7 +556,7 @@ impl Compiler {
                     vec![*while_stmt.body]
                 };
                 
-                self.compile_while_statement(while_stmt.condition, body_statements);
+                self.compile_while_statement(while_stmt.label.map(|l| l.name), while_stmt.condition, body_statements);
             },
             Statement::ForStatement(for_stmt) => {
                 let body_statements = if let Statement::BlockStatement(block) = *for_stmt.body {
@@ -500,27 +565,32 @@ impl Compiler {
                     vec![*for_stmt.body]
                 };
                 
-                self.compile_for_statement(for_stmt.variable.name, for_stmt.iterable, body_statements);
+                self.compile_for_statement(
+                    for_stmt.label.map(|l| l.name),
+                    for_stmt.variable.name,
+                    for_stmt.destructure,
+                    for_stmt.iterable,
+                    body_statements,
+                );
             },
-            Statement::BreakStatement(_) => {
-                self.compile_break_statement();
+            Statement::BreakStatement(break_stmt) => {
+                self.compile_break_statement(break_stmt);
             },
-            Statement::ContinueStatement(_) => {
-                self.compile_continue_statement();
+            Statement::ContinueStatement(continue_stmt) => {
+                self.compile_continue_statement(continue_stmt);
             },
             Statement::MatchStatement(match_stmt) => {
                 self.compile_match_statement(match_stmt);
             },
             Statement::ConstantDeclaration(const_decl) => {
-                // Handle const declarations similar to variable declarations
-                // but mark them as immutable in the symbol table
-                self.symbol_table.define(&const_decl.name.name);
-                
+                // Handle const declarations similar to variable declarations,
+                // but the runtime rejects later stores to them
                 // Compile the initializer expression
                 self.compile_expression(const_decl.initializer);
                 
                 // Store the constant value
-                self.emit(IR::StoreVar(const_decl.name.name));
+                self.symbol_table.define(&const_decl.name.name);
+                self.emit(IR::DeclareConst(const_decl.name.name));
             },
             Statement::TypeAliasDeclaration(type_alias) => {
                 // Register the type alias for later resolution
@@ -536,9 +606,24 @@ impl Compiler {
             Statement::EnumDeclaration(enum_decl) => {
                 self.compile_enum_declaration(enum_decl);
             },
+            Statement::TraitDeclaration(trait_decl) => {
+                // Default methods are compiled into each implementing type by compile_impl_block;
+                // traits declared inside a block are only registered here
+                self.register_trait_defaults(&trait_decl);
+            },
             Statement::ImplBlock(impl_block) => {
                 self.compile_impl_block(impl_block);
             },
+            Statement::TryStatement(try_stmt) => {
+                self.compile_try_statement(try_stmt);
+            },
+            Statement::ThrowStatement(throw_stmt) => {
+                self.compile_expression(throw_stmt.value);
+                self.emit(IR::ThrowException);
+            },
+            Statement::DeferStatement(defer_stmt) => {
+                self.compile_defer_statement(defer_stmt);
+            },
             _ => {
                 // Handle other statement types as needed
                 if !self.clean_output {
@@ -548,9 +633,25 @@ impl Compiler {
         }
     }
 
-    fn compile_function_declaration(&mut self, name: String, parameters: Vec<String>, body: Vec<Statement>) {
+    /// Parameters as registered with the runtime, which binds call arguments to them
+    fn parameter_specs(parameters: &[crate::frontend::parser::ast::Parameter]) -> Vec<ParamSpec> {
+        parameters.iter()
+            .map(|p| ParamSpec {
+                name: p.name.name.clone(),
+                has_default: p.default_value.is_some(),
+                variadic: p.is_variadic,
+            })
+            .collect()
+    }
+
+    fn compile_function_declaration(&mut self, name: String, parameters: Vec<crate::frontend::parser::ast::Parameter>, body: Vec<Statement>) {
+        let param_specs = Self::parameter_specs(&parameters);
         let old_function = self.current_function.clone();
         self.current_function = Some(name.clone());
+        // Try blocks around a function definition do not cover its body
+        let old_try_depth = std::mem::replace(&mut self.try_depth, 0);
+        // The body runs in its own frame, outside any enclosing block scopes
+        let old_block_depth = std::mem::replace(&mut self.block_depth, 0);
 
         let function_label = self.generate_label("function_");
         let end_label = self.generate_label("end_");
@@ -561,11 +662,10 @@ impl Compiler {
         // Mark function start
         let function_start = self.emit_label(&function_label);
         self.function_table.define(&name, function_start);
-        self.function_param_names.insert(name.clone(), parameters.clone());
-        
         if !self.clean_output {
-            println!("DEBUG COMPILER: Registered function '{}' with params: {:?}", name, parameters);
+            println!("DEBUG COMPILER: Registered function '{}' with params: {:?}", name, param_specs);
         }
+        self.function_param_names.insert(name.clone(), param_specs);
 
         self.emit(IR::DefineFunction(name.clone(), function_start));
 
@@ -574,7 +674,20 @@ impl Compiler {
 
         // Define parameters
         for param in &parameters {
-            self.symbol_table.define(param);
+            self.symbol_table.define(&param.name.name);
+        }
+
+        // Arguments the caller left out are filled in from their default values,
+        // which may refer to earlier parameters
+        for param in parameters {
+            if let Some(default_value) = param.default_value {
+                let skip_label = self.generate_label("default_skip_");
+                let jump_pos = self.emit(IR::JumpIfDefined(param.name.name.clone(), 0));
+                self.compile_expression(default_value);
+                self.emit(IR::StoreVar(param.name.name.clone()));
+                let skip_pos = self.emit_label(&skip_label);
+                self.replace_instruction(jump_pos, IR::JumpIfDefined(param.name.name, skip_pos));
+            }
         }
 
         // Compile function body
@@ -591,6 +704,8 @@ impl Compiler {
 
         self.leave_scope();
         self.current_function = old_function;
+        self.try_depth = old_try_depth;
+        self.block_depth = old_block_depth;
     }
 
     fn compile_if_elif_else_statement(&mut self, if_stmt: crate::frontend::parser::ast::IfStatement) {
@@ -650,13 +765,16 @@ impl Compiler {
         }
     }
 
-    fn compile_while_statement(&mut self, condition: Expression, body: Vec<Statement>) {
+    fn compile_while_statement(&mut self, label: Option<String>, condition: Expression, body: Vec<Statement>) {
         let loop_label = self.generate_label("loop_");
         let continue_label = self.generate_label("continue_");
         let end_label = self.generate_label("end_");
 
         // Push loop context for break/continue
         self.loop_stack.push((continue_label.clone(), end_label.clone()));
+        self.loop_labels.push((label, false));
+        self.loop_try_depths.push(self.try_depth);
+        self.loop_block_depths.push(self.block_depth);
         self.break_stack.push(Vec::new());
         self.continue_stack.push(Vec::new());
 
@@ -668,12 +786,12 @@ impl Compiler {
         self.emit(IR::JumpIfFalse(0));
         let jump_to_end_pos = self.ir.len() - 1;
 
-        // Compile loop body
-        self.enter_scope();
+        // Compile loop body; each iteration gets fresh bindings
+        self.enter_block_scope();
         for stmt in body {
             self.compile_statement(stmt);
         }
-        self.leave_scope();
+        self.leave_block_scope();
 
         self.emit(IR::Jump(loop_start));
 
@@ -693,9 +811,80 @@ impl Compiler {
         }
         
         self.loop_stack.pop();
+        self.loop_labels.pop();
+        self.loop_try_depths.pop();
+        self.loop_block_depths.pop();
     }
 
-    fn compile_for_statement(&mut self, variable: String, iterable: Expression, body: Vec<Statement>) {
+    /// Compile `loop { ... }`; every `break` leaves its value on the stack as the result
+    fn compile_loop_expression(&mut self, loop_expr: crate::frontend::parser::ast::LoopExpression) {
+        let loop_label = self.generate_label("loop_");
+        let end_label = self.generate_label("loop_end_");
+
+        // Push loop context for break/continue
+        self.loop_stack.push((loop_label.clone(), end_label.clone()));
+        self.loop_labels.push((loop_expr.label.map(|l| l.name), true));
+        self.loop_try_depths.push(self.try_depth);
+        self.loop_block_depths.push(self.block_depth);
+        self.break_stack.push(Vec::new());
+        self.continue_stack.push(Vec::new());
+
+        let loop_start = self.emit_label(&loop_label);
+        self.continue_positions.push(loop_start);
+
+        self.enter_block_scope();
+        for stmt in loop_expr.body.statements {
+            self.compile_statement(stmt);
+        }
+        self.leave_block_scope();
+
+        self.emit(IR::Jump(loop_start));
+
+        let end_pos = self.emit_label(&end_label);
+
+        // Patch break and continue statements
+        if let (Some(break_positions), Some(continue_positions)) =
+            (self.break_stack.pop(), self.continue_stack.pop()) {
+            let actual_continue_pos = self.continue_positions.pop().unwrap_or(loop_start);
+            for pos in break_positions {
+                self.replace_instruction(pos, IR::Jump(end_pos));
+            }
+            for pos in continue_positions {
+                self.replace_instruction(pos, IR::Jump(actual_continue_pos));
+            }
+        }
+
+        self.loop_stack.pop();
+        self.loop_labels.pop();
+        self.loop_try_depths.pop();
+        self.loop_block_depths.pop();
+    }
+
+    /// Unpack the tuple on top of the stack into the given variables
+    fn emit_destructure(&mut self, names: &[crate::frontend::parser::ast::Identifier]) {
+        self.emit(IR::UnpackTuple(names.len()));
+        for name in names {
+            self.emit_declare(name.name.clone());
+        }
+    }
+
+    /// Store the value on top of the stack into the loop variable(s)
+    fn emit_loop_binding(&mut self, variable: &str, destructure: &[crate::frontend::parser::ast::Identifier]) {
+        if destructure.is_empty() {
+            self.emit_declare(variable.to_string());
+        } else {
+            self.emit_destructure(destructure);
+        }
+    }
+
+    fn compile_for_statement(
+        &mut self,
+        label: Option<String>,
+        variable: String,
+        destructure: Vec<crate::frontend::parser::ast::Identifier>,
+        iterable: Expression,
+        body: Vec<Statement>,
+    ) {
         let loop_label = self.generate_label("for_loop_");
         let continue_label = self.generate_label("for_continue_");
         let end_label = self.generate_label("for_end_");
@@ -704,6 +893,9 @@ impl Compiler {
 
         // Push loop context for break/continue
         self.loop_stack.push((continue_label.clone(), end_label.clone()));
+        self.loop_labels.push((label, false));
+        self.loop_try_depths.push(self.try_depth);
+        self.loop_block_depths.push(self.block_depth);
         self.break_stack.push(Vec::new());
         self.continue_stack.push(Vec::new());
 
@@ -735,13 +927,15 @@ impl Compiler {
                 let jump_to_end_pos = self.ir.len() - 1;
                 
                 // Set loop variable to current index
+                self.enter_block_scope();
                 self.emit(IR::LoadVar(index_var.clone()));
-                self.emit(IR::StoreVar(variable.clone()));
+                self.emit_loop_binding(&variable, &destructure);
                 
                 // Compile loop body
                 for stmt in body {
                     self.compile_statement(stmt);
                 }
+                self.leave_block_scope();
                 
                 // This is where continue should jump to - the increment part
                 let actual_continue_pos = self.ir.len();
@@ -750,7 +944,7 @@ impl Compiler {
                 
                 // Increment index
                 self.emit(IR::LoadVar(index_var.clone()));
-                self.emit(IR::PushNumber(1.0));
+                self.emit(IR::PushInteger(1));
                 self.emit(IR::Add);
                 self.emit(IR::StoreVar(index_var));
                 
@@ -759,39 +953,32 @@ impl Compiler {
                 let end_pos = self.emit_label(&end_label);
                 self.replace_instruction(jump_to_end_pos, IR::JumpIfFalse(end_pos));
             },
-            Expression::ArrayLiteral(array) => {
-                // For array literals like [1, 2, 3] - simpler approach
-                // Just iterate through each element directly
-                for (_i, element) in array.elements.iter().enumerate() {
-                    // Set loop variable to current element
-                    self.compile_expression(element.clone());
-                    self.emit(IR::StoreVar(variable.clone()));
-                    
-                    // Compile loop body for this iteration
-                    for stmt in body.clone() {
-                        self.compile_statement(stmt);
-                    }
-                }
-                
-                // No need for complex loop logic - we've already iterated through all elements
-                let _loop_start = self.emit_label(&loop_label);
-                let _continue_pos = self.emit_label(&continue_label);
-                let _end_pos = self.emit_label(&end_label);
-            },
             _ => {
-                // For other expressions, treat as single value iteration
+                // Everything else goes through the iteration protocol, one item at a time
                 self.compile_expression(iterable);
-                self.emit(IR::StoreVar(variable.clone()));
-                
-                let _loop_start = self.emit_label(&loop_label);
-                let _continue_pos = self.emit_label(&continue_label);
-                
-                // Compile loop body (executes once)
+                self.emit(IR::GetIter);
+                self.emit(IR::StoreVar(iter_var.clone()));
+
+                let loop_start = self.emit_label(&loop_label);
+                let continue_pos = self.emit_label(&continue_label);
+                self.continue_positions.push(continue_pos);
+
+                self.emit(IR::IterNext(iter_var.clone(), 0));
+                let next_pos = self.ir.len() - 1;
+
+                // Bind the produced item
+                self.enter_block_scope();
+                self.emit_loop_binding(&variable, &destructure);
+
                 for stmt in body {
                     self.compile_statement(stmt);
                 }
-                
-                let _end_pos = self.emit_label(&end_label);
+                self.leave_block_scope();
+
+                self.emit(IR::Jump(loop_start));
+
+                let end_pos = self.emit_label(&end_label);
+                self.replace_instruction(next_pos, IR::IterNext(iter_var, end_pos));
             }
         }
         
@@ -810,90 +997,354 @@ impl Compiler {
         
         self.leave_scope();
         self.loop_stack.pop();
+        self.loop_labels.pop();
+        self.loop_try_depths.pop();
+        self.loop_block_depths.pop();
+    }
+
+    /// Index of the loop a break/continue targets: the innermost one, or the one with the label
+    fn loop_target(&self, label: &Option<crate::frontend::parser::ast::Identifier>) -> Option<usize> {
+        match label {
+            Some(label) => self.loop_labels.iter().rposition(|(name, _)| name.as_deref() == Some(label.name.as_str())),
+            None => self.loop_labels.len().checked_sub(1),
+        }
     }
 
-    fn compile_break_statement(&mut self) {
-        if !self.break_stack.is_empty() {
+    fn compile_break_statement(&mut self, break_stmt: crate::frontend::parser::ast::BreakStatement) {
+        if let Some(target) = self.loop_target(&break_stmt.label) {
+            // A `loop` expression produces the break value; other loops have none
+            if self.loop_labels[target].1 {
+                match break_stmt.value {
+                    Some(value) => self.compile_expression(value),
+                    None => { self.emit(IR::PushNull); },
+                }
+            } else if break_stmt.value.is_some() {
+                self.errors.push("Break with a value outside of `loop`".to_string());
+            }
+            self.emit_try_exits(target);
+            self.emit_scope_exits(target);
             self.emit(IR::Jump(0)); // Placeholder, will be patched
             let pos = self.ir.len() - 1;
-            if let Some(break_positions) = self.break_stack.last_mut() {
-                break_positions.push(pos);
-            }
+            self.break_stack[target].push(pos);
         } else {
             self.errors.push("Break statement outside of loop".to_string());
         }
     }
 
-    fn compile_continue_statement(&mut self) {
-        if !self.continue_stack.is_empty() {
+    fn compile_continue_statement(&mut self, continue_stmt: crate::frontend::parser::ast::ContinueStatement) {
+        if let Some(target) = self.loop_target(&continue_stmt.label) {
+            self.emit_try_exits(target);
+            self.emit_scope_exits(target);
             self.emit(IR::Jump(0)); // Placeholder, will be patched
             let pos = self.ir.len() - 1;
-            if let Some(continue_positions) = self.continue_stack.last_mut() {
-                continue_positions.push(pos);
-            }
+            self.continue_stack[target].push(pos);
         } else {
             self.errors.push("Continue statement outside of loop".to_string());
         }
     }
 
+    /// Clear the handlers of try blocks that a break/continue jumps out of
+    fn emit_try_exits(&mut self, target: usize) {
+        for _ in self.loop_try_depths[target]..self.try_depth {
+            self.emit(IR::ClearTryCatch);
+        }
+    }
+
+    /// Close the block scopes that a break/continue jumps out of
+    fn emit_scope_exits(&mut self, target: usize) {
+        for _ in self.loop_block_depths[target]..self.block_depth {
+            self.emit(IR::ExitScope);
+        }
+    }
+
+    fn compile_try_statement(&mut self, try_stmt: crate::frontend::parser::ast::TryStatement) {
+        let catch_label = self.generate_label("catch_");
+        let end_label = self.generate_label("try_end_");
+
+        // Install the handler; its catch address is patched below
+        let setup_pos = self.emit(IR::SetupTryCatch(0));
+
+        self.try_depth += 1;
+        self.enter_block_scope();
+        for stmt in try_stmt.body.statements {
+            self.compile_statement(stmt);
+        }
+        self.leave_block_scope();
+        self.try_depth -= 1;
+
+        // Normal completion: remove the handler and skip the catch block
+        self.emit(IR::ClearTryCatch);
+        let jump_to_end_pos = self.emit(IR::Jump(0));
+
+        // The runtime jumps here with the thrown value on the stack
+        let catch_pos = self.emit_label(&catch_label);
+        self.replace_instruction(setup_pos, IR::SetupTryCatch(catch_pos));
+
+        self.enter_block_scope();
+        match try_stmt.catch_clause {
+            Some(catch_clause) => {
+                if let Some(param) = catch_clause.parameter {
+                    self.emit_declare(param.name);
+                } else {
+                    self.emit(IR::Pop);
+                }
+                for stmt in catch_clause.body.statements {
+                    self.compile_statement(stmt);
+                }
+            },
+            None => {
+                // try without catch swallows the thrown value
+                self.emit(IR::Pop);
+            }
+        }
+        self.leave_block_scope();
+
+        let end_pos = self.emit_label(&end_label);
+        self.replace_instruction(jump_to_end_pos, IR::Jump(end_pos));
+    }
+
+    fn compile_defer_statement(&mut self, defer_stmt: crate::frontend::parser::ast::DeferStatement) {
+        let defer_label = self.generate_label("defer_");
+        let end_label = self.generate_label("defer_end_");
+
+        // Register the deferred code, then skip over it
+        let push_pos = self.emit(IR::PushDefer(0));
+        let jump_pos = self.emit(IR::Jump(0));
+
+        let defer_start = self.emit_label(&defer_label);
+        self.replace_instruction(push_pos, IR::PushDefer(defer_start));
+
+        // The runtime runs the code in a block scope of its own once the function exits,
+        // when no try block of the function is active any more
+        let old_try_depth = std::mem::replace(&mut self.try_depth, 0);
+        let old_block_depth = std::mem::replace(&mut self.block_depth, 1);
+        self.enter_scope();
+        for stmt in defer_stmt.body.statements {
+            self.compile_statement(stmt);
+        }
+        self.leave_scope();
+        self.try_depth = old_try_depth;
+        self.block_depth = old_block_depth;
+        self.emit(IR::EndDefer);
+
+        let end_pos = self.emit_label(&end_label);
+        self.replace_instruction(jump_pos, IR::Jump(end_pos));
+    }
+
     fn compile_match_statement(&mut self, match_stmt: crate::frontend::parser::ast::MatchStatement) {
-        // Compile the match expression
-        self.compile_expression(match_stmt.expression);
-        
+        self.compile_match(match_stmt.expression, match_stmt.arms);
+        // Statement form discards the produced value
+        self.emit(IR::Pop);
+    }
+
+    /// Compile a match into a chain of pattern tests. Leaves the value of the
+    /// selected arm (or null when no arm matched) on the stack.
+    fn compile_match(&mut self, subject: Expression, arms: Vec<crate::frontend::parser::ast::MatchArm>) {
+        use crate::frontend::parser::ast::MatchArmBody;
+
+        // Keep the subject in a temporary so patterns can project into it
+        let subject_var = self.generate_label("__match_");
+        let end_label = self.generate_label("match_end_");
+        self.compile_expression(subject);
+        self.symbol_table.define(&subject_var);
+        self.emit(IR::StoreVar(subject_var.clone()));
+
         let mut jump_to_end_positions = Vec::new();
-        
-        // Compile each match arm
-        for arm in match_stmt.arms {
-            // Duplicate the match value for comparison
-            self.emit(IR::Dup);
-            
-            // Compile the pattern
-            self.compile_pattern(arm.pattern);
-            
-            // Compare the values
-            self.emit(IR::Equal);
-            
-            // Jump to next arm if not equal
-            self.emit(IR::JumpIfFalse(0));
-            let jump_to_next_pos = self.ir.len() - 1;
-            
-            // Pop the duplicated match value since we found a match
-            self.emit(IR::Pop);
-            
-            // Compile the arm body
-            self.compile_expression(arm.body);
-            self.emit(IR::Pop); // Discard result
-            
-            // Jump to end
-            self.emit(IR::Jump(0));
-            jump_to_end_positions.push(self.ir.len() - 1);
-            
-            // Update the jump to next arm
+
+        for arm in arms {
+            let mut fail_jumps = Vec::new();
+            let mut bindings = Vec::new();
+            self.compile_pattern(&arm.pattern, &subject_var, &[], &mut fail_jumps, &mut bindings);
+
+            // The arm's bindings only exist once its pattern has matched
+            self.enter_block_scope();
+            self.emit_pattern_bindings(&subject_var, bindings);
+
+            let guard_fail_jump = arm.guard.map(|guard| {
+                self.compile_expression(guard);
+                self.emit(IR::JumpIfFalse(0))
+            });
+
+            match arm.body {
+                MatchArmBody::Expression(expr) => self.compile_expression(expr),
+                MatchArmBody::Block(block) => {
+                    let mut statements = block.statements;
+                    // A trailing expression statement is the value of the arm
+                    let tail = match statements.last() {
+                        Some(Statement::ExpressionStatement(_)) => statements.pop(),
+                        _ => None,
+                    };
+                    for stmt in statements {
+                        self.compile_statement(stmt);
+                    }
+                    match tail {
+                        Some(Statement::ExpressionStatement(expr_stmt)) => {
+                            self.compile_expression(expr_stmt.expression);
+                        }
+                        _ => {
+                            self.emit(IR::PushNull);
+                        }
+                    }
+                }
+            }
+            self.leave_block_scope();
+
+            jump_to_end_positions.push(self.emit(IR::Jump(0)));
+
+            // A failed guard leaves the arm's scope before trying the next arm
+            if let Some(pos) = guard_fail_jump {
+                let guard_fail_pos = self.emit(IR::ExitScope);
+                self.replace_instruction(pos, IR::JumpIfFalse(guard_fail_pos));
+            }
+
+            // Failed tests fall through to the next arm
             let next_arm_pos = self.ir.len();
-            self.replace_instruction(jump_to_next_pos, IR::JumpIfFalse(next_arm_pos));
+            for pos in fail_jumps {
+                self.replace_instruction(pos, IR::JumpIfFalse(next_arm_pos));
+            }
         }
-        
-        // Pop the match value if no arm matched
-        self.emit(IR::Pop);
-        
-        // Update all jumps to end
-        let end_pos = self.ir.len();
-        for jump_pos in jump_to_end_positions {
-            self.replace_instruction(jump_pos, IR::Jump(end_pos));
+
+        // No arm matched
+        self.emit(IR::PushNull);
+
+        let end_pos = self.emit_label(&end_label);
+        for pos in jump_to_end_positions {
+            self.replace_instruction(pos, IR::Jump(end_pos));
+        }
+    }
+
+    /// Push the part of the match subject addressed by `path`
+    fn emit_pattern_path(&mut self, subject_var: &str, path: &[PatternStep]) {
+        self.emit(IR::LoadVar(subject_var.to_string()));
+        for step in path {
+            match step {
+                PatternStep::Index(index) => {
+                    self.emit(IR::PushInteger(*index as i64));
+                    self.emit(IR::GetKey);
+                }
+                PatternStep::Field(name) => {
+                    self.emit(IR::PushString(name.clone()));
+                    self.emit(IR::GetKey);
+                }
+                PatternStep::Payload(index) => {
+                    self.emit(IR::GetVariantField(*index));
+                }
+            }
+        }
+    }
+
+    fn emit_pattern_bindings(&mut self, subject_var: &str, bindings: Vec<(String, Vec<PatternStep>)>) {
+        for (name, path) in bindings {
+            self.emit_pattern_path(subject_var, &path);
+            self.emit_declare(name);
         }
     }
 
-    fn compile_pattern(&mut self, pattern: crate::frontend::parser::ast::Pattern) {
+    /// Emit the tests for `pattern`. Every failing test jumps to a position
+    /// recorded in `fail_jumps`; variables it binds are collected in `bindings`
+    /// and stored only once the whole pattern has matched.
+    fn compile_pattern(
+        &mut self,
+        pattern: &crate::frontend::parser::ast::Pattern,
+        subject_var: &str,
+        path: &[PatternStep],
+        fail_jumps: &mut Vec<usize>,
+        bindings: &mut Vec<(String, Vec<PatternStep>)>,
+    ) {
+        use crate::frontend::parser::ast::Pattern;
+
+        let sub_path = |step: PatternStep| {
+            let mut sub_path = path.to_vec();
+            sub_path.push(step);
+            sub_path
+        };
+
         match pattern {
-            crate::frontend::parser::ast::Pattern::Literal(expr) => {
-                self.compile_expression(expr);
+            Pattern::Wildcard => {},
+            Pattern::Identifier(ident) => {
+                bindings.push((ident.name.clone(), path.to_vec()));
+            },
+            Pattern::Literal(expr) => {
+                self.emit_pattern_path(subject_var, path);
+                self.compile_expression(expr.clone());
+                self.emit(IR::Equal);
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
             },
-            crate::frontend::parser::ast::Pattern::Identifier(ident) => {
-                self.emit(IR::LoadVar(ident.name));
+            Pattern::Range { start, end, inclusive } => {
+                self.emit_pattern_path(subject_var, path);
+                self.compile_expression((**start).clone());
+                self.emit(IR::GreaterEqual);
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
+
+                self.emit_pattern_path(subject_var, path);
+                self.compile_expression((**end).clone());
+                self.emit(if *inclusive { IR::LessEqual } else { IR::LessThan });
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
             },
-            crate::frontend::parser::ast::Pattern::Wildcard => {
-                // Wildcard matches anything, push true
-                self.emit(IR::PushBoolean(true));
+            Pattern::Variant { enum_name, variant, payload } => {
+                let variant_name = match enum_name {
+                    Some(enum_name) => format!("{}.{}", enum_name.name, variant.name),
+                    None => variant.name.clone(),
+                };
+                self.emit_pattern_path(subject_var, path);
+                self.emit(IR::MatchVariant(variant_name, payload.len()));
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
+
+                for (i, sub_pattern) in payload.iter().enumerate() {
+                    self.compile_pattern(sub_pattern, subject_var, &sub_path(PatternStep::Payload(i)), fail_jumps, bindings);
+                }
+            },
+            Pattern::Struct { name, fields } => {
+                self.emit_pattern_path(subject_var, path);
+                self.emit(IR::MatchStruct(name.name.clone()));
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
+
+                for field in fields {
+                    self.compile_pattern(&field.pattern, subject_var, &sub_path(PatternStep::Field(field.name.name.clone())), fail_jumps, bindings);
+                }
+            },
+            Pattern::Array { elements, has_rest } => {
+                self.emit_pattern_path(subject_var, path);
+                self.emit(IR::MatchLength(elements.len(), *has_rest));
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
+
+                for (i, element) in elements.iter().enumerate() {
+                    self.compile_pattern(element, subject_var, &sub_path(PatternStep::Index(i)), fail_jumps, bindings);
+                }
+            },
+            Pattern::Tuple(elements) => {
+                self.emit_pattern_path(subject_var, path);
+                self.emit(IR::MatchLength(elements.len(), false));
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
+
+                for (i, element) in elements.iter().enumerate() {
+                    self.compile_pattern(element, subject_var, &sub_path(PatternStep::Index(i)), fail_jumps, bindings);
+                }
+            },
+            Pattern::Or(alternatives) => {
+                // Each alternative binds its own variables before joining
+                let mut success_jumps = Vec::new();
+                for alternative in alternatives {
+                    let mut alternative_fails = Vec::new();
+                    let mut alternative_bindings = Vec::new();
+                    self.compile_pattern(alternative, subject_var, path, &mut alternative_fails, &mut alternative_bindings);
+                    self.emit_pattern_bindings(subject_var, alternative_bindings);
+                    success_jumps.push(self.emit(IR::Jump(0)));
+
+                    let next_pos = self.ir.len();
+                    for pos in alternative_fails {
+                        self.replace_instruction(pos, IR::JumpIfFalse(next_pos));
+                    }
+                }
+
+                // No alternative matched
+                self.emit(IR::PushBoolean(false));
+                fail_jumps.push(self.emit(IR::JumpIfFalse(0)));
+
+                let success_pos = self.ir.len();
+                for pos in success_jumps {
+                    self.replace_instruction(pos, IR::Jump(success_pos));
+                }
             },
         }
     }
@@ -911,19 +1362,32 @@ impl Compiler {
     }
 
     fn compile_enum_declaration(&mut self, enum_decl: crate::frontend::parser::ast::EnumDeclaration) {
-        // Register the enum type name as a variable so it can be used in expressions
+        // Enums are compile-time only: variants are constructed with MakeEnum
         if !self.clean_output {
             println!("Registering enum type: {}", enum_decl.name.name);
         }
-        
-        // Register the enum type itself
-        let enum_name = enum_decl.name.name.clone();
-        self.symbol_table.define(&enum_name);
-        self.emit(IR::PushString(format!("enum_{}", enum_name)));
-        self.emit(IR::StoreVar(enum_name.clone()));
-        
-        // Store enum metadata for future instantiation
-        self.symbol_table.define(&format!("enum_{}", enum_name));
+
+        self.register_enum_variants(&enum_decl);
+    }
+
+    fn register_enum_variants(&mut self, enum_decl: &crate::frontend::parser::ast::EnumDeclaration) {
+        let variants = enum_decl.variants.iter().map(|v| v.name.name.clone()).collect();
+        self.enum_variants.insert(enum_decl.name.name.clone(), variants);
+    }
+
+    fn register_trait_defaults(&mut self, trait_decl: &crate::frontend::parser::ast::TraitDeclaration) {
+        let defaults = trait_decl.methods.iter()
+            .filter(|m| m.default_body.is_some())
+            .cloned()
+            .collect();
+        self.trait_defaults.insert(trait_decl.name.name.clone(), defaults);
+    }
+
+    /// Check whether `enum_name.variant` names a declared enum variant
+    fn is_enum_variant(&self, enum_name: &str, variant: &str) -> bool {
+        self.enum_variants
+            .get(enum_name)
+            .is_some_and(|variants| variants.iter().any(|v| v == variant))
     }
 
     fn compile_impl_block(&mut self, impl_block: crate::frontend::parser::ast::ImplBlock) {
@@ -933,19 +1397,28 @@ impl Compiler {
             println!("Compiling impl block for type: {}", type_name);
         }
         
+        // Default trait methods the impl does not override become methods of the type,
+        // so `MethodCall` finds them by the receiver's runtime type like any other method
+        let inherited: Vec<_> = impl_block.trait_name.as_ref()
+            .and_then(|trait_name| self.trait_defaults.get(&trait_name.name))
+            .map(|defaults| defaults.iter()
+                .filter(|d| !impl_block.methods.iter().any(|m| m.name.name == d.name.name))
+                .cloned()
+                .collect())
+            .unwrap_or_default();
+        for method in inherited {
+            let method_name = format!("{}.{}", type_name, method.name.name);
+            let body = method.default_body.map(|b| b.statements).unwrap_or_default();
+            self.compile_function_declaration(method_name, method.parameters, body);
+        }
+        
         // Compile each method in the impl block
         for method in impl_block.methods {
             // Use dot notation for method names (Person.new, not Person::new)
             let method_name = format!("{}.{}", type_name, method.name.name);
-            let mut parameters: Vec<String> = Vec::new();
-            
-            // Add parameters (including self if not static)
-            for param in &method.parameters {
-                parameters.push(param.name.name.clone());
-            }
             
-            // Compile the method as a function
-            self.compile_function_declaration(method_name, parameters, method.body.statements);
+            // Compile the method as a function (parameters include self if not static)
+            self.compile_function_declaration(method_name, method.parameters, method.body.statements);
         }
     }
 
@@ -979,11 +1452,17 @@ impl Compiler {
                 self.emit(IR::PushString(char_lit.value.to_string()));
             },
             Expression::IntegerLiteral(int_lit) => {
-                self.emit(IR::PushInteger(int_lit.value));
+                match i64::try_from(&int_lit.value) {
+                    Ok(value) => self.emit(IR::PushInteger(value)),
+                    Err(_) => self.emit(IR::PushBigInt(int_lit.value)),
+                };
             },
             Expression::FloatLiteral(float_lit) => {
                 self.emit(IR::PushNumber(float_lit.value));
             },
+            Expression::DecimalLiteral(decimal_lit) => {
+                self.emit(IR::PushDecimal(decimal_lit.value));
+            },
             Expression::BooleanLiteral(bool_lit) => {
                 self.emit(IR::PushBoolean(bool_lit.value));
             },
@@ -991,26 +1470,57 @@ impl Compiler {
                 self.emit(IR::PushNull);
             },
             Expression::CallExpression(call_expr) => {
-                // Compile arguments
-                for arg in &call_expr.arguments {
-                    self.compile_expression(arg.clone());
+                // Callees that produce a function value (e.g. make_adder(5)(3)) are
+                // evaluated first so the runtime finds them below the arguments
+                let calls_value = !matches!(*call_expr.callee, Expression::Identifier(_) | Expression::MemberExpression(_));
+                if calls_value {
+                    self.compile_expression(*call_expr.callee.clone());
                 }
 
+                // Compile arguments
+                let arg_count = call_expr.arguments.len();
+                let spread = self.compile_arguments(call_expr.arguments);
+
                 // Handle different types of callees
-                match *call_expr.callee {
-                    Expression::Identifier(ident) => {
+                match (*call_expr.callee, spread) {
+                    (Expression::Identifier(ident), false) => {
                         // Regular function call
-                        self.emit(IR::Call(ident.name, call_expr.arguments.len()));
+                        self.emit(IR::Call(ident.name, arg_count));
                     },
-                    Expression::MemberExpression(member_expr) => {
+                    (Expression::Identifier(ident), true) => {
+                        self.emit(IR::CallSpread(ident.name));
+                    },
+                    (Expression::MemberExpression(member_expr), false) => {
                         // Method call like input().toint()
-                        self.compile_method_call(member_expr, call_expr.arguments.len());
+                        self.compile_method_call(member_expr, arg_count);
                     },
-                    _ => {
-                        self.errors.push("Invalid function call".to_string());
+                    (Expression::MemberExpression(member_expr), true) => {
+                        self.errors.push(format!("Cannot spread arguments into method '{}'", member_expr.property.name));
+                    },
+                    (_, false) => {
+                        self.emit(IR::CallValue(arg_count));
+                    },
+                    (_, true) => {
+                        self.emit(IR::CallValueSpread);
                     }
                 }
             },
+            Expression::BinaryExpression(bin_expr) if bin_expr.operator == crate::frontend::parser::ast::BinaryOperator::NullCoalesce => {
+                let rhs_label = self.generate_label("coalesce_rhs_");
+                let end_label = self.generate_label("coalesce_end_");
+
+                // The right side is only evaluated when the left is null or None
+                self.compile_expression(*bin_expr.left);
+                let jump_if_null_pos = self.emit(IR::JumpIfNull(0));
+                let jump_to_end_pos = self.emit(IR::Jump(0));
+
+                let rhs_pos = self.emit_label(&rhs_label);
+                self.compile_expression(*bin_expr.right);
+
+                let end_pos = self.emit_label(&end_label);
+                self.replace_instruction(jump_if_null_pos, IR::JumpIfNull(rhs_pos));
+                self.replace_instruction(jump_to_end_pos, IR::Jump(end_pos));
+            },
             Expression::BinaryExpression(bin_expr) => {
                 self.compile_expression(*bin_expr.left);
                 self.compile_expression(*bin_expr.right);
@@ -1063,7 +1573,7 @@ impl Compiler {
                         // ++var: increment then use
                         if let Expression::Identifier(ident) = *unary_expr.operand {
                             self.emit(IR::LoadVar(ident.name.clone()));
-                            self.emit(IR::PushNumber(1.0));
+                            self.emit(IR::PushInteger(1));
                             self.emit(IR::Add);
                             self.emit(IR::Dup); // Duplicate for return value
                             self.emit(IR::StoreVar(ident.name));
@@ -1076,7 +1586,7 @@ impl Compiler {
                         if let Expression::Identifier(ident) = *unary_expr.operand {
                             self.emit(IR::LoadVar(ident.name.clone()));
                             self.emit(IR::Dup); // Keep original value for return
-                            self.emit(IR::PushNumber(1.0));
+                            self.emit(IR::PushInteger(1));
                             self.emit(IR::Add);
                             self.emit(IR::StoreVar(ident.name));
                         } else {
@@ -1087,7 +1597,7 @@ impl Compiler {
                         // --var: decrement then use
                         if let Expression::Identifier(ident) = *unary_expr.operand {
                             self.emit(IR::LoadVar(ident.name.clone()));
-                            self.emit(IR::PushNumber(1.0));
+                            self.emit(IR::PushInteger(1));
                             self.emit(IR::Subtract);
                             self.emit(IR::Dup); // Duplicate for return value
                             self.emit(IR::StoreVar(ident.name));
@@ -1100,7 +1610,7 @@ impl Compiler {
                         if let Expression::Identifier(ident) = *unary_expr.operand {
                             self.emit(IR::LoadVar(ident.name.clone()));
                             self.emit(IR::Dup); // Keep original value for return
-                            self.emit(IR::PushNumber(1.0));
+                            self.emit(IR::PushInteger(1));
                             self.emit(IR::Subtract);
                             self.emit(IR::StoreVar(ident.name));
                         } else {
@@ -1122,12 +1632,17 @@ impl Compiler {
                 self.emit(IR::Call("create_range".to_string(), 3)); // start, end, inclusive
             },
             Expression::ArrayLiteral(array_lit) => {
-                // Compile all array elements
-                for element in &array_lit.elements {
-                    self.compile_expression(element.clone());
+                let element_count = array_lit.elements.len();
+                if let Some(Expression::NamedArgument(named)) = array_lit.elements.iter()
+                    .find(|element| matches!(element, Expression::NamedArgument(_)))
+                {
+                    self.errors.push(format!("Named argument '{}' can only be used in a function call", named.name.name));
+                    return;
+                }
+                if !self.compile_arguments(array_lit.elements) {
+                    // Create array with the specified number of elements
+                    self.emit(IR::CreateArray(element_count));
                 }
-                // Create array with the specified number of elements
-                self.emit(IR::CreateArray(array_lit.elements.len()));
             },
             Expression::MapLiteral(map_lit) => {
                 // Compile all map key-value pairs
@@ -1138,11 +1653,18 @@ impl Compiler {
                 // Create map with the specified number of pairs
                 self.emit(IR::CreateMap(map_lit.pairs.len()));
             },
+            Expression::TupleLiteral(tuple_lit) => {
+                let element_count = tuple_lit.elements.len();
+                for element in tuple_lit.elements {
+                    self.compile_expression(element);
+                }
+                self.emit(IR::CreateTuple(element_count));
+            },
+            Expression::TupleIndexExpression(tuple_index) => {
+                self.compile_expression(*tuple_index.object);
+                self.emit(IR::GetTupleField(tuple_index.index));
+            },
             Expression::StructInstantiation(struct_inst) => {
-                // For now, compile struct instantiation as a map-like structure
-                // Push struct type name first
-                self.emit(IR::PushString(struct_inst.name.name.clone()));
-                
                 // Compile all field values
                 for field in &struct_inst.fields {
                     self.emit(IR::PushString(field.name.name.clone())); // field name
@@ -1150,7 +1672,7 @@ impl Compiler {
                 }
                 
                 // Create struct with the specified number of fields
-                self.emit(IR::CreateMap(struct_inst.fields.len() + 1)); // +1 for type name
+                self.emit(IR::CreateStruct(struct_inst.name.name.clone(), struct_inst.fields.len()));
             },
             Expression::QualifiedStructInstantiation(qualified_struct_inst) => {
                 // Compile qualified struct instantiation (e.g., module.Type { ... })
@@ -1168,9 +1690,6 @@ impl Compiler {
                     "unknown".to_string()
                 };
                 
-                // Push struct type name first
-                self.emit(IR::PushString(type_name));
-                
                 // Compile all field values
                 for field in &qualified_struct_inst.fields {
                     self.emit(IR::PushString(field.name.name.clone())); // field name
@@ -1178,11 +1697,11 @@ impl Compiler {
                 }
                 
                 // Create struct with the specified number of fields
-                self.emit(IR::CreateMap(qualified_struct_inst.fields.len() + 1)); // +1 for type name
+                self.emit(IR::CreateStruct(type_name, qualified_struct_inst.fields.len()));
             },
             Expression::AssignmentExpression(assign_expr) => {
                 // Handle assignment to identifier
-                if let Expression::Identifier(ident) = *assign_expr.left {
+                if let Expression::Identifier(ident) = &*assign_expr.left {
                     let qualified_name = self.qualify_var_name(&ident.name);
                     
                     match assign_expr.operator {
@@ -1262,6 +1781,10 @@ impl Compiler {
                             self.emit(IR::StoreVar(qualified_name));
                         },
                     }
+                } else if let Some((root, steps)) = self.place_path(*assign_expr.left) {
+                    // Field and element stores: p.address.city = v, self.items[i] += v
+                    let operator = Self::compound_operator(&assign_expr.operator);
+                    self.compile_place_store(root, steps, operator, *assign_expr.right);
                 } else {
                     self.errors.push("Invalid assignment target".to_string());
                 }
@@ -1271,12 +1794,66 @@ impl Compiler {
                 self.compile_expression(*method_call.object);
                 
                 // Compile arguments
-                for arg in &method_call.arguments {
-                    self.compile_expression(arg.clone());
+                let arg_count = method_call.arguments.len();
+                if self.compile_arguments(method_call.arguments) {
+                    self.emit(IR::MethodCallSpread(method_call.method.name));
+                } else {
+                    // Call the method (object is already on stack as first argument)
+                    self.emit(IR::MethodCall(method_call.method.name, arg_count + 1)); // +1 for self
                 }
-                
-                // Call the method (object is already on stack as first argument)
-                self.emit(IR::MethodCall(method_call.method.name, method_call.arguments.len() + 1)); // +1 for self
+            },
+            Expression::MatchExpression(match_expr) => {
+                self.compile_match(*match_expr.expression, match_expr.arms);
+            },
+            Expression::LoopExpression(loop_expr) => {
+                self.compile_loop_expression(loop_expr);
+            },
+            Expression::PropagateExpression(propagate) => {
+                let return_label = self.generate_label("propagate_return_");
+                let end_label = self.generate_label("propagate_end_");
+
+                // Err/None jumps to the return below; anything else is unwrapped in place
+                self.compile_expression(*propagate.expression);
+                let propagate_pos = self.emit(IR::PropagateError(0));
+                let jump_to_end_pos = self.emit(IR::Jump(0));
+
+                let return_pos = self.emit_label(&return_label);
+                self.emit(IR::Return);
+
+                let end_pos = self.emit_label(&end_label);
+                self.replace_instruction(propagate_pos, IR::PropagateError(return_pos));
+                self.replace_instruction(jump_to_end_pos, IR::Jump(end_pos));
+            },
+            Expression::OptionalChainExpression(chain) => {
+                let null_label = self.generate_label("chain_null_");
+                let end_label = self.generate_label("chain_end_");
+
+                // A null or None object skips the access and yields null
+                self.compile_expression(*chain.object);
+                let jump_if_null_pos = self.emit(IR::JumpIfNull(0));
+
+                match chain.arguments {
+                    Some(arguments) => {
+                        let arg_count = arguments.len();
+                        if self.compile_arguments(arguments) {
+                            self.emit(IR::MethodCallSpread(chain.property.name));
+                        } else {
+                            self.emit(IR::MethodCall(chain.property.name, arg_count + 1)); // +1 for self
+                        }
+                    }
+                    None => {
+                        self.emit(IR::PushString(chain.property.name));
+                        self.emit(IR::GetKey);
+                    }
+                }
+                let jump_to_end_pos = self.emit(IR::Jump(0));
+
+                let null_pos = self.emit_label(&null_label);
+                self.emit(IR::PushNull);
+
+                let end_pos = self.emit_label(&end_label);
+                self.replace_instruction(jump_if_null_pos, IR::JumpIfNull(null_pos));
+                self.replace_instruction(jump_to_end_pos, IR::Jump(end_pos));
             },
             Expression::SelfExpression(_) => {
                 // Load the 'self' variable from current scope
@@ -1294,11 +1871,9 @@ impl Compiler {
                         return;
                     }
                     
-                    // Check if this looks like an enum type (starts with uppercase)
-                    if module_ident.name.chars().next().unwrap_or('a').is_uppercase() {
-                        // This is likely enum variant access: EnumType.Variant
-                        let enum_variant = format!("{}::{}", module_ident.name, member_expr.property.name);
-                        self.emit(IR::PushString(enum_variant));
+                    // Unit enum variant: Color.Red
+                    if self.is_enum_variant(&module_ident.name, &member_expr.property.name) {
+                        self.emit(IR::MakeEnum(module_ident.name.clone(), member_expr.property.name, 0));
                         return;
                     }
                 }
@@ -1311,6 +1886,16 @@ impl Compiler {
             Expression::ModuleCallExpression(module_call) => {
                 // Check if this is a variable (instance method call) or a type/module (static method/module call)
                 let module_name = &module_call.module.name;
+
+                // Enum variant constructor: Shape.Circle(2.0)
+                if self.is_enum_variant(module_name, &module_call.function.name) {
+                    let arg_count = module_call.arguments.len();
+                    for arg in module_call.arguments {
+                        self.compile_expression(arg);
+                    }
+                    self.emit(IR::MakeEnum(module_call.module.name, module_call.function.name, arg_count));
+                    return;
+                }
                 
                 // Check if it's a variable by trying to resolve it
                 let is_variable = self.symbol_table.resolve(module_name).is_some();
@@ -1321,41 +1906,236 @@ impl Compiler {
                     self.emit(IR::LoadVar(module_name.clone()));
                     
                     // Compile arguments
-                    for arg in &module_call.arguments {
-                        self.compile_expression(arg.clone());
+                    let arg_count = module_call.arguments.len();
+                    if self.compile_arguments(module_call.arguments) {
+                        self.emit(IR::MethodCallSpread(module_call.function.name));
+                    } else {
+                        // Use MethodCall instruction (runtime will extract type from object)
+                        self.emit(IR::MethodCall(module_call.function.name, arg_count + 1)); // +1 for self
                     }
-                    
-                    // Use MethodCall instruction (runtime will extract type from object)
-                    self.emit(IR::MethodCall(module_call.function.name.clone(), module_call.arguments.len() + 1)); // +1 for self
                 } else {
                     // This is a static method call (Person.new) or module call (utils.add)
-                    // Compile arguments
-                    for arg in &module_call.arguments {
-                        self.compile_expression(arg.clone());
-                    }
-                    
                     // Create a qualified function name using dot notation
                     let qualified_name = format!("{}.{}", module_name, module_call.function.name);
                     
-                    // Call the function with the qualified name
-                    self.emit(IR::Call(qualified_name, module_call.arguments.len()));
+                    // Compile arguments
+                    let arg_count = module_call.arguments.len();
+                    if self.compile_arguments(module_call.arguments) {
+                        self.emit(IR::CallSpread(qualified_name));
+                    } else {
+                        // Call the function with the qualified name
+                        self.emit(IR::Call(qualified_name, arg_count));
+                    }
                 }
             },
             Expression::GroupingExpression(grouping) => {
                 // Parenthesized expression - just compile the inner expression
                 self.compile_expression(*grouping.expression);
             },
+            Expression::SliceExpression(slice_expr) => {
+                // Missing bounds are passed as null and default to the ends
+                self.compile_expression(*slice_expr.object);
+                for bound in [slice_expr.start, slice_expr.end] {
+                    match bound {
+                        Some(bound) => self.compile_expression(*bound),
+                        None => {
+                            self.emit(IR::PushNull);
+                        }
+                    }
+                }
+                self.emit(IR::Slice(slice_expr.inclusive));
+            },
             Expression::IndexExpression(index_expr) => {
                 // Array or map indexing: object[index]
                 self.compile_expression(*index_expr.object);
                 self.compile_expression(*index_expr.index);
-                self.emit(IR::GetKey); // Use GetKey for both array and map access
+                self.emit(IR::GetIndex); // Like GetKey, but structs can overload it with index()
+            },
+            Expression::LambdaExpression(lambda) => {
+                self.compile_lambda_expression(lambda);
+            },
+            Expression::SpreadExpression(_) => {
+                self.errors.push("Spread '...' can only be used in call arguments or array literals".to_string());
+            },
+            Expression::NamedArgument(named) => {
+                self.errors.push(format!("Named argument '{}' can only be used in a function call", named.name.name));
             },
             // Note: All expression types are handled above, this case is unreachable
             // but kept for future expression types that might be added
         }
     }
     
+    /// Split an assignment target into the variable it is rooted at and the field and
+    /// index steps leading from that variable to the stored-to element
+    fn place_path(&self, target: Expression) -> Option<(String, Vec<PlaceStep>)> {
+        match target {
+            Expression::Identifier(ident) => Some((self.qualify_var_name(&ident.name), Vec::new())),
+            Expression::SelfExpression(_) => Some(("self".to_string(), Vec::new())),
+            Expression::GroupingExpression(grouping) => self.place_path(*grouping.expression),
+            Expression::MemberExpression(member_expr) => {
+                // Module variables live under their qualified name
+                if let Expression::Identifier(module_ident) = &*member_expr.object {
+                    let qualified_name = format!("{}.{}", module_ident.name, member_expr.property.name);
+                    if self.symbol_table.resolve(&qualified_name).is_some() {
+                        return Some((qualified_name, Vec::new()));
+                    }
+                }
+                let (root, mut steps) = self.place_path(*member_expr.object)?;
+                steps.push(PlaceStep::Field(member_expr.property.name));
+                Some((root, steps))
+            },
+            Expression::IndexExpression(index_expr) => {
+                let (root, mut steps) = self.place_path(*index_expr.object)?;
+                steps.push(PlaceStep::Index(*index_expr.index));
+                Some((root, steps))
+            },
+            _ => None,
+        }
+    }
+
+    /// Instruction combining the current and new value of a compound assignment
+    fn compound_operator(operator: &crate::frontend::parser::ast::AssignmentOperator) -> Option<IR> {
+        use crate::frontend::parser::ast::AssignmentOperator;
+        match operator {
+            AssignmentOperator::Assign => None,
+            AssignmentOperator::AddAssign => Some(IR::Add),
+            AssignmentOperator::SubtractAssign => Some(IR::Subtract),
+            AssignmentOperator::MultiplyAssign => Some(IR::Multiply),
+            AssignmentOperator::DivideAssign => Some(IR::Divide),
+            AssignmentOperator::ModuloAssign => Some(IR::Modulo),
+            AssignmentOperator::BitwiseAndAssign => Some(IR::BitwiseAnd),
+            AssignmentOperator::BitwiseOrAssign => Some(IR::BitwiseOr),
+            AssignmentOperator::BitwiseXorAssign => Some(IR::BitwiseXor),
+            AssignmentOperator::LeftShiftAssign => Some(IR::LeftShift),
+            AssignmentOperator::RightShiftAssign => Some(IR::RightShift),
+        }
+    }
+
+    /// Store into a field or element path. Every container along the path is read,
+    /// updated with its new inner value and stored back, ending with the root variable.
+    fn compile_place_store(&mut self, root: String, steps: Vec<PlaceStep>, operator: Option<IR>, value: Expression) {
+        self.emit(IR::LoadVar(root.clone()));
+
+        // Leave each container and its key on the stack, innermost on top
+        let depth = steps.len();
+        let mut setters = Vec::with_capacity(depth);
+        for (i, step) in steps.into_iter().enumerate() {
+            let setter = match step {
+                PlaceStep::Field(name) => {
+                    self.emit(IR::PushString(name));
+                    IR::SetKey
+                },
+                PlaceStep::Index(index) => {
+                    self.compile_expression(index);
+                    IR::SetIndex
+                },
+            };
+            setters.push(setter);
+            if i + 1 < depth {
+                self.emit(IR::Dup2);
+                self.emit(IR::GetKey);
+            }
+        }
+
+        match operator {
+            Some(operator) => {
+                self.emit(IR::Dup2);
+                self.emit(IR::GetKey);
+                self.compile_expression(value);
+                self.emit(operator);
+            },
+            None => self.compile_expression(value),
+        }
+
+        // Write the new values back out to the root
+        for setter in setters.into_iter().rev() {
+            self.emit(setter);
+        }
+        self.emit(IR::StoreVar(root));
+    }
+
+    /// Append every item of an iterable to the array on top of the stack
+    fn compile_spread(&mut self, iterable: Expression) {
+        if matches!(iterable, Expression::ArrayLiteral(_)) {
+            self.compile_expression(iterable);
+            self.emit(IR::ExtendArray);
+            return;
+        }
+
+        let iter_var = self.generate_label("spread_iter_");
+        self.compile_expression(iterable);
+        self.emit(IR::GetIter);
+        self.emit(IR::StoreVar(iter_var.clone()));
+
+        let loop_start = self.ir.len();
+        self.emit(IR::IterNext(iter_var.clone(), 0));
+        self.emit(IR::CreateArray(1));
+        self.emit(IR::ExtendArray);
+        self.emit(IR::Jump(loop_start));
+        let end_pos = self.ir.len();
+        self.replace_instruction(loop_start, IR::IterNext(iter_var, end_pos));
+    }
+
+    /// Compile call arguments or array elements. Without a spread each value is pushed
+    /// individually and `false` is returned; with one, a single array holding all the
+    /// values is pushed instead and `true` is returned.
+    /// Named arguments come last and are announced to the runtime with `NamedArgs`.
+    fn compile_arguments(&mut self, arguments: Vec<Expression>) -> bool {
+        let mut names = Vec::new();
+        let has_spread = arguments.iter().any(|arg| matches!(arg, Expression::SpreadExpression(_)));
+        if has_spread {
+            self.emit(IR::CreateArray(0));
+        }
+
+        for arg in arguments {
+            let value = match arg {
+                Expression::NamedArgument(named) => {
+                    names.push(named.name.name);
+                    *named.value
+                }
+                other if !names.is_empty() => {
+                    self.errors.push("Positional arguments must come before named arguments".to_string());
+                    other
+                }
+                other => other,
+            };
+
+            match value {
+                Expression::SpreadExpression(spread) => {
+                    self.compile_spread(*spread.expression);
+                }
+                value if has_spread => {
+                    self.compile_expression(value);
+                    self.emit(IR::CreateArray(1));
+                    self.emit(IR::ExtendArray);
+                }
+                value => self.compile_expression(value),
+            }
+        }
+
+        if !names.is_empty() {
+            self.emit(IR::NamedArgs(names));
+        }
+        has_spread
+    }
+
+    /// Compile a lambda into an anonymous function and push a closure value for it
+    fn compile_lambda_expression(&mut self, lambda: crate::frontend::parser::ast::LambdaExpression) {
+        let name = self.generate_label(super::value::LAMBDA_PREFIX);
+        let body = match lambda.body {
+            crate::frontend::parser::ast::LambdaBody::Expression(expr) => {
+                vec![Statement::ReturnStatement(crate::frontend::parser::ast::ReturnStatement {
+                    value: Some(*expr),
+                })]
+            },
+            crate::frontend::parser::ast::LambdaBody::Block(block) => block.statements,
+        };
+
+        // The body is emitted inline behind a jump, just like a named function
+        self.compile_function_declaration(name.clone(), lambda.parameters, body);
+        self.emit(IR::MakeClosure(name));
+    }
+
     /// Compile method call like input().toint()
     fn compile_method_call(&mut self, member_expr: crate::frontend::parser::ast::MemberExpression, _arg_count: usize) {
         // First, compile the object (e.g., input())
@@ -3,29 +3,50 @@
 
 use std::fmt;
 
+/// A function parameter as the runtime binds call arguments to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub name: String,
+    pub has_default: bool,  // the function body fills it in when the call leaves it unbound
+    pub variadic: bool,  // collects the leftover positional arguments into an array
+}
+
 /// Intermediate representation for code generation
 /// Exact implementation from the proven old compiler design
 #[derive(Debug, Clone)]
 pub enum IR {
     // Stack operations
     PushInteger(i64),
+    PushBigInt(num_bigint::BigInt),
     PushNumber(f64),
+    PushDecimal(rust_decimal::Decimal),
     PushString(String),
     PushBoolean(bool),
     PushNull,
     Pop,
     Dup,
+    Dup2,  // duplicates the top two values, keeping their order
     Swap,
 
     // Exception handling
-    SetupTryCatch,
+    SetupTryCatch(usize),  // catch handler address
     ClearTryCatch,
     ThrowException,
 
+    // Deferred blocks
+    PushDefer(usize), // deferred code address, run when the current function exits
+    EndDefer,
+
     // Memory operations (Stack-based)
     StoreVar(String),
     LoadVar(String),
     SetGlobal(String),  // Global variable operations
+    DeclareVar(String),  // like StoreVar, but the binding ends with the innermost block scope
+    DeclareConst(String),  // like DeclareVar, but later stores to the binding are rejected
+
+    // Block scopes
+    EnterScope,
+    ExitScope,
     
     // Register-based operations for RAIE optimization
     LoadReg(u8, String),    // Load variable into register: LoadReg(reg_id, var_name)
@@ -76,9 +97,17 @@ pub enum IR {
     Jump(usize),
     JumpIfFalse(usize),
     JumpIfTrue(usize),
+    JumpIfNull(usize),  // pops null/None and jumps; otherwise unwraps Some in place
+    JumpIfDefined(String, usize),  // jumps if the variable is bound in the current call frame
     Call(String, usize),  // function name, arg count
     MethodCall(String, usize),  // method name, arg count (including self)
+    CallValue(usize),  // arg count; callee is below the arguments on the stack
+    CallSpread(String),  // like Call, but the arguments arrive as one array
+    MethodCallSpread(String),  // like MethodCall; self is below the argument array
+    CallValueSpread,  // like CallValue; callee is below the argument array
+    NamedArgs(Vec<String>),  // names the trailing arguments of the call that follows
     Return,
+    PropagateError(usize),  // return address taken for Err/None; otherwise unwraps in place
 
     // I/O operations
     Print,
@@ -87,16 +116,38 @@ pub enum IR {
 
     // Array operations
     CreateArray(usize),
+    ExtendArray,  // pops an array and appends its elements to the array below it
     GetIndex,
-    SetIndex,
+    SetIndex,  // pops value, index and object; pushes the updated object
+    Slice(bool),  // inclusive end; pops end and start (null for open bounds) and the object
+
+    // Iteration
+    GetIter,  // pops an iterable and pushes an iterator over it
+    IterNext(String, usize),  // iterator variable, end address; pushes the next item or jumps when done
+
+    // Tuple operations
+    CreateTuple(usize),
+    GetTupleField(usize),  // element index
+    UnpackTuple(usize),  // pops a tuple of this length and pushes its elements, first element on top
 
     // Map operations
     CreateMap(usize),
+    CreateStruct(String, usize),  // type name, field count; pops name-value pairs like CreateMap
     GetKey,
-    SetKey,
+    SetKey,  // pops value, key and object; pushes the updated object
+
+    // Enum operations
+    MakeEnum(String, String, usize),  // enum name, variant name, payload count
+
+    // Pattern matching
+    MatchVariant(String, usize),  // variant name, payload arity; pushes whether the value matches
+    MatchStruct(String),  // struct type name; pushes whether the value is that struct
+    MatchLength(usize, bool),  // element count, allow extra elements; pushes whether the value matches
+    GetVariantField(usize),  // payload index
 
     // Function definition
     DefineFunction(String, usize),  // function name, address
+    MakeClosure(String),  // function name; captures the current frame's locals
 
     // Labels for jumps
     Label(String),
@@ -110,14 +161,21 @@ impl fmt::Display for IR {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             IR::PushInteger(i) => write!(f, "PUSH_INT {}", i),
+            IR::PushBigInt(i) => write!(f, "PUSH_BIGINT {}", i),
+            IR::PushDecimal(d) => write!(f, "PUSH_DECIMAL {}", d),
             IR::PushNumber(n) => write!(f, "PUSH_NUM {}", n),
             IR::PushString(s) => write!(f, "PUSH_STR \"{}\"", s),
             IR::PushBoolean(b) => write!(f, "PUSH_BOOL {}", b),
             IR::PushNull => write!(f, "PUSH_NULL"),
             IR::Pop => write!(f, "POP"),
             IR::Dup => write!(f, "DUP"),
+            IR::Dup2 => write!(f, "DUP2"),
             IR::Swap => write!(f, "SWAP"),
             IR::StoreVar(name) => write!(f, "STORE {}", name),
+            IR::DeclareVar(name) => write!(f, "DECLARE {}", name),
+            IR::DeclareConst(name) => write!(f, "DECLARE_CONST {}", name),
+            IR::EnterScope => write!(f, "ENTER_SCOPE"),
+            IR::ExitScope => write!(f, "EXIT_SCOPE"),
             IR::LoadVar(name) => write!(f, "LOAD {}", name),
             IR::SetGlobal(name) => write!(f, "SET_GLOBAL {}", name),
             
@@ -159,26 +217,50 @@ impl fmt::Display for IR {
             IR::RightShift => write!(f, "RSHIFT"),
             IR::Jump(addr) => write!(f, "JUMP {}", addr),
             IR::JumpIfFalse(addr) => write!(f, "JIF {}", addr),
+            IR::GetIter => write!(f, "GET_ITER"),
+            IR::IterNext(name, addr) => write!(f, "ITER_NEXT {} {}", name, addr),
             IR::JumpIfTrue(addr) => write!(f, "JIT {}", addr),
+            IR::JumpIfNull(addr) => write!(f, "JIN {}", addr),
+            IR::JumpIfDefined(name, addr) => write!(f, "JID {} {}", name, addr),
             IR::Call(name, argc) => write!(f, "CALL {} {}", name, argc),
             IR::MethodCall(name, argc) => write!(f, "METHOD_CALL {} {}", name, argc),
+            IR::CallValue(argc) => write!(f, "CALL_VALUE {}", argc),
+            IR::CallSpread(name) => write!(f, "CALL_SPREAD {}", name),
+            IR::MethodCallSpread(name) => write!(f, "METHOD_CALL_SPREAD {}", name),
+            IR::CallValueSpread => write!(f, "CALL_VALUE_SPREAD"),
+            IR::NamedArgs(names) => write!(f, "NAMED_ARGS {}", names.join(",")),
             IR::Return => write!(f, "RET"),
+            IR::PropagateError(addr) => write!(f, "PROPAGATE {}", addr),
             IR::Print => write!(f, "PRINT"),
             IR::ReadInput => write!(f, "READ"),
             IR::Exit => write!(f, "EXIT"),
             IR::CreateArray(size) => write!(f, "ARRAY {}", size),
+            IR::ExtendArray => write!(f, "ARRAY_EXTEND"),
             IR::GetIndex => write!(f, "GET_IDX"),
             IR::SetIndex => write!(f, "SET_IDX"),
+            IR::Slice(inclusive) => write!(f, "SLICE{}", if *inclusive { " INCLUSIVE" } else { "" }),
+            IR::CreateTuple(size) => write!(f, "TUPLE {}", size),
+            IR::GetTupleField(index) => write!(f, "TUPLE_GET {}", index),
+            IR::UnpackTuple(size) => write!(f, "UNPACK {}", size),
             IR::CreateMap(size) => write!(f, "MAP {}", size),
+            IR::CreateStruct(name, size) => write!(f, "STRUCT {} {}", name, size),
             IR::GetKey => write!(f, "GET_KEY"),
             IR::SetKey => write!(f, "SET_KEY"),
             IR::DefineFunction(name, addr) => write!(f, "DEF_FN {} {}", name, addr),
+            IR::MakeClosure(name) => write!(f, "MAKE_CLOSURE {}", name),
+            IR::MakeEnum(type_name, variant, count) => write!(f, "MAKE_ENUM {}.{} {}", type_name, variant, count),
+            IR::MatchVariant(name, arity) => write!(f, "MATCH_VARIANT {} {}", name, arity),
+            IR::MatchStruct(name) => write!(f, "MATCH_STRUCT {}", name),
+            IR::MatchLength(len, at_least) => write!(f, "MATCH_LEN {} {}", len, at_least),
+            IR::GetVariantField(index) => write!(f, "GET_VARIANT_FIELD {}", index),
             IR::Label(name) => write!(f, "{}:", name),
             IR::Sleep => write!(f, "SLEEP"),
             IR::LibraryCall(lib, func, argc) => write!(f, "LIB_CALL {}.{} {}", lib, func, argc),
-            IR::SetupTryCatch => write!(f, "TRY_SETUP"),
+            IR::SetupTryCatch(addr) => write!(f, "TRY_SETUP {}", addr),
             IR::ClearTryCatch => write!(f, "TRY_CLEAR"),
             IR::ThrowException => write!(f, "THROW"),
+            IR::PushDefer(addr) => write!(f, "DEFER {}", addr),
+            IR::EndDefer => write!(f, "END_DEFER"),
         }
     }
 }
@@ -1,11 +1,79 @@
 // src/backend/execution/runtime.rs
 //! Clean runtime execution engine based on the proven old implementation
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, Write, BufRead};
 use std::{thread, time::Duration};
-use super::ir::IR;
-use super::value::Value;
+use num_integer::Integer;
+use num_traits::Zero;
+use super::ir::{ParamSpec, IR};
+use super::value::{function_display_name, IterState, Value};
+use crate::frontend::diagnostics::DiagnosticKind;
+
+/// Control flow outcome of executing a single instruction
+enum Step {
+    Next, // Continue with the following instruction
+    Jump, // `pc` was already moved (jump, call or return)
+    Exit, // Stop execution
+}
+
+/// Active try/catch handler installed by `SetupTryCatch`
+struct ExceptionHandler {
+    catch_addr: usize,
+    call_depth: usize, // call_stack length when the handler was installed
+    stack_len: usize,  // operand stack length when the handler was installed
+    scope_depth: usize, // scopes length when the handler was installed
+}
+
+/// Block scope opened by `EnterScope`. Declarations write straight into the
+/// frame's variable map; the bindings they replaced are put back on exit.
+struct BlockScope {
+    call_depth: usize, // call_stack length of the frame that owns the scope
+    shadowed: Vec<(String, Option<Value>)>, // previous binding of each name declared in the scope
+    constants_len: usize, // constants length when the scope was entered
+}
+
+/// Constness of a binding. The latest entry for a name in a frame wins, so a
+/// block-local `var` can shadow a `const` until its scope ends.
+struct ConstBinding {
+    call_depth: usize, // call_stack length of the frame that owns the binding
+    name: String,
+    is_const: bool,
+    frozen: HashSet<usize>, // storage of the arrays, maps and structs the constant holds, nested ones included
+}
+
+/// Outcome of advancing an iteration by one item
+enum IterStep {
+    Item(Value),
+    Done,
+    Call(IterCall), // Razen code has to run first; its result resumes the step
+}
+
+/// Call made on behalf of an iteration
+enum IterCall {
+    Method(Value, &'static str), // iter()/next() on a struct
+    Function(Value, Vec<Value>), // adapter closure
+}
+
+/// Operator method call whose result the calling instruction still has to finish
+enum OperatorResume {
+    Negate,                         // `!=`, `<=` and `>=` built from eq() and lt()
+    Stringified(Option<Value>),     // to_string() result, followed by the operand that came after it
+}
+
+/// Deferred block registered by `PushDefer`, run when its frame exits
+struct PendingDefer {
+    call_depth: usize, // call_stack length of the frame that owns the defer
+    addr: usize,
+    captured: Vec<(String, Value)>, // block-local bindings visible when the defer statement ran
+}
+
+/// How a frame is being left while its deferred blocks run
+enum FrameExit {
+    Return(Value),
+    Throw(Value),
+    Error(String), // runtime error, caught as a string value
+}
 
 /// Runtime execution engine with stack machine - OPTIMIZED with typed values
 pub struct Runtime {
@@ -13,8 +81,14 @@ pub struct Runtime {
     variables: HashMap<String, Value>,
     functions: HashMap<String, usize>, // Changed to usize for direct addressing
     call_stack: Vec<(usize, HashMap<String, Value>)>,
-    _exception_handlers: Vec<(String, usize)>,
-    function_params: HashMap<String, Vec<String>>, // Store function parameter names
+    scopes: Vec<BlockScope>,
+    constants: Vec<ConstBinding>,
+    exception_handlers: Vec<ExceptionHandler>,
+    defers: Vec<PendingDefer>,
+    frame_exits: Vec<(usize, FrameExit)>, // exit in progress for each frame running deferred code
+    operator_resumes: Vec<(usize, usize, OperatorResume)>, // call depth and address waiting for an operator method
+    function_params: HashMap<String, Vec<ParamSpec>>, // Store function parameters
+    pending_named_args: Vec<String>, // names for the trailing arguments of the next call
     clean_output: bool,
 }
 
@@ -25,8 +99,14 @@ impl Runtime {
             variables: HashMap::with_capacity(256),
             functions: HashMap::new(),
             call_stack: Vec::new(),
-            _exception_handlers: Vec::new(),
+            scopes: Vec::new(),
+            constants: Vec::new(),
+            exception_handlers: Vec::new(),
+            defers: Vec::new(),
+            frame_exits: Vec::new(),
+            operator_resumes: Vec::new(),
             function_params: HashMap::new(),
+            pending_named_args: Vec::new(),
             clean_output: false,
         }
     }
@@ -39,8 +119,8 @@ impl Runtime {
         self.clean_output
     }
 
-    /// Register function parameter names for proper parameter binding
-    pub fn register_function_params(&mut self, name: String, params: Vec<String>) {
+    /// Register function parameters for proper parameter binding
+    pub fn register_function_params(&mut self, name: String, params: Vec<ParamSpec>) {
         self.function_params.insert(name, params);
     }
 
@@ -91,7 +171,7 @@ impl Runtime {
                 IR::PushString(s) => self.stack.push(Value::String(s.clone())),
                 IR::PushBoolean(b) => self.stack.push(Value::Boolean(*b)),
                 IR::PushNull => self.stack.push(Value::Null),
-                IR::StoreVar(name) => {
+                IR::StoreVar(name) | IR::DeclareConst(name) => {
                     if let Some(value) = self.stack.pop() {
                         self.variables.insert(name.clone(), value);
                         init_count += 1;
@@ -125,600 +205,965 @@ impl Runtime {
         while pc < ir.len() {
             let instruction = &ir[pc];
             
-            match instruction {
-                IR::PushInteger(i) => {
-                    self.stack.push(Value::Integer(*i));
-                },
-                IR::PushNumber(n) => {
-                    self.stack.push(Value::Number(*n));
-                },
-                IR::PushString(s) => {
-                    self.stack.push(Value::String(s.clone()));
-                },
-                IR::PushBoolean(b) => {
-                    self.stack.push(Value::Boolean(*b));
-                },
-                IR::PushNull => {
-                    self.stack.push(Value::Null);
-                },
-                IR::Pop => {
-                    self.stack.pop();
-                },
-                IR::Dup => {
-                    if let Some(value) = self.stack.last().cloned() {
-                        self.stack.push(value);
+            match self.execute_instruction(instruction, &mut pc) {
+                Ok(Step::Next) => pc += 1,
+                Ok(Step::Jump) => {},
+                Ok(Step::Exit) => return Ok(()),
+                Err(error) => {
+                    // Runtime errors (including builtin and stdlib failures) are thrown
+                    // as string values so an enclosing try/catch can handle them
+                    if self.exception_handlers.is_empty() && self.defers.is_empty() {
+                        return Err(error);
                     }
-                },
-                IR::Swap => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(b);
-                        self.stack.push(a);
+                    pc = self.exit_frame(FrameExit::Error(error))?;
+                }
+            }
+        }
+
+        if !self.clean_output {
+            println!("Execution completed successfully");
+            println!("Final stack size: {}", self.stack.len());
+            println!("Variables in scope: {}", self.variables.len());
+        }
+        Ok(())
+    }
+
+    /// Execute a single instruction, updating `pc` for jumps and calls
+    fn execute_instruction(&mut self, instruction: &IR, pc: &mut usize) -> Result<Step, String> {
+        match instruction {
+            IR::PushInteger(i) => {
+                self.stack.push(Value::Integer(*i));
+            },
+            IR::PushBigInt(i) => {
+                self.stack.push(Value::bigint(i.clone()));
+            },
+            IR::PushDecimal(d) => {
+                self.stack.push(Value::Decimal(*d));
+            },
+            IR::PushNumber(n) => {
+                self.stack.push(Value::Number(*n));
+            },
+            IR::PushString(s) => {
+                self.stack.push(Value::String(s.clone()));
+            },
+            IR::PushBoolean(b) => {
+                self.stack.push(Value::Boolean(*b));
+            },
+            IR::PushNull => {
+                self.stack.push(Value::Null);
+            },
+            IR::Pop => {
+                self.stack.pop();
+            },
+            IR::Dup => {
+                if let Some(value) = self.stack.last().cloned() {
+                    self.stack.push(value);
+                }
+            },
+            IR::Dup2 => {
+                if self.stack.len() >= 2 {
+                    let top = self.stack[self.stack.len() - 2..].to_vec();
+                    self.stack.extend(top);
+                }
+            },
+            IR::Swap => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(b);
+                    self.stack.push(a);
+                }
+            },
+            IR::StoreVar(name) => {
+                if let Some(value) = self.stack.pop() {
+                    if !self.clean_output {
+                        println!("DEBUG: StoreVar '{}' = '{}', call_stack depth: {}", name, value, self.call_stack.len());
                     }
-                },
-                IR::StoreVar(name) => {
-                    if let Some(value) = self.stack.pop() {
-                        if !self.clean_output {
-                            println!("DEBUG: StoreVar '{}' = '{}', call_stack depth: {}", name, value, self.call_stack.len());
-                        }
-                        // Check if this is a qualified module variable (contains '.')
-                        if name.contains('.') {
-                            // Module-level variable - always store globally
-                            self.variables.insert(name.clone(), value);
-                        } else if let Some((_, func_vars)) = self.call_stack.last_mut() {
-                            // Local variable - store in function scope
-                            if !self.clean_output {
-                                println!("DEBUG: Storing '{}' in function scope", name);
-                            }
-                            func_vars.insert(name.clone(), value);
-                        } else {
-                            // Global variable
-                            if !self.clean_output {
-                                println!("DEBUG: Storing '{}' in global scope", name);
-                            }
-                            self.variables.insert(name.clone(), value);
-                        }
+                    let call_depth = if name.contains('.') { 0 } else { self.call_stack.len() };
+                    if self.is_constant(name, call_depth) {
+                        return Err(format!("Cannot assign to constant '{}'", name));
                     }
-                },
-                IR::LoadVar(name) => {
                     // Check if this is a qualified module variable (contains '.')
-                    let value = if name.contains('.') {
-                        // Module-level variable - load from global scope
-                        if !self.clean_output {
-                            println!("DEBUG: Loading module var '{}' from global scope", name);
-                            println!("DEBUG: Available global vars: {:?}", self.variables.keys().collect::<Vec<_>>());
-                        }
-                        self.variables.get(name)
-                    } else if let Some((_, func_vars)) = self.call_stack.last() {
-                        // Try function scope first, then global
-                        func_vars.get(name).or_else(|| self.variables.get(name))
-                    } else {
-                        // Load from global scope
-                        self.variables.get(name)
-                    };
-
-                    if let Some(val) = value {
+                    if name.contains('.') {
+                        // Module-level variable - always store globally
+                        self.variables.insert(name.clone(), value);
+                    } else if let Some((_, func_vars)) = self.call_stack.last_mut() {
+                        // Local variable - store in function scope
                         if !self.clean_output {
-                            println!("DEBUG: LoadVar '{}' = '{}', call_stack depth: {}", name, val, self.call_stack.len());
+                            println!("DEBUG: Storing '{}' in function scope", name);
                         }
-                        self.stack.push(val.clone());
+                        func_vars.insert(name.clone(), value);
                     } else {
+                        // Global variable
                         if !self.clean_output {
-                            println!("DEBUG: Variable '{}' not found, pushing 'null', call_stack depth: {}", name, self.call_stack.len());
+                            println!("DEBUG: Storing '{}' in global scope", name);
                         }
-                        self.stack.push(Value::Null);
-                    }
-                },
-                IR::SetGlobal(name) => {
-                    if let Some(value) = self.stack.pop() {
                         self.variables.insert(name.clone(), value);
                     }
-                },
-                IR::Add => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.add(&b));
-                    }
-                },
-                IR::Subtract => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.subtract(&b));
-                    }
-                },
-                IR::Multiply => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.multiply(&b));
+                }
+            },
+            IR::LoadVar(name) => {
+                // Check if this is a qualified module variable (contains '.')
+                let value = if name.contains('.') {
+                    // Module-level variable - load from global scope
+                    if !self.clean_output {
+                        println!("DEBUG: Loading module var '{}' from global scope", name);
+                        println!("DEBUG: Available global vars: {:?}", self.variables.keys().collect::<Vec<_>>());
                     }
-                },
-                IR::Divide => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.divide(&b)?);
+                    self.variables.get(name)
+                } else if let Some((_, func_vars)) = self.call_stack.last() {
+                    // Try function scope first, then global
+                    func_vars.get(name).or_else(|| self.variables.get(name))
+                } else {
+                    // Load from global scope
+                    self.variables.get(name)
+                };
+
+                if let Some(val) = value {
+                    if !self.clean_output {
+                        println!("DEBUG: LoadVar '{}' = '{}', call_stack depth: {}", name, val, self.call_stack.len());
                     }
-                },
-                IR::Modulo => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_num), Some(b_num)) = (a.to_number(), b.to_number()) {
-                            if b_num != 0.0 {
-                                self.stack.push(Value::Number(a_num % b_num));
-                            } else {
-                                return Err("Modulo by zero".to_string());
-                            }
-                        }
+                    self.stack.push(val.clone());
+                } else if self.functions.contains_key(name) {
+                    // Named functions can be used as values (e.g., passed as callbacks)
+                    self.stack.push(Value::Function {
+                        name: name.clone(),
+                        captures: HashMap::new(),
+                    });
+                } else {
+                    if !self.clean_output {
+                        println!("DEBUG: Variable '{}' not found, pushing 'null', call_stack depth: {}", name, self.call_stack.len());
                     }
-                },
-                IR::Power => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_num), Some(b_num)) = (a.to_number(), b.to_number()) {
-                            self.stack.push(Value::Number(a_num.powf(b_num)));
+                    self.stack.push(Value::Null);
+                }
+            },
+            IR::SetGlobal(name) => {
+                if let Some(value) = self.stack.pop() {
+                    self.variables.insert(name.clone(), value);
+                }
+            },
+            IR::DeclareVar(name) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                self.declare_variable(name, value, false);
+            },
+            IR::DeclareConst(name) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                self.declare_variable(name, value, true);
+            },
+            IR::EnterScope => {
+                self.scopes.push(BlockScope {
+                    call_depth: self.call_stack.len(),
+                    shadowed: Vec::new(),
+                    constants_len: self.constants.len(),
+                });
+            },
+            IR::ExitScope => {
+                if let Some(scope) = self.scopes.pop() {
+                    self.restore_scope(scope);
+                }
+            },
+            IR::Add => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.add(&b)?);
+                }
+            },
+            IR::Subtract => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.subtract(&b)?);
+                }
+            },
+            IR::Multiply => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.multiply(&b)?);
+                }
+            },
+            IR::Divide => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.divide(&b)?);
+                }
+            },
+            IR::Modulo => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    if let Some(remainder) = a.decimal_remainder(&b) {
+                        self.stack.push(remainder?);
+                    } else if let Some((a_big, b_big)) = a.bigint_operands(&b) {
+                        if b_big.is_zero() {
+                            return Err("Modulo by zero".to_string());
                         }
-                    }
-                },
-                IR::Negate => {
-                    if let Some(a) = self.stack.pop() {
-                        match a {
-                            Value::Number(n) => self.stack.push(Value::Number(-n)),
-                            Value::Integer(i) => self.stack.push(Value::Integer(-i)),
-                            _ => self.stack.push(Value::Null),
+                        self.stack.push(Value::bigint(a_big % b_big));
+                    } else if let (Some(a_num), Some(b_num)) = (a.to_number(), b.to_number()) {
+                        if b_num != 0.0 {
+                            self.stack.push(Value::Number(a_num % b_num));
+                        } else {
+                            return Err("Modulo by zero".to_string());
                         }
                     }
-                },
-                IR::Equal => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.equal(&b)));
-                    }
-                },
-                IR::NotEqual => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(!a.equal(&b)));
-                    }
-                },
-                IR::GreaterThan => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.greater_than(&b)));
-                    }
-                },
-                IR::GreaterEqual => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.greater_equal(&b)));
-                    }
-                },
-                IR::LessThan => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.less_than(&b)));
-                    }
-                },
-                IR::LessEqual => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.less_equal(&b)));
-                    }
-                },
-                IR::And => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.is_truthy() && b.is_truthy()));
-                    }
-                },
-                IR::Or => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(Value::Boolean(a.is_truthy() || b.is_truthy()));
-                    }
-                },
-                IR::Not => {
-                    if let Some(a) = self.stack.pop() {
-                        self.stack.push(Value::Boolean(!a.is_truthy()));
-                    }
-                },
-                IR::Jump(target) => {
-                    if !self.clean_output {
-                        println!("DEBUG: Jump from {} to {}", pc, target);
-                    }
-                    pc = *target;
-                    continue;
-                },
-                IR::JumpIfFalse(target) => {
-                    if let Some(value) = self.stack.pop() {
-                        if !value.is_truthy() {
-                            pc = *target;
-                            continue;
-                        }
+                }
+            },
+            IR::Power => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.power(&b)?);
+                }
+            },
+            IR::Negate => {
+                if let Some(a) = self.stack.pop() {
+                    self.stack.push(a.negate()?);
+                }
+            },
+            IR::Equal => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.equal(&b)));
+                }
+            },
+            IR::NotEqual => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(!a.equal(&b)));
+                }
+            },
+            IR::GreaterThan => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.greater_than(&b)));
+                }
+            },
+            IR::GreaterEqual => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.greater_equal(&b)));
+                }
+            },
+            IR::LessThan => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.less_than(&b)));
+                }
+            },
+            IR::LessEqual => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.less_equal(&b)));
+                }
+            },
+            IR::And => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.is_truthy() && b.is_truthy()));
+                }
+            },
+            IR::Or => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(Value::Boolean(a.is_truthy() || b.is_truthy()));
+                }
+            },
+            IR::Not => {
+                if let Some(a) = self.stack.pop() {
+                    self.stack.push(Value::Boolean(!a.is_truthy()));
+                }
+            },
+            IR::Jump(target) => {
+                if !self.clean_output {
+                    println!("DEBUG: Jump from {} to {}", *pc, target);
+                }
+                *pc = *target;
+                return Ok(Step::Jump);
+            },
+            IR::JumpIfFalse(target) => {
+                if let Some(value) = self.stack.pop() {
+                    if !value.is_truthy() {
+                        *pc = *target;
+                        return Ok(Step::Jump);
                     }
-                },
-                IR::JumpIfTrue(target) => {
-                    if let Some(value) = self.stack.pop() {
-                        if value.is_truthy() {
-                            pc = *target;
-                            continue;
-                        }
+                }
+            },
+            IR::JumpIfTrue(target) => {
+                if let Some(value) = self.stack.pop() {
+                    if value.is_truthy() {
+                        *pc = *target;
+                        return Ok(Step::Jump);
                     }
-                },
-                IR::Call(name, arg_count) => {
-                    // Handle builtin functions
-                    if self.is_builtin(name) {
-                        self.execute_builtin(name, *arg_count)?;
-                    } else if name.contains('.') {
-                        // Check if this is a stdlib function call (module.function)
-                        let parts: Vec<&str> = name.splitn(2, '.').collect();
-                        if parts.len() == 2 {
-                            let module_name = parts[0];
-                            let function_name = parts[1];
-                            
-                            if crate::stdlib::is_stdlib_module(module_name) {
-                                // Collect arguments from stack
-                                let mut args = Vec::new();
-                                for _ in 0..*arg_count {
-                                    if let Some(arg) = self.stack.pop() {
-                                        args.push(arg);
-                                    }
+                }
+            },
+            IR::JumpIfDefined(name, target) => {
+                // Parameters the caller supplied skip their default-value initializer
+                let defined = self.call_stack.last()
+                    .is_some_and(|(_, func_vars)| func_vars.contains_key(name));
+                if defined {
+                    *pc = *target;
+                    return Ok(Step::Jump);
+                }
+            },
+            IR::NamedArgs(names) => {
+                self.pending_named_args = names.clone();
+            },
+            IR::JumpIfNull(target) => {
+                match self.stack.pop().unwrap_or(Value::Null) {
+                    value if value.is_nullish() => {
+                        *pc = *target;
+                        return Ok(Step::Jump);
+                    },
+                    Value::Option { value, .. } => self.stack.push(*value),
+                    value => self.stack.push(value),
+                }
+            },
+            IR::Call(name, arg_count) => {
+                // Variables holding function values (lambdas, callbacks) take priority
+                if let Some(function) = self.lookup_function_value(name) {
+                    let args = self.pop_arguments(*arg_count);
+                    *pc = self.call_function_value(function, args, *pc + 1)?;
+                    return Ok(Step::Jump);
+                }
+
+                // Printing a struct goes through its to_string() method
+                if matches!(name.as_str(), "print" | "println" | "tostr") && *arg_count == 1
+                    && let Some(target) = self.dispatch_to_string(*pc)?
+                {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+
+                // Handle builtin functions; user functions of the same name take priority
+                if self.is_builtin(name) && !self.functions.contains_key(name) {
+                    self.reject_named_args(name)?;
+                    self.execute_builtin(name, *arg_count)?;
+                } else if name.contains('.') {
+                    // Check if this is a stdlib function call (module.function)
+                    let parts: Vec<&str> = name.splitn(2, '.').collect();
+                    if parts.len() == 2 {
+                        let module_name = parts[0];
+                        let function_name = parts[1];
+                        
+                        if crate::stdlib::is_stdlib_module(module_name) {
+                            self.reject_named_args(name)?;
+                            // Collect arguments from stack
+                            let mut args = Vec::new();
+                            for _ in 0..*arg_count {
+                                if let Some(arg) = self.stack.pop() {
+                                    args.push(arg);
                                 }
-                                args.reverse(); // Arguments are pushed in reverse order
-                                
-                                if !self.clean_output {
-                                    println!("[DEBUG] Calling stdlib function: {}.{}", module_name, function_name);
+                            }
+                            args.reverse(); // Arguments are pushed in reverse order
+                            
+                            if crate::stdlib::mutates_first_argument(module_name, function_name)
+                                && let Some(target) = args.first()
+                            {
+                                self.check_not_frozen(target)?;
+                            }
+                            if !self.clean_output {
+                                println!("[DEBUG] Calling stdlib function: {}.{}", module_name, function_name);
+                            }
+                            
+                            // Call stdlib function
+                            match crate::stdlib::call_stdlib_function(module_name, function_name, args) {
+                                Ok(result) => {
+                                    self.stack.push(result);
                                 }
-                                
-                                // Call stdlib function
-                                match crate::stdlib::call_stdlib_function(module_name, function_name, args) {
-                                    Ok(result) => {
-                                        self.stack.push(result);
-                                    }
-                                    Err(e) => {
-                                        return Err(format!("Stdlib function error: {}", e));
-                                    }
+                                Err(e) => {
+                                    return Err(format!("Stdlib function error: {}", e));
                                 }
-                            } else {
-                                // Not a stdlib module, try user-defined function
-                                self.call_user_function(name, *arg_count, &mut pc)?;
                             }
                         } else {
-                            self.call_user_function(name, *arg_count, &mut pc)?;
+                            // Not a stdlib module, try user-defined function
+                            self.call_user_function(name, *arg_count, pc)?;
                         }
                     } else {
-                        // User-defined function call
-                        self.call_user_function(name, *arg_count, &mut pc)?;
+                        self.call_user_function(name, *arg_count, pc)?;
                     }
-                },
-                IR::MethodCall(method_name, arg_count) => {
-                    // Check if this is a builtin method first
-                    if self.is_builtin(method_name) {
-                        // Handle builtin methods - the object is already on the stack as the first argument
-                        self.execute_builtin(method_name, *arg_count)?;
-                    } else {
-                        // Method calls are handled similarly to function calls
-                        // but the first argument is the 'self' object
-                        let mut args = Vec::new();
-                        for _ in 0..*arg_count {
-                            if let Some(arg) = self.stack.pop() {
-                                args.push(arg);
-                            }
-                        }
-                        args.reverse(); // Arguments are pushed in reverse order
-                        
-                        if args.is_empty() {
-                            return Err("Method call requires at least self argument".to_string());
+                } else {
+                    // User-defined function call
+                    self.call_user_function(name, *arg_count, pc)?;
+                }
+            },
+            IR::MethodCall(method_name, arg_count) => {
+                // Copies share arrays, maps and structs; clone() makes an independent one
+                if method_name == "clone" && *arg_count == 1 && !self.receiver_has_method(method_name, *arg_count) {
+                    let receiver = self.stack.pop().unwrap_or(Value::Null);
+                    self.stack.push(receiver.deep_clone());
+                    return Ok(Step::Next);
+                }
+
+                // Methods of built-in types are backed by the stdlib, e.g. "abc".upper()
+                let receiver_type = self.stack.len().checked_sub(*arg_count)
+                    .and_then(|i| self.stack.get(i))
+                    .map(|receiver| receiver.type_name().to_string());
+                if let Some(type_name) = receiver_type
+                    && crate::stdlib::method_module(&type_name, method_name).is_some()
+                {
+                    self.reject_named_args(method_name)?;
+                    let args = self.pop_arguments(*arg_count);
+                    if crate::stdlib::is_mutating_method(&type_name, method_name) {
+                        self.check_not_frozen(&args[0])?;
+                    }
+                    let result = crate::stdlib::call_method(&type_name, method_name, args)?;
+                    self.stack.push(result);
+                }
+                // Check if this is a builtin method first, unless the receiver defines it
+                else if self.is_builtin(method_name) && !self.receiver_has_method(method_name, *arg_count) {
+                    // Handle builtin methods - the object is already on the stack as the first argument
+                    self.reject_named_args(method_name)?;
+                    self.execute_builtin(method_name, *arg_count)?;
+                } else {
+                    // Method calls are handled similarly to function calls
+                    // but the first argument is the 'self' object
+                    let named = std::mem::take(&mut self.pending_named_args);
+                    let mut args = Vec::new();
+                    for _ in 0..*arg_count {
+                        if let Some(arg) = self.stack.pop() {
+                            args.push(arg);
                         }
-                        
-                        let self_obj = &args[0];
-                        let method_args = &args[1..];
-                        
-                        // Extract type from the object
-                        let object_type = if let Value::Struct { type_name, .. } = self_obj {
-                            // Proper struct value - get type directly
-                            Some(type_name.clone())
-                        } else if let Value::String(obj_str) = self_obj {
-                            // Fallback: parse string representation (backwards compatibility)
-                            if obj_str.starts_with('{') && obj_str.contains("__type__:") {
-                                let content = &obj_str[1..obj_str.len()-1];
-                                let pairs: Vec<&str> = content.split(", ").collect();
-                                let mut found_type = None;
-                                for pair in pairs {
-                                    if let Some(colon_pos) = pair.find(": ") {
-                                        let key = &pair[..colon_pos];
-                                        let value = &pair[colon_pos + 2..];
-                                        if key == "__type__" {
-                                            found_type = Some(value.to_string());
-                                            break;
-                                        }
+                    }
+                    args.reverse(); // Arguments are pushed in reverse order
+                    
+                    if args.is_empty() {
+                        return Err("Method call requires at least self argument".to_string());
+                    }
+                    
+                    let self_obj = &args[0];
+                    let method_args = &args[1..];
+                    
+                    // Extract type from the object
+                    let object_type = if let Value::Struct { type_name, .. } = self_obj {
+                        // Proper struct value - get type directly
+                        Some(type_name.clone())
+                    } else if let Value::String(obj_str) = self_obj {
+                        // Fallback: parse string representation (backwards compatibility)
+                        if obj_str.starts_with('{') && obj_str.contains("__type__:") {
+                            let content = &obj_str[1..obj_str.len()-1];
+                            let pairs: Vec<&str> = content.split(", ").collect();
+                            let mut found_type = None;
+                            for pair in pairs {
+                                if let Some(colon_pos) = pair.find(": ") {
+                                    let key = &pair[..colon_pos];
+                                    let value = &pair[colon_pos + 2..];
+                                    if key == "__type__" {
+                                        found_type = Some(value.to_string());
+                                        break;
                                     }
                                 }
-                                found_type
-                            } else {
-                                None
                             }
+                            found_type
                         } else {
                             None
-                        };
-                        
-                        // Build the full method name using dot notation
-                        let full_method_name = if let Some(type_name) = object_type {
-                            format!("{}.{}", type_name, method_name)
-                        } else {
-                            // Fallback: try to find any method with this name
-                            let mut found_name = format!("Object.{}", method_name);
-                            for func_name in self.functions.keys() {
-                                if func_name.ends_with(&format!(".{}", method_name)) {
-                                    found_name = func_name.clone();
-                                    break;
-                                }
+                        }
+                    } else {
+                        None
+                    };
+                    
+                    // Build the full method name using dot notation
+                    let full_method_name = if let Some(type_name) = object_type {
+                        format!("{}.{}", type_name, method_name)
+                    } else {
+                        // Fallback: try to find any method with this name
+                        let mut found_name = format!("{}.{}", self_obj.type_name(), method_name);
+                        for func_name in self.functions.keys() {
+                            if func_name.ends_with(&format!(".{}", method_name)) {
+                                found_name = func_name.clone();
+                                break;
                             }
-                            found_name
-                        };
-                        
-                        if !self.clean_output {
-                            println!("Looking for method '{}' (full name: '{}')", method_name, full_method_name);
                         }
+                        found_name
+                    };
+                    
+                    if !self.clean_output {
+                        println!("Looking for method '{}' (full name: '{}')", method_name, full_method_name);
+                    }
+                    
+                    if let Some(&func_addr) = self.functions.get(&full_method_name) {
+                        // Create new method scope with self and parameters
+                        let mut func_variables = HashMap::new();
+                        func_variables.insert("self".to_string(), self_obj.clone());
                         
-                        if let Some(&func_addr) = self.functions.get(&full_method_name) {
-                            // Create new method scope with self and parameters
-                            let mut func_variables = HashMap::new();
-                            func_variables.insert("self".to_string(), self_obj.clone());
-                            
-                            // Bind method parameters
-                            if let Some(param_names) = self.function_params.get(&full_method_name) {
-                                // Skip first parameter (self) if it exists in param_names
-                                let method_param_names = if param_names.first() == Some(&"self".to_string()) {
-                                    &param_names[1..]
-                                } else {
-                                    param_names
-                                };
-                                
-                                for (i, param_name) in method_param_names.iter().enumerate() {
-                                    if i < method_args.len() {
-                                        func_variables.insert(param_name.clone(), method_args[i].clone());
-                                    } else {
-                                        func_variables.insert(param_name.clone(), Value::Null);
-                                    }
-                                }
-                            }
+                        // Bind method parameters
+                        if let Some(params) = self.function_params.get(&full_method_name) {
+                            // Skip first parameter (self) if it exists in params
+                            let method_params = match params.first() {
+                                Some(first) if first.name == "self" => &params[1..],
+                                _ => params,
+                            };
                             
-                            // Save current state and jump to method
-                            // Use same approach as regular function calls - keep variables in call stack
-                            self.call_stack.push((pc + 1, func_variables));
-                            pc = func_addr;
-                            continue;
-                        } else {
-                            return Err(format!("Method '{}' not found", full_method_name));
+                            Self::bind_parameters(&full_method_name, method_params, method_args, &named, &mut func_variables)?;
                         }
-                    }
-                },
-                IR::Return => {
-                    let return_value = self.stack.pop().unwrap_or(Value::Null);
-                    if let Some((return_addr, _func_variables)) = self.call_stack.pop() {
-                        // Don't restore variables - global variables stay in self.variables
-                        self.stack.push(return_value);
-                        pc = return_addr;
-                        continue;
+                        
+                        // Save current state and jump to method
+                        // Use same approach as regular function calls - keep variables in call stack
+                        self.call_stack.push((*pc + 1, func_variables));
+                        *pc = func_addr;
+                        return Ok(Step::Jump);
                     } else {
-                        self.stack.push(return_value);
+                        return Err(format!("Method '{}' not found", full_method_name));
                     }
-                },
-                IR::Print => {
-                    if let Some(value) = self.stack.pop() {
-                        print!("{}", value);
-                        io::stdout().flush().unwrap();
-                    }
-                },
-                IR::ReadInput => {
-                    let stdin = io::stdin();
-                    let mut line = String::new();
-                    stdin.lock().read_line(&mut line).expect("Failed to read line");
-                    if line.ends_with('\n') {
-                        line.pop();
-                        if line.ends_with('\r') {
-                            line.pop();
-                        }
-                    }
-                    self.stack.push(Value::String(line));
-                },
-                IR::Exit => {
-                    return Ok(());
-                },
-                IR::Sleep => {
-                    if let Some(duration_val) = self.stack.pop() {
-                        if let Some(duration) = duration_val.to_number() {
-                            thread::sleep(Duration::from_secs_f64(duration));
-                        }
+                }
+            },
+            IR::CallValue(arg_count) => {
+                // Call whatever value the callee expression produced, e.g. make_adder(5)(3)
+                let args = self.pop_arguments(*arg_count);
+                let callee = self.stack.pop().unwrap_or(Value::Null);
+                *pc = self.call_function_value(callee, args, *pc + 1)?;
+                return Ok(Step::Jump);
+            },
+            IR::CallSpread(name) => {
+                let arg_count = self.unpack_argument_array()?;
+                return self.execute_instruction(&IR::Call(name.clone(), arg_count), pc);
+            },
+            IR::MethodCallSpread(method_name) => {
+                let arg_count = self.unpack_argument_array()?;
+                return self.execute_instruction(&IR::MethodCall(method_name.clone(), arg_count + 1), pc); // +1 for self
+            },
+            IR::CallValueSpread => {
+                let arg_count = self.unpack_argument_array()?;
+                return self.execute_instruction(&IR::CallValue(arg_count), pc);
+            },
+            IR::MakeClosure(name) => {
+                // Capture the enclosing frame's locals by value; globals stay visible anyway
+                let captures = self.call_stack.last()
+                    .map(|(_, func_vars)| func_vars.clone())
+                    .unwrap_or_default();
+                self.stack.push(Value::Function { name: name.clone(), captures });
+            },
+            IR::MatchVariant(name, arity) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                let matched = match (&value, name.as_str()) {
+                    (Value::Option { is_some: true, .. }, "Some") => *arity == 1,
+                    (Value::Option { is_some: false, .. }, "None") => *arity == 0,
+                    (Value::Result { is_ok: true, .. }, "Ok") => *arity == 1,
+                    (Value::Result { is_ok: false, .. }, "Err") => *arity == 1,
+                    (Value::Enum { type_name, variant, payload }, pattern) => {
+                        // Patterns may name the variant bare or qualified with its enum
+                        let name_matches = match pattern.split_once('.') {
+                            Some((enum_name, variant_name)) => enum_name == type_name && variant_name == variant,
+                            None => pattern == variant,
+                        };
+                        name_matches && payload.len() == *arity
+                    },
+                    _ => false,
+                };
+                self.stack.push(Value::Boolean(matched));
+            },
+            IR::MakeEnum(type_name, variant, arg_count) => {
+                let payload = self.pop_arguments(*arg_count);
+                self.stack.push(Value::Enum {
+                    type_name: type_name.clone(),
+                    variant: variant.clone(),
+                    payload,
+                });
+            },
+            IR::MatchStruct(name) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                let matched = matches!(&value, Value::Struct { type_name, .. } if type_name == name);
+                self.stack.push(Value::Boolean(matched));
+            },
+            IR::MatchLength(len, at_least) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                let matched = match &value {
+                    Value::Array(elements) if *at_least => elements.borrow().len() >= *len,
+                    Value::Array(elements) => elements.borrow().len() == *len,
+                    Value::Tuple(elements) => elements.len() == *len,
+                    _ => false,
+                };
+                self.stack.push(Value::Boolean(matched));
+            },
+            IR::GetVariantField(index) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                let field = match value {
+                    Value::Option { value: inner, .. } | Value::Result { value: inner, .. } if *index == 0 => *inner,
+                    Value::Enum { mut payload, .. } if *index < payload.len() => payload.swap_remove(*index),
+                    _ => Value::Null,
+                };
+                self.stack.push(field);
+            },
+            IR::SetupTryCatch(catch_addr) => {
+                self.exception_handlers.push(ExceptionHandler {
+                    catch_addr: *catch_addr,
+                    call_depth: self.call_stack.len(),
+                    stack_len: self.stack.len(),
+                    scope_depth: self.scopes.len(),
+                });
+            },
+            IR::ClearTryCatch => {
+                self.exception_handlers.pop();
+            },
+            IR::ThrowException => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                *pc = self.exit_frame(FrameExit::Throw(value))?;
+                return Ok(Step::Jump);
+            },
+            IR::Return => {
+                let return_value = self.stack.pop().unwrap_or(Value::Null);
+                if self.call_stack.is_empty() {
+                    self.stack.push(return_value);
+                } else {
+                    // Handlers installed inside the returning function are no longer active,
+                    // not even while its deferred blocks run
+                    while self.exception_handlers.last().is_some_and(|h| h.call_depth >= self.call_stack.len()) {
+                        self.exception_handlers.pop();
                     }
-                },
-                IR::FloorDiv => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_num), Some(b_num)) = (a.to_number(), b.to_number()) {
-                            if b_num != 0.0 {
-                                self.stack.push(Value::Number((a_num / b_num).floor()));
-                            } else {
-                                return Err("Division by zero".to_string());
-                            }
+                    *pc = self.exit_frame(FrameExit::Return(return_value))?;
+                    return Ok(Step::Jump);
+                }
+            },
+            IR::PushDefer(addr) => {
+                // Block-local bindings may be gone by the time the frame exits, so the
+                // deferred code gets the values they have now
+                let call_depth = self.call_stack.len();
+                let frame_vars = match self.call_stack.last() {
+                    Some((_, func_vars)) => func_vars,
+                    None => &self.variables,
+                };
+                let mut captured: Vec<(String, Value)> = Vec::new();
+                for scope in self.scopes.iter().filter(|s| s.call_depth == call_depth) {
+                    for (name, _) in &scope.shadowed {
+                        if let Some(value) = frame_vars.get(name)
+                            && !captured.iter().any(|(seen, _)| seen == name)
+                        {
+                            captured.push((name.clone(), value.clone()));
                         }
                     }
-                },
-                IR::CreateArray(size) => {
-                    // Pop 'size' elements from stack and create an array
-                    let mut elements = Vec::new();
-                    for _ in 0..*size {
-                        if let Some(element) = self.stack.pop() {
-                            elements.push(element);
-                        }
+                }
+                self.defers.push(PendingDefer { call_depth, addr: *addr, captured });
+            },
+            IR::EndDefer => {
+                // Close the scope the deferred code ran in, then carry on leaving the frame
+                if let Some(scope) = self.scopes.pop() {
+                    self.restore_scope(scope);
+                }
+                let exit = match self.frame_exits.pop() {
+                    Some((_, exit)) => exit,
+                    None => FrameExit::Return(Value::Null),
+                };
+                *pc = self.exit_frame(exit)?;
+                return Ok(Step::Jump);
+            },
+            IR::PropagateError(return_addr) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                match value {
+                    // Failures are returned from the enclosing function unchanged
+                    Value::Result { is_ok: false, .. } | Value::Option { is_some: false, .. } => {
+                        self.stack.push(value);
+                        *pc = *return_addr;
+                        return Ok(Step::Jump);
+                    },
+                    Value::Result { value, .. } | Value::Option { value, .. } => self.stack.push(*value),
+                    other => self.stack.push(other),
+                }
+            },
+            IR::Print => {
+                if let Some(target) = self.dispatch_to_string(*pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                if let Some(value) = self.stack.pop() {
+                    print!("{}", value);
+                    io::stdout().flush().unwrap();
+                }
+            },
+            IR::ReadInput => {
+                let stdin = io::stdin();
+                let mut line = String::new();
+                stdin.lock().read_line(&mut line).expect("Failed to read line");
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
                     }
-                    elements.reverse(); // Restore original order
-                    
-                    // Create proper Array value
-                    self.stack.push(Value::Array(elements));
-                },
-                IR::CreateMap(size) => {
-                    if !self.clean_output {
-                        println!("DEBUG: CreateMap with size {}", size);
-                        println!("DEBUG: Stack size before CreateMap: {}", self.stack.len());
+                }
+                self.stack.push(Value::String(line));
+            },
+            IR::Exit => {
+                return Ok(Step::Exit);
+            },
+            IR::Sleep => {
+                if let Some(duration_val) = self.stack.pop() {
+                    if let Some(duration) = duration_val.to_number() {
+                        thread::sleep(Duration::from_secs_f64(duration));
                     }
-                    
-                    // Pop 'size * 2' elements from stack (key-value pairs)
-                    let mut pairs = Vec::new();
-                    for _ in 0..*size {
-                        if let (Some(value), Some(key)) = (self.stack.pop(), self.stack.pop()) {
-                            if !self.clean_output {
-                                println!("DEBUG: Pair - key: {}, value: {}", key, value);
-                            }
-                            pairs.push((key, value));
+                }
+            },
+            IR::FloorDiv => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    if let Some((a_big, b_big)) = a.bigint_operands(&b) {
+                        if b_big.is_zero() {
+                            return Err("Division by zero".to_string());
                         }
-                    }
-                    pairs.reverse(); // Restore original order
-                    
-                    // Check if first key is "__type__" to determine if this is a struct
-                    let mut type_name = None;
-                    let mut fields = HashMap::new();
-                    
-                    for (key, value) in pairs {
-                        let key_str = key.to_string();
-                        if key_str == "__type__" {
-                            type_name = Some(value.to_string());
+                        self.stack.push(Value::bigint(a_big.div_floor(&b_big)));
+                    } else if let (Some(a_num), Some(b_num)) = (a.to_number(), b.to_number()) {
+                        if b_num != 0.0 {
+                            self.stack.push(Value::Number((a_num / b_num).floor()));
                         } else {
-                            fields.insert(key_str, value);
+                            return Err("Division by zero".to_string());
                         }
                     }
-                    
-                    // If we have a type name, create a proper Struct value
-                    if let Some(type_name) = type_name {
-                        if !self.clean_output {
-                            println!("DEBUG: Creating struct '{}' with fields: {:?}", type_name, fields.keys().collect::<Vec<_>>());
-                        }
-                        self.stack.push(Value::Struct { type_name, fields });
-                    } else {
-                        // Otherwise, create a proper Map value
-                        if !self.clean_output {
-                            println!("DEBUG: Creating map with {} fields", fields.len());
-                        }
-                        self.stack.push(Value::Map(fields));
+                }
+            },
+            IR::CreateArray(size) => {
+                // Pop 'size' elements from stack and create an array
+                let mut elements = Vec::new();
+                for _ in 0..*size {
+                    if let Some(element) = self.stack.pop() {
+                        elements.push(element);
                     }
-                },
-                IR::GetKey => {
-                    // Pop key and object from stack, push the value for that key
-                    if let (Some(key), Some(object)) = (self.stack.pop(), self.stack.pop()) {
-                        match &object {
-                            // Handle Array indexing
-                            Value::Array(arr) => {
-                                if let Some(index) = key.to_integer() {
-                                    if index >= 0 && (index as usize) < arr.len() {
-                                        self.stack.push(arr[index as usize].clone());
-                                    } else {
-                                        if !self.clean_output {
-                                            println!("DEBUG: Array index {} out of bounds (len: {})", index, arr.len());
-                                        }
-                                        self.stack.push(Value::Null);
-                                    }
-                                } else {
-                                    if !self.clean_output {
-                                        println!("DEBUG: Invalid array index: {}", key);
-                                    }
-                                    self.stack.push(Value::Null);
-                                }
-                            },
-                            // Handle Map indexing
-                            Value::Map(map) => {
-                                let key_str = key.to_string();
-                                if let Some(value) = map.get(&key_str) {
-                                    self.stack.push(value.clone());
-                                } else {
-                                    if !self.clean_output {
-                                        println!("DEBUG: Key '{}' not found in map", key_str);
-                                    }
-                                    self.stack.push(Value::Null);
-                                }
-                            },
-                            // Handle Struct field access
-                            Value::Struct { type_name, fields } => {
-                                let key_str = key.to_string();
+                }
+                elements.reverse(); // Restore original order
+                
+                // Create proper Array value
+                self.stack.push(Value::array(elements));
+            },
+            IR::SetKey | IR::SetIndex => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                let key = self.stack.pop().unwrap_or(Value::Null);
+                let object = self.stack.pop().unwrap_or(Value::Null);
+                self.check_not_frozen(&object)?;
+                let updated = Self::set_element(object, key, value)?;
+                self.stack.push(updated);
+            },
+            IR::GetIndex => {
+                if let Some(target) = self.dispatch_operator(instruction, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+                // Without an index() method, indexing reads like any other key
+                return self.execute_instruction(&IR::GetKey, pc);
+            },
+            IR::Slice(inclusive) => {
+                let end = self.stack.pop().unwrap_or(Value::Null);
+                let start = self.stack.pop().unwrap_or(Value::Null);
+                let object = self.stack.pop().unwrap_or(Value::Null);
+                let sliced = Self::slice_value(object, start, end, *inclusive)?;
+                self.stack.push(sliced);
+            },
+            IR::GetIter => {
+                let iterable = self.stack.pop().unwrap_or(Value::Null);
+                let state = self.iter_state(iterable)?;
+                self.stack.push(Value::Iterator(Box::new(state)));
+            },
+            IR::IterNext(name, end_addr) => {
+                if let Some(target) = self.iter_next(name, *end_addr, *pc)? {
+                    *pc = target;
+                    return Ok(Step::Jump);
+                }
+            },
+            IR::CreateTuple(size) => {
+                let elements = self.pop_arguments(*size);
+                self.stack.push(Value::Tuple(elements));
+            },
+            IR::GetTupleField(index) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                match value {
+                    Value::Tuple(mut elements) if *index < elements.len() => {
+                        self.stack.push(elements.swap_remove(*index));
+                    }
+                    Value::Tuple(elements) => {
+                        return Err(format!(
+                            "Tuple index {} out of range for tuple of length {}",
+                            index, elements.len()
+                        ));
+                    }
+                    other => return Err(format!("Cannot access .{} on non-tuple value '{}'", index, other)),
+                }
+            },
+            IR::UnpackTuple(size) => {
+                let value = self.stack.pop().unwrap_or(Value::Null);
+                let elements = match value {
+                    Value::Tuple(elements) => elements,
+                    Value::Array(elements) => elements.borrow().clone(),
+                    other => return Err(format!("Cannot destructure non-tuple value '{}'", other)),
+                };
+                if elements.len() != *size {
+                    return Err(format!(
+                        "Cannot destructure {} value(s) into {} variable(s)",
+                        elements.len(), size
+                    ));
+                }
+                self.stack.extend(elements.into_iter().rev());
+            },
+            IR::ExtendArray => {
+                let spread = self.stack.pop().unwrap_or(Value::Null);
+                let items = match spread {
+                    Value::Array(items) => items.borrow().clone(),
+                    other => return Err(format!("Cannot spread non-array value '{}'", other)),
+                };
+                match self.stack.last_mut() {
+                    Some(Value::Array(elements)) => elements.borrow_mut().extend(items),
+                    _ => return Err("ARRAY_EXTEND requires an array on the stack".to_string()),
+                }
+            },
+            IR::CreateMap(size) => {
+                if !self.clean_output {
+                    println!("DEBUG: CreateMap with size {}", size);
+                    println!("DEBUG: Stack size before CreateMap: {}", self.stack.len());
+                }
+                let fields = self.pop_entries(*size);
+                if !self.clean_output {
+                    println!("DEBUG: Creating map with {} fields", fields.len());
+                }
+                self.stack.push(Value::map(fields));
+            },
+            IR::CreateStruct(type_name, size) => {
+                let fields = self.pop_entries(*size);
+                if !self.clean_output {
+                    println!("DEBUG: Creating struct '{}' with fields: {:?}", type_name, fields.keys().collect::<Vec<_>>());
+                }
+                self.stack.push(Value::new_struct(type_name.clone(), fields));
+            },
+            IR::GetKey => {
+                // Pop key and object from stack, push the value for that key
+                if let (Some(key), Some(object)) = (self.stack.pop(), self.stack.pop()) {
+                    match &object {
+                        // Handle Array (and tuple) indexing; negative indices count from the end
+                        Value::Array(arr) => self.push_element(&arr.borrow(), &key),
+                        Value::Tuple(arr) => self.push_element(arr, &key),
+                        // Strings are indexed by character
+                        Value::String(s) => {
+                            let len = s.chars().count();
+                            match key.to_integer().and_then(|index| Self::resolve_index(index, len)) {
+                                Some(index) => {
+                                    let ch = s.chars().nth(index).map(String::from).unwrap_or_default();
+                                    self.stack.push(Value::String(ch));
+                                },
+                                None => self.stack.push(Value::Null),
+                            }
+                        },
+                        // Ranges are indexed without materializing their elements
+                        Value::Range { start, end, inclusive } => {
+                            let len = Self::range_len(*start, *end, *inclusive);
+                            match key.to_integer() {
+                                Some(index) if index >= 0 && (index as usize) < len => {
+                                    self.stack.push(Value::Integer(start + index));
+                                },
+                                _ => self.stack.push(Value::Null),
+                            }
+                        },
+                        // Handle Map indexing
+                        Value::Map(map) => {
+                            let key_str = key.to_string();
+                            if let Some(value) = map.borrow().get(&key_str) {
+                                self.stack.push(value.clone());
+                            } else {
                                 if !self.clean_output {
-                                    println!("DEBUG: GetKey on struct '{}', looking for field '{}', available fields: {:?}", type_name, key_str, fields.keys().collect::<Vec<_>>());
+                                    println!("DEBUG: Key '{}' not found in map", key_str);
                                 }
-                                if let Some(value) = fields.get(&key_str) {
-                                    if !self.clean_output {
-                                        println!("DEBUG: Found field '{}' = '{}'", key_str, value);
-                                    }
-                                    self.stack.push(value.clone());
-                                } else {
-                                    if !self.clean_output {
-                                        println!("DEBUG: Field '{}' not found in struct", key_str);
-                                    }
-                                    self.stack.push(Value::Null);
+                                self.stack.push(Value::Null);
+                            }
+                        },
+                        // Handle Struct field access
+                        Value::Struct { type_name, fields } => {
+                            let key_str = key.to_string();
+                            if !self.clean_output {
+                                println!("DEBUG: GetKey on struct '{}', looking for field '{}', available fields: {:?}", type_name, key_str, fields.borrow().keys().collect::<Vec<_>>());
+                            }
+                            if let Some(value) = fields.borrow().get(&key_str) {
+                                if !self.clean_output {
+                                    println!("DEBUG: Found field '{}' = '{}'", key_str, value);
                                 }
-                            },
-                            // Fallback for other types
-                            _ => {
+                                self.stack.push(value.clone());
+                            } else {
                                 if !self.clean_output {
-                                    println!("DEBUG: GetKey not supported for value type: {:?}", object);
+                                    println!("DEBUG: Field '{}' not found in struct", key_str);
                                 }
                                 self.stack.push(Value::Null);
                             }
+                        },
+                        // Fallback for other types
+                        _ => {
+                            if !self.clean_output {
+                                println!("DEBUG: GetKey not supported for value type: {:?}", object);
+                            }
+                            self.stack.push(Value::Null);
                         }
                     }
-                },
-                IR::BitwiseAnd => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
-                            self.stack.push(Value::Integer(a_int & b_int));
-                        } else {
-                            self.stack.push(Value::Integer(0)); // Error case
-                        }
-                    }
-                },
-                IR::BitwiseOr => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
-                            self.stack.push(Value::Integer(a_int | b_int));
-                        } else {
-                            self.stack.push(Value::Integer(0)); // Error case
-                        }
-                    }
-                },
-                IR::BitwiseXor => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
-                            self.stack.push(Value::Integer(a_int ^ b_int));
-                        } else {
-                            self.stack.push(Value::Integer(0)); // Error case
-                        }
-                    }
-                },
-                IR::BitwiseNot => {
-                    if let Some(a) = self.stack.pop() {
-                        if let Some(a_int) = a.to_integer() {
-                            self.stack.push(Value::Integer(!a_int));
-                        } else {
-                            self.stack.push(Value::Integer(0)); // Error case
-                        }
+                }
+            },
+            IR::BitwiseAnd => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    if let Some((a_big, b_big)) = a.bigint_operands(&b) {
+                        self.stack.push(Value::bigint(a_big & b_big));
+                    } else if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
+                        self.stack.push(Value::Integer(a_int & b_int));
+                    } else {
+                        self.stack.push(Value::Integer(0)); // Error case
                     }
-                },
-                IR::LeftShift => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
-                            self.stack.push(Value::Integer(a_int << b_int));
-                        } else {
-                            self.stack.push(Value::Integer(0)); // Error case
-                        }
+                }
+            },
+            IR::BitwiseOr => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    if let Some((a_big, b_big)) = a.bigint_operands(&b) {
+                        self.stack.push(Value::bigint(a_big | b_big));
+                    } else if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
+                        self.stack.push(Value::Integer(a_int | b_int));
+                    } else {
+                        self.stack.push(Value::Integer(0)); // Error case
                     }
-                },
-                IR::RightShift => {
-                    if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
-                            self.stack.push(Value::Integer(a_int >> b_int));
-                        } else {
-                            self.stack.push(Value::Integer(0)); // Error case
-                        }
+                }
+            },
+            IR::BitwiseXor => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    if let Some((a_big, b_big)) = a.bigint_operands(&b) {
+                        self.stack.push(Value::bigint(a_big ^ b_big));
+                    } else if let (Some(a_int), Some(b_int)) = (a.to_integer(), b.to_integer()) {
+                        self.stack.push(Value::Integer(a_int ^ b_int));
+                    } else {
+                        self.stack.push(Value::Integer(0)); // Error case
                     }
-                },
-                IR::DefineFunction(_, _) | IR::Label(_) => {
-                    // Ignored at runtime
-                },
-                _ => {
-                    if !self.clean_output {
-                        println!("Unimplemented instruction: {:?}", instruction);
+                }
+            },
+            IR::BitwiseNot => {
+                if let Some(a) = self.stack.pop() {
+                    if let Value::BigInt(a_big) = a {
+                        self.stack.push(Value::bigint(!*a_big));
+                    } else if let Some(a_int) = a.to_integer() {
+                        self.stack.push(Value::Integer(!a_int));
+                    } else {
+                        self.stack.push(Value::Integer(0)); // Error case
                     }
                 }
-            }
-            pc += 1;
-        }
-
-        if !self.clean_output {
-            println!("Execution completed successfully");
-            println!("Final stack size: {}", self.stack.len());
-            println!("Variables in scope: {}", self.variables.len());
+            },
+            IR::LeftShift => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.shift_left(&b)?);
+                }
+            },
+            IR::RightShift => {
+                if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
+                    self.stack.push(a.shift_right(&b)?);
+                }
+            },
+            IR::DefineFunction(_, _) | IR::Label(_) => {
+                // Ignored at runtime
+            },
+            _ => {
+                if !self.clean_output {
+                    println!("Unimplemented instruction: {:?}", instruction);
+                }
+            }
         }
-        Ok(())
+        Ok(Step::Next)
     }
 
     /// Apply color formatting to text based on color name or hex code
@@ -778,7 +1223,7 @@ impl Runtime {
     }
 
     fn is_builtin(&self, name: &str) -> bool {
-        matches!(name, "print" | "println" | "printc" | "printlnc" | "input" | "read" | "write" | "len" | "append" | "remove" | "toint" | "tofloat" | "tostr" | "tobool" | "typeof" | "create_range" | "array_get" | "concat_string" | "load_var_by_name" | "Ok" | "Err" | "Some" | "None" | "is_ok" | "is_err" | "is_some" | "is_none" | "unwrap" | "unwrap_or")
+        matches!(name, "print" | "println" | "printc" | "printlnc" | "input" | "read" | "write" | "len" | "append" | "remove" | "toint" | "tobigint" | "tofloat" | "decimal" | "tostr" | "tobool" | "typeof" | "create_range" | "array_get" | "concat_string" | "load_var_by_name" | "Ok" | "Err" | "Some" | "None" | "is_ok" | "is_err" | "is_some" | "is_none" | "unwrap" | "unwrap_or" | "enumerate" | "zip" | "map" | "filter" | "take")
     }
 
     fn execute_builtin(&mut self, name: &str, arg_count: usize) -> Result<(), String> {
@@ -855,10 +1300,14 @@ impl Runtime {
             "len" => {
                 if let Some(value) = self.stack.pop() {
                     let len = match &value {
-                        Value::String(s) => s.len(),
+                        Value::String(s) => s.chars().count(),
+                        Value::Array(items) => items.borrow().len(),
+                        Value::Tuple(items) => items.len(),
+                        Value::Range { start, end, inclusive } => Self::range_len(*start, *end, *inclusive),
+                        Value::Map(map) => map.borrow().len(),
                         _ => value.to_string().len(),
                     };
-                    self.stack.push(Value::Integer(len as i64));
+                    self.stack.push(Value::Integer(i64::try_from(len).unwrap_or(i64::MAX)));
                 } else {
                     self.stack.push(Value::Integer(0));
                 }
@@ -875,6 +1324,16 @@ impl Runtime {
                     return Err("toint() requires one argument".to_string());
                 }
             },
+            "tobigint" => {
+                if let Some(value) = self.stack.pop() {
+                    match value.to_bigint() {
+                        Some(big) => self.stack.push(Value::bigint(big)),
+                        None => return Err(format!("Cannot convert '{}' to bigint", value)),
+                    }
+                } else {
+                    return Err("tobigint() requires one argument".to_string());
+                }
+            },
             "tofloat" => {
                 if let Some(value) = self.stack.pop() {
                     if let Some(float_val) = value.to_number() {
@@ -886,6 +1345,16 @@ impl Runtime {
                     return Err("tofloat() requires one argument".to_string());
                 }
             },
+            "decimal" => {
+                if let Some(value) = self.stack.pop() {
+                    match value.to_decimal() {
+                        Some(decimal) => self.stack.push(Value::Decimal(decimal)),
+                        None => return Err(format!("Cannot convert '{}' to decimal", value)),
+                    }
+                } else {
+                    return Err("decimal() requires one argument".to_string());
+                }
+            },
             "tostr" => {
                 if let Some(value) = self.stack.pop() {
                     // Everything can be converted to string
@@ -903,37 +1372,26 @@ impl Runtime {
             },
             "typeof" => {
                 if let Some(value) = self.stack.pop() {
-                    let type_name = match value {
-                        Value::Integer(_) => "int",
-                        Value::Number(_) => "float",
-                        Value::String(_) => "str",
-                        Value::Boolean(_) => "bool",
-                        Value::Array(_) => "array",
-                        Value::Map(_) => "map",
-                        Value::Struct { ref type_name, .. } => type_name.as_str(),
-                        Value::Result { .. } => "Result",
-                        Value::Option { .. } => "Option",
-                        Value::Null => "null",
-                    };
-                    self.stack.push(Value::String(type_name.to_string()));
+                    self.stack.push(Value::String(value.type_name().to_string()));
                 } else {
                     return Err("typeof() requires one argument".to_string());
                 }
             },
             "create_range" => {
-                // Create a range object from start, end, and inclusive flag
+                // Create a lazy range from start, end, and inclusive flag
                 if arg_count >= 3 {
                     let inclusive = self.stack.pop().unwrap_or(Value::Boolean(false));
                     let end = self.stack.pop().unwrap_or(Value::Integer(0));
                     let start = self.stack.pop().unwrap_or(Value::Integer(0));
-                    
-                    // Store range as a formatted string "start..end" or "start..=end"
-                    let range_str = if inclusive.is_truthy() {
-                        format!("{}..={}", start, end)
-                    } else {
-                        format!("{}..{}", start, end)
-                    };
-                    self.stack.push(Value::String(range_str));
+
+                    match (start.to_integer(), end.to_integer()) {
+                        (Some(start), Some(end)) => self.stack.push(Value::Range {
+                            start,
+                            end,
+                            inclusive: inclusive.is_truthy(),
+                        }),
+                        _ => return Err(format!("Range bounds must be integers, got {} and {}", start, end)),
+                    }
                 } else {
                     return Err("create_range() requires 3 arguments (start, end, inclusive)".to_string());
                 }
@@ -1067,6 +1525,58 @@ impl Runtime {
                     return Err("unwrap_or() requires 2 arguments".to_string());
                 }
             },
+            "enumerate" => {
+                if arg_count >= 1 {
+                    let iterable = self.stack.pop().unwrap_or(Value::Null);
+                    let inner = Box::new(self.iter_state(iterable)?);
+                    self.stack.push(Value::Iterator(Box::new(IterState::Enumerate { inner, count: 0 })));
+                } else {
+                    return Err("enumerate() requires 1 argument".to_string());
+                }
+            },
+            "zip" => {
+                if arg_count >= 2 {
+                    let right = self.stack.pop().unwrap_or(Value::Null);
+                    let left = self.stack.pop().unwrap_or(Value::Null);
+                    let state = IterState::Zip {
+                        left: Box::new(self.iter_state(left)?),
+                        right: Box::new(self.iter_state(right)?),
+                        left_item: None,
+                    };
+                    self.stack.push(Value::Iterator(Box::new(state)));
+                } else {
+                    return Err("zip() requires 2 arguments".to_string());
+                }
+            },
+            "map" | "filter" => {
+                if arg_count >= 2 {
+                    let function = Box::new(self.stack.pop().unwrap_or(Value::Null));
+                    let iterable = self.stack.pop().unwrap_or(Value::Null);
+                    let inner = Box::new(self.iter_state(iterable)?);
+                    let state = if name == "map" {
+                        IterState::Map { inner, function, waiting: false }
+                    } else {
+                        IterState::Filter { inner, function, candidate: None }
+                    };
+                    self.stack.push(Value::Iterator(Box::new(state)));
+                } else {
+                    return Err(format!("{}() requires 2 arguments", name));
+                }
+            },
+            "take" => {
+                if arg_count >= 2 {
+                    let count = self.stack.pop().unwrap_or(Value::Null);
+                    let iterable = self.stack.pop().unwrap_or(Value::Null);
+                    let remaining = match count.to_integer() {
+                        Some(n) if n >= 0 => n as usize,
+                        _ => return Err(format!("take() count must be a non-negative integer, got '{}'", count)),
+                    };
+                    let inner = Box::new(self.iter_state(iterable)?);
+                    self.stack.push(Value::Iterator(Box::new(IterState::Take { inner, remaining })));
+                } else {
+                    return Err("take() requires 2 arguments".to_string());
+                }
+            },
             _ => {
                 // Pop arguments for unimplemented builtins
                 for _ in 0..arg_count {
@@ -1080,6 +1590,8 @@ impl Runtime {
 
     /// Helper method to call user-defined functions
     fn call_user_function(&mut self, name: &str, arg_count: usize, pc: &mut usize) -> Result<(), String> {
+        let named = std::mem::take(&mut self.pending_named_args);
+
         // User-defined function call - collect arguments from stack
         let mut args = Vec::new();
         for _ in 0..arg_count {
@@ -1101,20 +1613,11 @@ impl Runtime {
             let mut func_variables = HashMap::new();
             
             // If we have parameter names stored, bind arguments to parameters
-            if let Some(param_names) = self.function_params.get(name) {
+            if let Some(params) = self.function_params.get(name) {
                 if !self.clean_output {
-                    println!("[DEBUG] Binding {} parameters for function '{}'", param_names.len(), name);
-                }
-                for (i, param_name) in param_names.iter().enumerate() {
-                    if i < args.len() {
-                        if !self.clean_output {
-                            println!("[DEBUG] Binding param '{}' = '{}'", param_name, args[i]);
-                        }
-                        func_variables.insert(param_name.clone(), args[i].clone());
-                    } else {
-                        func_variables.insert(param_name.clone(), Value::Null);
-                    }
+                    println!("[DEBUG] Binding {} parameters for function '{}'", params.len(), name);
                 }
+                Self::bind_parameters(name, params, &args, &named, &mut func_variables)?;
             } else {
                 if !self.clean_output {
                     println!("[DEBUG] No param names registered for function '{}', using generic names", name);
@@ -1140,6 +1643,760 @@ impl Runtime {
         Ok(())
     }
     
+    /// Bind call arguments to parameters, checking the argument count.
+    /// The last `named.len()` arguments are bound by name; a variadic parameter collects
+    /// leftover positional arguments into an array, and a parameter with a default is
+    /// filled in by the function body when it is left unbound.
+    fn bind_parameters(name: &str, params: &[ParamSpec], args: &[Value], named: &[String], func_variables: &mut HashMap<String, Value>) -> Result<(), String> {
+        let name = function_display_name(name);
+        let rest_param = params.last().filter(|param| param.variadic);
+        let fixed_params = &params[..params.len() - rest_param.is_some() as usize];
+
+        let positional_count = args.len().saturating_sub(named.len());
+        let (positional, named_values) = args.split_at(positional_count);
+
+        if rest_param.is_none() && positional.len() > fixed_params.len() {
+            let required = fixed_params.iter().filter(|param| !param.has_default).count();
+            let expected = if required == fixed_params.len() {
+                required.to_string()
+            } else {
+                format!("{} to {}", required, fixed_params.len())
+            };
+            return Err(format!("Function '{}' expects {} argument(s) but {} were given", name, expected, positional.len()));
+        }
+
+        let mut bound = vec![false; fixed_params.len()];
+        for (i, arg) in positional.iter().take(fixed_params.len()).enumerate() {
+            func_variables.insert(fixed_params[i].name.clone(), arg.clone());
+            bound[i] = true;
+        }
+
+        for (arg_name, arg) in named.iter().zip(named_values) {
+            let index = fixed_params.iter().position(|param| param.name == *arg_name)
+                .ok_or_else(|| format!("Function '{}' has no parameter named '{}'", name, arg_name))?;
+            if bound[index] {
+                return Err(format!("Function '{}' got multiple values for argument '{}'", name, arg_name));
+            }
+            func_variables.insert(arg_name.clone(), arg.clone());
+            bound[index] = true;
+        }
+
+        for (param, _) in fixed_params.iter().zip(bound).filter(|(_, bound)| !bound) {
+            if !param.has_default {
+                return Err(format!("Function '{}' is missing argument '{}'", name, param.name));
+            }
+            // Captured variables must not hide a missing argument from the default initializer
+            func_variables.remove(&param.name);
+        }
+
+        if let Some(rest_param) = rest_param {
+            let extra = positional.get(fixed_params.len()..).unwrap_or_default();
+            func_variables.insert(rest_param.name.clone(), Value::array(extra.to_vec()));
+        }
+        Ok(())
+    }
+
+    /// Builtin and stdlib functions only take positional arguments
+    fn reject_named_args(&mut self, name: &str) -> Result<(), String> {
+        if self.pending_named_args.is_empty() {
+            return Ok(());
+        }
+        self.pending_named_args.clear();
+        Err(format!("Function '{}' does not accept named arguments", name))
+    }
+
+    /// Leave the current frame, or unwind frames up to the nearest exception handler,
+    /// returning the address to continue at. A frame with pending deferred blocks runs
+    /// the most recent one first; its `EndDefer` resumes the exit from there.
+    fn exit_frame(&mut self, mut exit: FrameExit) -> Result<usize, String> {
+        loop {
+            let call_depth = self.call_stack.len();
+
+            // Thrown values stop at the frame that installed the nearest handler
+            if !matches!(exit, FrameExit::Return(_)) {
+                let handler_depth = self.exception_handlers.last().map(|h| h.call_depth);
+                if call_depth == 0 || handler_depth.is_some_and(|depth| depth >= call_depth) {
+                    return self.throw_value(exit);
+                }
+            }
+
+            // An exit started from deferred code replaces the one already in progress
+            while self.frame_exits.last().is_some_and(|(depth, _)| *depth >= call_depth) {
+                self.frame_exits.pop();
+            }
+
+            if self.defers.last().is_some_and(|d| d.call_depth == call_depth)
+                && let Some(defer) = self.defers.pop()
+            {
+                self.scopes.push(BlockScope {
+                    call_depth,
+                    shadowed: Vec::new(),
+                    constants_len: self.constants.len(),
+                });
+                for (name, value) in defer.captured {
+                    self.declare_variable(&name, value, false);
+                }
+                self.frame_exits.push((call_depth, exit));
+                return Ok(defer.addr);
+            }
+
+            let Some((return_addr, _)) = self.call_stack.pop() else {
+                return self.throw_value(exit);
+            };
+            // Handlers, block scopes, constants and defers of the frame went away with it
+            let call_depth = self.call_stack.len();
+            while self.exception_handlers.last().is_some_and(|h| h.call_depth > call_depth) {
+                self.exception_handlers.pop();
+            }
+            while self.scopes.last().is_some_and(|s| s.call_depth > call_depth) {
+                self.scopes.pop();
+            }
+            while self.constants.last().is_some_and(|c| c.call_depth > call_depth) {
+                self.constants.pop();
+            }
+            while self.defers.last().is_some_and(|d| d.call_depth > call_depth) {
+                self.defers.pop();
+            }
+
+            match exit {
+                FrameExit::Return(value) => {
+                    // Don't restore variables - global variables stay in self.variables
+                    self.stack.push(value);
+                    return Ok(return_addr);
+                },
+                other => exit = other,
+            }
+        }
+    }
+
+    /// Hand a thrown value to the nearest exception handler, returning the catch
+    /// address. The value is left on the stack for the catch block to bind.
+    fn throw_value(&mut self, exit: FrameExit) -> Result<usize, String> {
+        let value = match exit {
+            FrameExit::Throw(value) | FrameExit::Return(value) => value,
+            FrameExit::Error(error) => match self.exception_handlers.last() {
+                Some(_) => Value::String(error),
+                None => return Err(error),
+            },
+        };
+        match self.exception_handlers.pop() {
+            Some(handler) => {
+                self.call_stack.truncate(handler.call_depth);
+                while self.constants.last().is_some_and(|c| c.call_depth > self.call_stack.len()) {
+                    self.constants.pop();
+                }
+                while self.scopes.len() > handler.scope_depth {
+                    if let Some(scope) = self.scopes.pop() {
+                        self.restore_scope(scope);
+                    }
+                }
+                self.stack.truncate(handler.stack_len);
+                self.stack.push(value);
+                // Operator calls cut short by the throw will never be resumed
+                let call_depth = self.call_stack.len();
+                self.operator_resumes.retain(|(depth, _, _)| *depth < call_depth);
+                Ok(handler.catch_addr)
+            },
+            None => Err(format!("Uncaught exception: {}", value)),
+        }
+    }
+
+    /// Bind a new variable in the current frame, recording what it shadows in
+    /// the innermost block scope
+    fn declare_variable(&mut self, name: &str, value: Value, is_const: bool) {
+        // Qualified module members always live in the global scope
+        let call_depth = if name.contains('.') { 0 } else { self.call_stack.len() };
+        let frame_vars = match self.call_stack.last_mut() {
+            Some((_, func_vars)) if call_depth > 0 => func_vars,
+            _ => &mut self.variables,
+        };
+        let frozen = if is_const { value.reachable_storage() } else { HashSet::new() };
+        let previous = frame_vars.insert(name.to_string(), value);
+
+        // Remember the outer binding the first time a scope declares the name
+        if let Some(scope) = self.scopes.last_mut()
+            && scope.call_depth == call_depth
+            && !scope.shadowed.iter().any(|(shadowed, _)| shadowed == name)
+        {
+            scope.shadowed.push((name.to_string(), previous));
+        }
+
+        if is_const || self.is_constant(name, call_depth) {
+            self.constants.push(ConstBinding { call_depth, name: name.to_string(), is_const, frozen });
+        }
+    }
+
+    /// Reject changes to the contents of a collection held by a live constant, at any
+    /// depth, which copies of the constant and of its elements share
+    fn check_not_frozen(&self, value: &Value) -> Result<(), String> {
+        let Some(storage) = value.storage_id() else {
+            return Ok(());
+        };
+        match self.constants.iter().find(|c| c.frozen.contains(&storage)) {
+            Some(constant) => Err(format!("Cannot modify the contents of constant '{}'", constant.name)),
+            None => Ok(()),
+        }
+    }
+
+    fn is_constant(&self, name: &str, call_depth: usize) -> bool {
+        self.constants.iter().rev()
+            .find(|c| c.call_depth == call_depth && c.name == name)
+            .is_some_and(|c| c.is_const)
+    }
+
+    /// Put back the bindings a block scope shadowed, dropping the ones it introduced
+    fn restore_scope(&mut self, scope: BlockScope) {
+        // Scopes of frames that already returned have nothing left to restore
+        if scope.call_depth != self.call_stack.len() {
+            return;
+        }
+        self.constants.truncate(scope.constants_len);
+        let frame_vars = match self.call_stack.last_mut() {
+            Some((_, func_vars)) => func_vars,
+            None => &mut self.variables,
+        };
+        for (name, previous) in scope.shadowed.into_iter().rev() {
+            match previous {
+                Some(value) => frame_vars.insert(name, value),
+                None => frame_vars.remove(&name),
+            };
+        }
+    }
+
+    /// Push `items[key]`, or null when the key is not a valid index
+    fn push_element(&mut self, arr: &[Value], key: &Value) {
+        if let Some(index) = key.to_integer() {
+            if let Some(index) = Self::resolve_index(index, arr.len()) {
+                self.stack.push(arr[index].clone());
+            } else {
+                if !self.clean_output {
+                    println!("DEBUG: Array index {} out of bounds (len: {})", index, arr.len());
+                }
+                self.stack.push(Value::Null);
+            }
+        } else {
+            if !self.clean_output {
+                println!("DEBUG: Invalid array index: {}", key);
+            }
+            self.stack.push(Value::Null);
+        }
+    }
+
+    /// Pop a spread argument array and push its elements as individual arguments
+    fn unpack_argument_array(&mut self) -> Result<usize, String> {
+        match self.stack.pop() {
+            Some(Value::Array(items)) => {
+                let items = items.borrow();
+                self.stack.extend(items.iter().cloned());
+                Ok(items.len())
+            },
+            _ => Err("Spread call requires an argument array".to_string()),
+        }
+    }
+
+    /// Pop `arg_count` call arguments from the stack, preserving their order
+    /// Pop `size` key-value pairs pushed key first, as for a map or struct literal
+    fn pop_entries(&mut self, size: usize) -> HashMap<String, Value> {
+        let mut fields = HashMap::new();
+        for _ in 0..size {
+            if let (Some(value), Some(key)) = (self.stack.pop(), self.stack.pop()) {
+                if !self.clean_output {
+                    println!("DEBUG: Pair - key: {}, value: {}", key, value);
+                }
+                // Popped last pair first, so an earlier duplicate key must not overwrite
+                fields.entry(key.to_string()).or_insert(value);
+            }
+        }
+        fields
+    }
+
+    fn pop_arguments(&mut self, arg_count: usize) -> Vec<Value> {
+        let mut args = Vec::with_capacity(arg_count);
+        for _ in 0..arg_count {
+            if let Some(arg) = self.stack.pop() {
+                args.push(arg);
+            }
+        }
+        args.reverse(); // Arguments are pushed in reverse order
+        args
+    }
+
+    /// Find a function value stored in a variable with the given name
+    fn lookup_function_value(&self, name: &str) -> Option<Value> {
+        let value = if let Some((_, func_vars)) = self.call_stack.last() {
+            func_vars.get(name).or_else(|| self.variables.get(name))
+        } else {
+            self.variables.get(name)
+        };
+
+        match value {
+            Some(function @ Value::Function { .. }) => Some(function.clone()),
+            _ => None,
+        }
+    }
+
+    /// Call a function value (lambda or named function) with already-collected arguments,
+    /// returning the address to jump to
+    fn call_function_value(&mut self, callee: Value, args: Vec<Value>, return_addr: usize) -> Result<usize, String> {
+        let named = std::mem::take(&mut self.pending_named_args);
+        let (name, captures) = match callee {
+            Value::Function { name, captures } => (name, captures),
+            other => return Err(format!("Cannot call non-function value '{}'", other)),
+        };
+
+        let func_addr = match self.functions.get(&name) {
+            Some(&addr) => addr,
+            None => return Err(format!("Function '{}' not found", function_display_name(&name))),
+        };
+
+        // Captured variables form the base of the new frame; parameters shadow them
+        let mut func_variables = captures;
+        if let Some(params) = self.function_params.get(&name) {
+            Self::bind_parameters(&name, params, &args, &named, &mut func_variables)?;
+        }
+
+        self.call_stack.push((return_addr, func_variables));
+        Ok(func_addr)
+    }
+
+    /// Advance the iterator stored in `name`, pushing its next item. Returns the address
+    /// to jump to when the iteration is done or has to call into Razen code first.
+    fn iter_next(&mut self, name: &str, end_addr: usize, pc: usize) -> Result<Option<usize>, String> {
+        let frame_vars = match self.call_stack.last_mut() {
+            Some((_, func_vars)) => func_vars,
+            None => &mut self.variables,
+        };
+        let mut state = match frame_vars.remove(name) {
+            Some(Value::Iterator(state)) => state,
+            _ => return Err(format!("'{}' is not an iterator", name)),
+        };
+
+        // A waiting iteration is resumed with the result of the call it made
+        let resume = if state.is_waiting() { self.stack.pop() } else { None };
+        let step = self.step_iterator(&mut state, resume);
+        let frame_vars = match self.call_stack.last_mut() {
+            Some((_, func_vars)) => func_vars,
+            None => &mut self.variables,
+        };
+        frame_vars.insert(name.to_string(), Value::Iterator(state));
+
+        match step? {
+            IterStep::Item(value) => {
+                self.stack.push(value);
+                Ok(None)
+            },
+            IterStep::Done => Ok(Some(end_addr)),
+            // The call returns to this instruction, which picks up its result
+            IterStep::Call(IterCall::Method(object, method)) => self.call_method_value(object, method, pc).map(Some),
+            IterStep::Call(IterCall::Function(function, args)) => self.call_function_value(function, args, pc).map(Some),
+        }
+    }
+
+    /// Call a parameterless method on a struct value, returning the address to jump to
+    fn call_method_value(&mut self, object: Value, method: &str, return_addr: usize) -> Result<usize, String> {
+        let full_method_name = match &object {
+            Value::Struct { type_name, .. } => format!("{}.{}", type_name, method),
+            other => return Err(format!("Cannot call {}() on '{}'", method, other)),
+        };
+        self.call_method_named(&full_method_name, object, Vec::new(), return_addr)
+    }
+
+    /// Call a method by its full `Type.method` name with `self` and positional arguments,
+    /// returning the address to jump to
+    fn call_method_named(&mut self, full_method_name: &str, object: Value, args: Vec<Value>, return_addr: usize) -> Result<usize, String> {
+        let func_addr = match self.functions.get(full_method_name) {
+            Some(&addr) => addr,
+            None => return Err(format!("Method '{}' not found", full_method_name)),
+        };
+
+        let mut func_variables = HashMap::new();
+        if let Some(params) = self.function_params.get(full_method_name) {
+            let method_params = match params.first() {
+                Some(first) if first.name == "self" => &params[1..],
+                _ => params,
+            };
+            Self::bind_parameters(full_method_name, method_params, &args, &[], &mut func_variables)?;
+        }
+        func_variables.insert("self".to_string(), object);
+        self.call_stack.push((return_addr, func_variables));
+        Ok(func_addr)
+    }
+
+    /// Full name of the method implementing an operator, if `value` is a struct defining it
+    fn operator_method(&self, value: &Value, method: &str) -> Option<String> {
+        match value {
+            Value::Struct { type_name, .. } => {
+                let full_method_name = format!("{}.{}", type_name, method);
+                self.functions.contains_key(&full_method_name).then_some(full_method_name)
+            },
+            _ => None,
+        }
+    }
+
+    /// Pick up the operator call made by the instruction at `pc` in the current frame
+    fn take_operator_resume(&mut self, pc: usize) -> Option<OperatorResume> {
+        let call_depth = self.call_stack.len();
+        match self.operator_resumes.last() {
+            Some((depth, addr, _)) if *depth == call_depth && *addr == pc => {
+                self.operator_resumes.pop().map(|(_, _, resume)| resume)
+            },
+            _ => None,
+        }
+    }
+
+    /// Call an operator method whose result the instruction at `pc` finishes off
+    fn call_operator_resuming(&mut self, method: &str, object: Value, args: Vec<Value>, pc: usize, resume: OperatorResume) -> Result<usize, String> {
+        self.operator_resumes.push((self.call_stack.len(), pc, resume));
+        self.call_method_named(method, object, args, pc)
+    }
+
+    /// Dispatch an operator to the `add`, `sub`, `mul`, `eq`, `lt`, `index` or `to_string`
+    /// method of a struct operand. The left operand decides, so `a > b` calls `b.lt(a)`
+    /// with the left operand's method. Returns the address to jump to, or None to let
+    /// the built-in operator run.
+    fn dispatch_operator(&mut self, instruction: &IR, pc: usize) -> Result<Option<usize>, String> {
+        if let Some(resume) = self.take_operator_resume(pc) {
+            let result = self.stack.pop().unwrap_or(Value::Null);
+            return match resume {
+                OperatorResume::Negate => {
+                    self.stack.push(Value::Boolean(!result.is_truthy()));
+                    Ok(Some(pc + 1))
+                },
+                OperatorResume::Stringified(after) => {
+                    self.stack.push(Value::String(result.to_string()));
+                    self.stack.extend(after);
+                    Ok(None)
+                },
+            };
+        }
+
+        let len = self.stack.len();
+        if len < 2 {
+            return Ok(None);
+        }
+        let (a, b) = (&self.stack[len - 2], &self.stack[len - 1]);
+
+        let method = match instruction {
+            IR::Add => "add",
+            IR::Subtract => "sub",
+            IR::Multiply => "mul",
+            IR::Equal | IR::NotEqual => "eq",
+            IR::LessThan | IR::GreaterThan | IR::LessEqual | IR::GreaterEqual => "lt",
+            IR::GetIndex => "index",
+            _ => return Ok(None),
+        };
+        let Some(full_method_name) = self.operator_method(a, method) else {
+            // Concatenating a struct with a string uses its to_string()
+            if matches!(instruction, IR::Add) {
+                if let (Value::String(_), Some(to_string)) = (a, self.operator_method(b, "to_string")) {
+                    let object = self.stack.pop().unwrap_or(Value::Null);
+                    let resume = OperatorResume::Stringified(None);
+                    return self.call_operator_resuming(&to_string, object, Vec::new(), pc, resume).map(Some);
+                }
+                if let (Some(to_string), Value::String(_)) = (self.operator_method(a, "to_string"), b) {
+                    let after = self.stack.pop();
+                    let object = self.stack.pop().unwrap_or(Value::Null);
+                    let resume = OperatorResume::Stringified(after);
+                    return self.call_operator_resuming(&to_string, object, Vec::new(), pc, resume).map(Some);
+                }
+            }
+            return Ok(None);
+        };
+
+        let b = self.stack.pop().unwrap_or(Value::Null);
+        let a = self.stack.pop().unwrap_or(Value::Null);
+        let target = match instruction {
+            IR::NotEqual | IR::GreaterEqual => {
+                self.call_operator_resuming(&full_method_name, a, vec![b], pc, OperatorResume::Negate)?
+            },
+            IR::LessEqual => {
+                self.call_operator_resuming(&full_method_name, b, vec![a], pc, OperatorResume::Negate)?
+            },
+            IR::GreaterThan => self.call_method_named(&full_method_name, b, vec![a], pc + 1)?,
+            _ => self.call_method_named(&full_method_name, a, vec![b], pc + 1)?,
+        };
+        Ok(Some(target))
+    }
+
+    /// Replace a struct on top of the stack with the result of its to_string() method
+    /// before the instruction at `pc` prints it
+    fn dispatch_to_string(&mut self, pc: usize) -> Result<Option<usize>, String> {
+        if let Some(resume) = self.take_operator_resume(pc) {
+            let result = self.stack.pop().unwrap_or(Value::Null);
+            self.stack.push(Value::String(result.to_string()));
+            if let OperatorResume::Stringified(after) = resume {
+                self.stack.extend(after);
+            }
+            return Ok(None);
+        }
+
+        let Some(to_string) = self.stack.last().and_then(|value| self.operator_method(value, "to_string")) else {
+            return Ok(None);
+        };
+        let object = self.stack.pop().unwrap_or(Value::Null);
+        let resume = OperatorResume::Stringified(None);
+        self.call_operator_resuming(&to_string, object, Vec::new(), pc, resume).map(Some)
+    }
+
+    /// Whether the receiver of a method call is a struct defining the method itself
+    fn receiver_has_method(&self, method: &str, arg_count: usize) -> bool {
+        match self.stack.len().checked_sub(arg_count).and_then(|i| self.stack.get(i)) {
+            Some(Value::Struct { type_name, .. }) => {
+                self.functions.contains_key(&format!("{}.{}", type_name, method))
+            },
+            _ => false,
+        }
+    }
+
+    /// Position of `index` in a sequence of `len` items, counting negative indices from the end
+    fn resolve_index(index: i64, len: usize) -> Option<usize> {
+        let resolved = if index < 0 { index + len as i64 } else { index };
+        (0..len as i64).contains(&resolved).then_some(resolved as usize)
+    }
+
+    /// Store `value` under `key` in a struct field, map entry or array element, in
+    /// place, returning the container. Negative array indices count from the end.
+    fn set_element(object: Value, key: Value, value: Value) -> Result<Value, String> {
+        match &object {
+            Value::Struct { type_name, fields } => {
+                let field = key.to_string();
+                match fields.borrow_mut().get_mut(&field) {
+                    Some(slot) => *slot = value,
+                    None => return Err(format!("Struct '{}' has no field '{}'", type_name, field)),
+                }
+            },
+            Value::Map(map) => {
+                map.borrow_mut().insert(key.to_string(), value);
+            },
+            Value::Array(items) => {
+                let Some(index) = key.to_integer() else {
+                    return Err(format!("Array index must be an integer, got '{}'", key));
+                };
+                let mut items = items.borrow_mut();
+                match Self::resolve_index(index, items.len()) {
+                    Some(resolved) => items[resolved] = value,
+                    None => return Err(Self::index_error(index, items.len(), "array")),
+                }
+            },
+            Value::String(_) => return Err("Strings are immutable; build a new string instead".to_string()),
+            Value::Tuple(_) => return Err("Tuples are immutable; build a new tuple instead".to_string()),
+            other => return Err(format!("Cannot assign to an element of '{}'", other)),
+        }
+        Ok(object)
+    }
+
+    /// Runtime error for an index outside a `container` of `length` items
+    fn index_error(index: i64, length: usize, container: &str) -> String {
+        DiagnosticKind::IndexOutOfBounds { index, length, container: container.to_string() }.title()
+    }
+
+    /// Take `object[start..end]` of an array, tuple or string (by character). Null bounds
+    /// default to the ends and negative bounds count from the end.
+    fn slice_value(object: Value, start: Value, end: Value, inclusive: bool) -> Result<Value, String> {
+        let len = match &object {
+            Value::Array(items) => items.borrow().len(),
+            Value::Tuple(items) => items.len(),
+            Value::String(s) => s.chars().count(),
+            other => return Err(format!("Cannot slice '{}'", other)),
+        };
+
+        let bound = |value: &Value, default: i64| -> Result<i64, String> {
+            match value {
+                Value::Null => Ok(default),
+                other => match other.to_integer() {
+                    Some(index) if index < 0 => Ok(index + len as i64),
+                    Some(index) => Ok(index),
+                    None => Err(format!("Slice index must be an integer, got '{}'", other)),
+                },
+            }
+        };
+        let from = bound(&start, 0)?;
+        let mut to = bound(&end, len as i64)?;
+        if inclusive {
+            to += 1;
+        }
+
+        let container = object.type_name();
+        if from < 0 || from > len as i64 {
+            return Err(Self::index_error(start.to_integer().unwrap_or(from), len, container));
+        }
+        if to > len as i64 {
+            return Err(Self::index_error(end.to_integer().unwrap_or(to), len, container));
+        }
+        if to < from {
+            let (start, end) = (start.to_integer().unwrap_or(from), end.to_integer().unwrap_or(to));
+            return Err(DiagnosticKind::ReversedSlice { start, end }.title());
+        }
+        let range = from as usize..to as usize;
+
+        Ok(match object {
+            Value::Array(items) => Value::array(items.borrow()[range].to_vec()),
+            Value::Tuple(items) => Value::Tuple(items[range].to_vec()),
+            Value::String(s) => Value::String(s.chars().skip(range.start).take(range.len()).collect()),
+            _ => unreachable!("checked above"),
+        })
+    }
+
+    fn range_len(start: i64, end: i64, inclusive: bool) -> usize {
+        // Widened, since the distance between two i64 bounds can overflow i64
+        let len = end as i128 - start as i128 + i128::from(inclusive);
+        usize::try_from(len.max(0)).unwrap_or(usize::MAX)
+    }
+
+    /// Start iterating over a value: arrays, tuples, strings (by character), maps
+    /// (entries in key order), ranges, iterators and structs defining iter() or next()
+    fn iter_state(&self, value: Value) -> Result<IterState, String> {
+        let state = match value {
+            // Iterating an array walks a snapshot of its elements
+            Value::Array(items) => IterState::Items { items: items.borrow().clone(), index: 0 },
+            Value::Tuple(items) => IterState::Items { items, index: 0 },
+            Value::String(s) => IterState::Items {
+                items: s.chars().map(|c| Value::String(c.to_string())).collect(),
+                index: 0,
+            },
+            Value::Map(map) => {
+                let mut entries: Vec<(String, Value)> = map.borrow().iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                IterState::Items {
+                    items: entries.into_iter()
+                        .map(|(key, value)| Value::Tuple(vec![Value::String(key), value]))
+                        .collect(),
+                    index: 0,
+                }
+            },
+            Value::Range { start, end, inclusive } => IterState::Range { next: start, end, inclusive },
+            Value::Iterator(state) => *state,
+            Value::Struct { ref type_name, .. } => {
+                if self.functions.contains_key(&format!("{}.next", type_name)) {
+                    IterState::Object { object: Box::new(value), waiting: false }
+                } else if self.functions.contains_key(&format!("{}.iter", type_name)) {
+                    IterState::Source { object: Box::new(value), waiting: false }
+                } else {
+                    return Err(format!("Type '{}' is not iterable: define an iter() or next() method", type_name));
+                }
+            },
+            other => return Err(format!("Cannot iterate over '{}'", other)),
+        };
+        Ok(state)
+    }
+
+    /// Advance an iteration by one item. `resume` carries the result of the call
+    /// the iteration was waiting for, if any.
+    fn step_iterator(&mut self, state: &mut IterState, mut resume: Option<Value>) -> Result<IterStep, String> {
+        match state {
+            IterState::Items { items, index } => {
+                if *index < items.len() {
+                    let item = std::mem::replace(&mut items[*index], Value::Null);
+                    *index += 1;
+                    Ok(IterStep::Item(item))
+                } else {
+                    Ok(IterStep::Done)
+                }
+            },
+            IterState::Range { next, end, inclusive } => {
+                let in_range = if *inclusive { *next <= *end } else { *next < *end };
+                if in_range {
+                    let item = *next;
+                    match next.checked_add(1) {
+                        Some(following) => *next = following,
+                        None => *inclusive = false, // yielded i64::MAX, the end of `..=i64::MAX`
+                    }
+                    Ok(IterStep::Item(Value::Integer(item)))
+                } else {
+                    Ok(IterStep::Done)
+                }
+            },
+            IterState::Object { object, waiting } => {
+                if !*waiting {
+                    *waiting = true;
+                    return Ok(IterStep::Call(IterCall::Method((**object).clone(), "next")));
+                }
+                *waiting = false;
+                match resume.unwrap_or(Value::Null) {
+                    Value::Option { is_some: true, value } => Ok(IterStep::Item(*value)),
+                    Value::Option { is_some: false, .. } | Value::Null => Ok(IterStep::Done),
+                    other => Err(format!("next() must return Some(value) or None, got '{}'", other)),
+                }
+            },
+            IterState::Source { object, waiting } => {
+                if !*waiting {
+                    *waiting = true;
+                    return Ok(IterStep::Call(IterCall::Method((**object).clone(), "iter")));
+                }
+                // iter() produced the value to iterate in its place
+                *state = self.iter_state(resume.unwrap_or(Value::Null))?;
+                self.step_iterator(state, None)
+            },
+            IterState::Enumerate { inner, count } => match self.step_iterator(inner, resume)? {
+                IterStep::Item(item) => {
+                    let index = *count;
+                    *count += 1;
+                    Ok(IterStep::Item(Value::Tuple(vec![Value::Integer(index), item])))
+                },
+                other => Ok(other),
+            },
+            IterState::Zip { left, right, left_item } => {
+                if left_item.is_none() {
+                    match self.step_iterator(left, resume.take())? {
+                        IterStep::Item(item) => *left_item = Some(Box::new(item)),
+                        other => return Ok(other),
+                    }
+                }
+                match self.step_iterator(right, resume)? {
+                    IterStep::Item(item) => {
+                        let first = left_item.take().map(|v| *v).unwrap_or(Value::Null);
+                        Ok(IterStep::Item(Value::Tuple(vec![first, item])))
+                    },
+                    other => Ok(other),
+                }
+            },
+            IterState::Map { inner, function, waiting } => {
+                if *waiting {
+                    *waiting = false;
+                    return Ok(IterStep::Item(resume.unwrap_or(Value::Null)));
+                }
+                match self.step_iterator(inner, resume)? {
+                    IterStep::Item(item) => {
+                        *waiting = true;
+                        Ok(IterStep::Call(IterCall::Function((**function).clone(), vec![item])))
+                    },
+                    other => Ok(other),
+                }
+            },
+            IterState::Filter { inner, function, candidate } => {
+                if candidate.is_some() {
+                    let keep = resume.take().is_some_and(|result| result.is_truthy());
+                    let item = candidate.take().map(|v| *v).unwrap_or(Value::Null);
+                    if keep {
+                        return Ok(IterStep::Item(item));
+                    }
+                }
+                // Otherwise test the next item; a rejected one comes back here to try the one after
+                match self.step_iterator(inner, resume.take())? {
+                    IterStep::Item(item) => {
+                        *candidate = Some(Box::new(item.clone()));
+                        Ok(IterStep::Call(IterCall::Function((**function).clone(), vec![item])))
+                    },
+                    other => Ok(other),
+                }
+            },
+            IterState::Take { inner, remaining } => {
+                if *remaining == 0 {
+                    return Ok(IterStep::Done);
+                }
+                match self.step_iterator(inner, resume)? {
+                    IterStep::Item(item) => {
+                        *remaining -= 1;
+                        Ok(IterStep::Item(item))
+                    },
+                    other => Ok(other),
+                }
+            },
+        }
+    }
+
     // Helper methods for RAIE adaptive engine
     
     /// Get variable value for specialized operations
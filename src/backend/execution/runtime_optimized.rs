@@ -144,17 +144,17 @@ impl OptimizedRuntime {
                 // Optimized arithmetic operations
                 IR::Add => {
                     if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.add(&b));
+                        self.stack.push(a.add(&b)?);
                     }
                 },
                 IR::Subtract => {
                     if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.subtract(&b));
+                        self.stack.push(a.subtract(&b)?);
                     }
                 },
                 IR::Multiply => {
                     if let (Some(b), Some(a)) = (self.stack.pop(), self.stack.pop()) {
-                        self.stack.push(a.multiply(&b));
+                        self.stack.push(a.multiply(&b)?);
                     }
                 },
                 IR::Divide => {
@@ -255,6 +255,16 @@ impl OptimizedRuntime {
                         }
                     }
                 },
+                IR::JumpIfNull(target) => {
+                    match self.stack.pop().unwrap_or(Value::Null) {
+                        value if value.is_nullish() => {
+                            pc = *target;
+                            continue;
+                        },
+                        Value::Option { value, .. } => self.stack.push(*value),
+                        value => self.stack.push(value),
+                    }
+                },
                 IR::Call(name, arg_count) => {
                     if self.is_builtin(name) {
                         self.execute_builtin(name, *arg_count)?;
@@ -2,20 +2,44 @@
 //! High-performance value representation for runtime
 
 use std::fmt;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+
+/// Heap storage of arrays, maps and structs. Copies of such a value share the
+/// storage, so changes made through one copy are seen by all of them;
+/// `Value::deep_clone` makes an independent copy.
+pub type Shared<T> = Rc<RefCell<T>>;
 
 /// Optimized value type for runtime - avoids string conversions
 #[derive(Debug, Clone)]
 pub enum Value {
     Number(f64),
     Integer(i64),
+    // Arbitrary-precision integer, from big literals, `tobigint()` or arithmetic with one
+    BigInt(Box<BigInt>),
+    // Exact fixed-point number for money, from 12.50d literals or `decimal()`
+    Decimal(Decimal),
     String(String),
     Boolean(bool),
-    Array(Vec<Value>),
-    Map(HashMap<String, Value>),
+    Array(Shared<Vec<Value>>),
+    Tuple(Vec<Value>),
+    // Lazy integer range: start..end or start..=end
+    Range {
+        start: i64,
+        end: i64,
+        inclusive: bool,
+    },
+    // Lazy iteration in progress, produced by the iterator adapters
+    Iterator(Box<IterState>),
+    Map(Shared<HashMap<String, Value>>),
     Struct {
         type_name: String,
-        fields: HashMap<String, Value>,
+        fields: Shared<HashMap<String, Value>>,
     },
     // Result type: Ok(value) or Err(error)
     Result {
@@ -27,10 +51,186 @@ pub enum Value {
         is_some: bool,
         value: Box<Value>,
     },
+    // Enum value: a variant of a user-declared enum with its payload
+    Enum {
+        type_name: String,
+        variant: String,
+        payload: Vec<Value>,
+    },
+    // Function value: named function or lambda with its captured variables
+    Function {
+        name: String,
+        captures: HashMap<String, Value>,
+    },
     Null,
 }
 
+/// Position of a lazy iteration. The runtime steps it one item at a time; states
+/// that need to call back into Razen code (`next()`, `iter()`, adapter closures)
+/// are waiting while that call runs.
+#[derive(Debug, Clone)]
+pub enum IterState {
+    Items { items: Vec<Value>, index: usize }, // arrays, tuples, string characters and map entries
+    Range { next: i64, end: i64, inclusive: bool },
+    Object { object: Box<Value>, waiting: bool }, // struct with a next() method
+    Source { object: Box<Value>, waiting: bool }, // struct whose iter() has not been called yet
+    Enumerate { inner: Box<IterState>, count: i64 },
+    Zip { left: Box<IterState>, right: Box<IterState>, left_item: Option<Box<Value>> },
+    Map { inner: Box<IterState>, function: Box<Value>, waiting: bool },
+    Filter { inner: Box<IterState>, function: Box<Value>, candidate: Option<Box<Value>> },
+    Take { inner: Box<IterState>, remaining: usize },
+}
+
+impl IterState {
+    /// Whether a call made on behalf of this iteration is still running, so the
+    /// next step receives its result
+    pub fn is_waiting(&self) -> bool {
+        match self {
+            IterState::Items { .. } | IterState::Range { .. } => false,
+            IterState::Object { waiting, .. } | IterState::Source { waiting, .. } => *waiting,
+            IterState::Map { inner, waiting, .. } => *waiting || inner.is_waiting(),
+            IterState::Filter { inner, candidate, .. } => candidate.is_some() || inner.is_waiting(),
+            IterState::Zip { left, right, left_item } => {
+                if left_item.is_some() { right.is_waiting() } else { left.is_waiting() }
+            }
+            IterState::Enumerate { inner, .. } | IterState::Take { inner, .. } => inner.is_waiting(),
+        }
+    }
+}
+
 impl Value {
+    /// Create a bigint value
+    pub fn bigint(value: BigInt) -> Value {
+        Value::BigInt(Box::new(value))
+    }
+
+    /// Create an array value with fresh storage
+    pub fn array(items: Vec<Value>) -> Value {
+        Value::Array(Rc::new(RefCell::new(items)))
+    }
+
+    /// Create a map value with fresh storage
+    pub fn map(entries: HashMap<String, Value>) -> Value {
+        Value::Map(Rc::new(RefCell::new(entries)))
+    }
+
+    /// Create a struct value with fresh storage
+    pub fn new_struct(type_name: String, fields: HashMap<String, Value>) -> Value {
+        Value::Struct {
+            type_name,
+            fields: Rc::new(RefCell::new(fields)),
+        }
+    }
+
+    /// Copy of the value that shares no storage with it, for `.clone()`
+    pub fn deep_clone(&self) -> Value {
+        self.deep_clone_with(&mut HashMap::new())
+    }
+
+    /// `deep_clone`, given the copies made so far of each array, map and struct. Storage
+    /// reached twice, or through a cycle, is copied once, so the copy keeps its shape.
+    fn deep_clone_with(&self, copies: &mut HashMap<usize, Value>) -> Value {
+        if let Some(copy) = self.storage_id().and_then(|id| copies.get(&id)) {
+            return copy.clone();
+        }
+        // The copy's storage is registered before it is filled, so a cycle finds it
+        let copy_entries = |entries: &Shared<HashMap<String, Value>>, copies: &mut HashMap<usize, Value>| {
+            entries.borrow().iter()
+                .map(|(key, value)| (key.clone(), value.deep_clone_with(copies)))
+                .collect()
+        };
+        match self {
+            Value::Array(items) => {
+                let storage = Rc::new(RefCell::new(Vec::new()));
+                copies.insert(storage_address(items), Value::Array(storage.clone()));
+                let copied = items.borrow().iter().map(|item| item.deep_clone_with(copies)).collect();
+                *storage.borrow_mut() = copied;
+                Value::Array(storage)
+            }
+            Value::Map(map) => {
+                let storage = Rc::new(RefCell::new(HashMap::new()));
+                copies.insert(storage_address(map), Value::Map(storage.clone()));
+                *storage.borrow_mut() = copy_entries(map, copies);
+                Value::Map(storage)
+            }
+            Value::Struct { type_name, fields } => {
+                let storage = Rc::new(RefCell::new(HashMap::new()));
+                let copy = Value::Struct { type_name: type_name.clone(), fields: storage.clone() };
+                copies.insert(storage_address(fields), copy.clone());
+                *storage.borrow_mut() = copy_entries(fields, copies);
+                copy
+            }
+            Value::Tuple(items) => Value::Tuple(items.iter().map(|item| item.deep_clone_with(copies)).collect()),
+            Value::Result { is_ok, value } => Value::Result { is_ok: *is_ok, value: Box::new(value.deep_clone_with(copies)) },
+            Value::Option { is_some, value } => Value::Option { is_some: *is_some, value: Box::new(value.deep_clone_with(copies)) },
+            Value::Enum { type_name, variant, payload } => Value::Enum {
+                type_name: type_name.clone(),
+                variant: variant.clone(),
+                payload: payload.iter().map(|item| item.deep_clone_with(copies)).collect(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Address of the storage an array, map or struct shares with its copies
+    pub fn storage_id(&self) -> Option<usize> {
+        match self {
+            Value::Array(items) => Some(storage_address(items)),
+            Value::Map(map) => Some(storage_address(map)),
+            Value::Struct { fields, .. } => Some(storage_address(fields)),
+            _ => None,
+        }
+    }
+
+    /// Storage of every array, map and struct reachable from the value, its own included
+    pub fn reachable_storage(&self) -> HashSet<usize> {
+        let mut found = HashSet::new();
+        self.collect_storage(&mut found);
+        found
+    }
+
+    fn collect_storage(&self, found: &mut HashSet<usize>) {
+        if let Some(id) = self.storage_id()
+            && !found.insert(id)
+        {
+            return; // already visited, possibly through a cycle
+        }
+        match self {
+            Value::Array(items) => items.borrow().iter().for_each(|item| item.collect_storage(found)),
+            Value::Map(entries) | Value::Struct { fields: entries, .. } => {
+                entries.borrow().values().for_each(|value| value.collect_storage(found))
+            }
+            Value::Tuple(items) | Value::Enum { payload: items, .. } => {
+                items.iter().for_each(|item| item.collect_storage(found))
+            }
+            Value::Result { value, .. } | Value::Option { value, .. } => value.collect_storage(found),
+            _ => {}
+        }
+    }
+
+    /// Name of the value's runtime type, as reported by `typeof`
+    pub fn type_name(&self) -> &str {
+        match self {
+            Value::Integer(_) => "int",
+            Value::BigInt(_) => "bigint",
+            Value::Decimal(_) => "decimal",
+            Value::Number(_) => "float",
+            Value::String(_) => "str",
+            Value::Boolean(_) => "bool",
+            Value::Array(_) => "array",
+            Value::Tuple(_) => "tuple",
+            Value::Range { .. } => "range",
+            Value::Iterator(_) => "iterator",
+            Value::Map(_) => "map",
+            Value::Struct { type_name, .. } => type_name,
+            Value::Result { .. } => "Result",
+            Value::Option { .. } => "Option",
+            Value::Enum { type_name, .. } => type_name,
+            Value::Function { .. } => "function",
+            Value::Null => "null",
+        }
+    }
+
     /// Fast conversion to boolean for conditionals
     #[inline]
     pub fn is_truthy(&self) -> bool {
@@ -38,12 +238,19 @@ impl Value {
             Value::Boolean(b) => *b,
             Value::Number(n) => *n != 0.0,
             Value::Integer(i) => *i != 0,
+            Value::BigInt(b) => !b.is_zero(),
+            Value::Decimal(d) => !d.is_zero(),
             Value::String(s) => !s.is_empty() && s != "null" && s != "false" && s != "False",
-            Value::Array(arr) => !arr.is_empty(),
-            Value::Map(map) => !map.is_empty(),
+            Value::Array(arr) => !arr.borrow().is_empty(),
+            Value::Tuple(elements) => !elements.is_empty(),
+            Value::Range { start, end, inclusive } => if *inclusive { start <= end } else { start < end },
+            Value::Iterator(_) => true,
+            Value::Map(map) => !map.borrow().is_empty(),
             Value::Struct { .. } => true,
             Value::Result { is_ok, .. } => *is_ok,
             Value::Option { is_some, .. } => *is_some,
+            Value::Enum { .. } => true,
+            Value::Function { .. } => true,
             Value::Null => false,
         }
     }
@@ -100,6 +307,11 @@ impl Value {
         matches!(self, Value::Option { is_some: false, .. })
     }
 
+    /// Check if this is null or a None Option (the values `??` and `?.` skip over)
+    pub fn is_nullish(&self) -> bool {
+        matches!(self, Value::Null | Value::Option { is_some: false, .. })
+    }
+
     /// Unwrap a Result or Option, returning the inner value
     pub fn unwrap(&self) -> Result<Value, String> {
         match self {
@@ -121,48 +333,245 @@ impl Value {
         }
     }
 
-    /// Fast numeric addition
-    #[inline]
-    pub fn add(&self, other: &Value) -> Value {
+    /// Both operands as bigints, when one is a bigint and the other an int or bigint
+    pub fn bigint_operands(&self, other: &Value) -> Option<(BigInt, BigInt)> {
+        match (self, other) {
+            (Value::BigInt(_), Value::Integer(_) | Value::BigInt(_)) | (Value::Integer(_), Value::BigInt(_)) => {
+                Some((self.to_bigint()?, other.to_bigint()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Both operands as decimals, when one is a decimal and the other a number. Floats
+    /// cannot represent amounts like 0.10 exactly, so mixing them in is an error.
+    fn decimal_operands(&self, other: &Value) -> Option<Result<(Decimal, Decimal), String>> {
+        match (self, other) {
+            (Value::Decimal(_), Value::Number(_)) | (Value::Number(_), Value::Decimal(_)) => Some(Err(format!(
+                "Cannot mix decimal and float: {} and {} (convert the float with decimal())",
+                self, other
+            ))),
+            (Value::Decimal(_), Value::Integer(_) | Value::BigInt(_) | Value::Decimal(_))
+            | (Value::Integer(_) | Value::BigInt(_), Value::Decimal(_)) => {
+                Some(match (self.to_decimal(), other.to_decimal()) {
+                    (Some(a), Some(b)) => Ok((a, b)),
+                    _ => Err(format!("Decimal overflow: {} and {} do not fit in a decimal", self, other)),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Ordering of two numbers of which at least one is a decimal
+    fn decimal_order(&self, other: &Value) -> Option<Ordering> {
+        match self.decimal_operands(other) {
+            Some(Ok((a, b))) => Some(a.cmp(&b)),
+            Some(Err(_)) => self.to_number()?.partial_cmp(&other.to_number()?),
+            None => None,
+        }
+    }
+
+    /// Ordering of two numbers of which at least one is a bigint
+    fn bigint_order(&self, other: &Value) -> Option<Ordering> {
+        if let Some((a, b)) = self.bigint_operands(other) {
+            return Some(a.cmp(&b));
+        }
         match (self, other) {
-            (Value::Integer(a), Value::Integer(b)) => Value::Integer(a + b),
+            (Value::BigInt(_), Value::Number(_)) | (Value::Number(_), Value::BigInt(_)) => {
+                self.to_number()?.partial_cmp(&other.to_number()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Fast numeric addition; int overflow is an error instead of wrapping
+    #[inline]
+    pub fn add(&self, other: &Value) -> Result<Value, String> {
+        if let Some(operands) = self.decimal_operands(other) {
+            let (a, b) = operands?;
+            return a.checked_add(b).map(Value::Decimal).ok_or_else(|| decimal_overflow(&a, "+", &b));
+        }
+        if let Some((a, b)) = self.bigint_operands(other) {
+            return Ok(Value::bigint(a + b));
+        }
+        Ok(match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => {
+                Value::Integer(a.checked_add(*b).ok_or_else(|| overflow_error(*a, "+", *b))?)
+            }
             (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
             (Value::Integer(a), Value::Number(b)) => Value::Number(*a as f64 + b),
             (Value::Number(a), Value::Integer(b)) => Value::Number(a + *b as f64),
+            (Value::BigInt(a), Value::Number(b)) => Value::Number(bigint_to_f64(a) + b),
+            (Value::Number(a), Value::BigInt(b)) => Value::Number(a + bigint_to_f64(b)),
+            (Value::Array(a), Value::Array(b)) => {
+                let mut elements = a.borrow().clone();
+                elements.extend(b.borrow().iter().cloned());
+                Value::array(elements)
+            }
             _ => {
                 // String concatenation fallback
                 Value::String(format!("{}{}", self, other))
             }
-        }
+        })
     }
 
-    /// Fast numeric subtraction
+    /// Fast numeric subtraction; int overflow is an error instead of wrapping
     #[inline]
-    pub fn subtract(&self, other: &Value) -> Value {
-        match (self, other) {
-            (Value::Integer(a), Value::Integer(b)) => Value::Integer(a - b),
+    pub fn subtract(&self, other: &Value) -> Result<Value, String> {
+        if let Some(operands) = self.decimal_operands(other) {
+            let (a, b) = operands?;
+            return a.checked_sub(b).map(Value::Decimal).ok_or_else(|| decimal_overflow(&a, "-", &b));
+        }
+        if let Some((a, b)) = self.bigint_operands(other) {
+            return Ok(Value::bigint(a - b));
+        }
+        Ok(match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => {
+                Value::Integer(a.checked_sub(*b).ok_or_else(|| overflow_error(*a, "-", *b))?)
+            }
             (Value::Number(a), Value::Number(b)) => Value::Number(a - b),
             (Value::Integer(a), Value::Number(b)) => Value::Number(*a as f64 - b),
             (Value::Number(a), Value::Integer(b)) => Value::Number(a - *b as f64),
+            (Value::BigInt(a), Value::Number(b)) => Value::Number(bigint_to_f64(a) - b),
+            (Value::Number(a), Value::BigInt(b)) => Value::Number(a - bigint_to_f64(b)),
             _ => Value::Null,
-        }
+        })
     }
 
-    /// Fast numeric multiplication
+    /// Fast numeric multiplication; int overflow is an error instead of wrapping
     #[inline]
-    pub fn multiply(&self, other: &Value) -> Value {
-        match (self, other) {
-            (Value::Integer(a), Value::Integer(b)) => Value::Integer(a * b),
+    pub fn multiply(&self, other: &Value) -> Result<Value, String> {
+        if let Some(operands) = self.decimal_operands(other) {
+            let (a, b) = operands?;
+            return a.checked_mul(b).map(Value::Decimal).ok_or_else(|| decimal_overflow(&a, "*", &b));
+        }
+        if let Some((a, b)) = self.bigint_operands(other) {
+            return Ok(Value::bigint(a * b));
+        }
+        Ok(match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => {
+                Value::Integer(a.checked_mul(*b).ok_or_else(|| overflow_error(*a, "*", *b))?)
+            }
             (Value::Number(a), Value::Number(b)) => Value::Number(a * b),
             (Value::Integer(a), Value::Number(b)) => Value::Number(*a as f64 * b),
             (Value::Number(a), Value::Integer(b)) => Value::Number(a * *b as f64),
+            (Value::BigInt(a), Value::Number(b)) => Value::Number(bigint_to_f64(a) * b),
+            (Value::Number(a), Value::BigInt(b)) => Value::Number(a * bigint_to_f64(b)),
             _ => Value::Null,
+        })
+    }
+
+    /// Remainder of a decimal division, when either operand is a decimal
+    pub fn decimal_remainder(&self, other: &Value) -> Option<Result<Value, String>> {
+        let (a, b) = match self.decimal_operands(other)? {
+            Ok(operands) => operands,
+            Err(error) => return Some(Err(error)),
+        };
+        Some(match a.checked_rem(b) {
+            Some(remainder) => Ok(Value::Decimal(remainder)),
+            None => Err("Modulo by zero".to_string()),
+        })
+    }
+
+    /// Exponentiation. Ints, bigints and decimals raised to a non-negative integer stay
+    /// exact, with overflow reported as an error; everything else is computed as float.
+    pub fn power(&self, other: &Value) -> Result<Value, String> {
+        if let (Value::Decimal(base), Value::Integer(exponent)) = (self, other)
+            && *exponent >= 0
+        {
+            let Ok(power) = u32::try_from(*exponent) else {
+                return Err(format!("Decimal exponent {} is too large (at most {})", exponent, u32::MAX));
+            };
+            return decimal_pow(*base, power)
+                .map(Value::Decimal)
+                .ok_or_else(|| format!("Decimal overflow: {} ** {} does not fit in a decimal", base, exponent));
+        }
+        if let Some((base, exponent)) = self.bigint_operands(other)
+            && !exponent.is_negative()
+        {
+            // Raising 0, 1 or -1 stays small however large the exponent
+            return exponent
+                .to_u64()
+                .filter(|exponent| base.bits() <= 1 || base.bits().saturating_mul(*exponent) <= MAX_BIGINT_BITS)
+                .map(|exponent| Value::bigint(num_traits::pow::Pow::pow(&base, exponent)))
+                .ok_or_else(|| format!("Bigint overflow: {} ** {} would exceed {} bits", base, exponent, MAX_BIGINT_BITS));
+        }
+        if let (Value::Integer(base), Value::Integer(exponent)) = (self, other)
+            && *exponent >= 0
+        {
+            return u32::try_from(*exponent)
+                .ok()
+                .and_then(|exponent| base.checked_pow(exponent))
+                .map(Value::Integer)
+                .ok_or_else(|| overflow_error(*base, "**", *exponent));
+        }
+        match (self.to_number(), other.to_number()) {
+            (Some(base), Some(exponent)) => Ok(Value::Number(base.powf(exponent))),
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Left shift; bits shifted out of an int are an overflow error
+    pub fn shift_left(&self, other: &Value) -> Result<Value, String> {
+        let amount = shift_amount(other)?;
+        match self {
+            Value::BigInt(a) if !a.is_zero() && a.bits().saturating_add(amount as u64) > MAX_BIGINT_BITS => {
+                Err(format!("Bigint overflow: {} << {} would exceed {} bits", a, amount, MAX_BIGINT_BITS))
+            }
+            Value::BigInt(a) => Ok(Value::bigint(&**a << amount)),
+            _ => {
+                let a = self.to_integer().unwrap_or(0);
+                match amount {
+                    _ if a == 0 => Ok(Value::Integer(0)),
+                    0..=63 if (a << amount) >> amount == a => Ok(Value::Integer(a << amount)),
+                    _ => Err(overflow_error(a, "<<", amount as i64)),
+                }
+            }
+        }
+    }
+
+    /// Arithmetic right shift
+    pub fn shift_right(&self, other: &Value) -> Result<Value, String> {
+        let amount = shift_amount(other)?;
+        match self {
+            Value::BigInt(a) => Ok(Value::bigint(&**a >> amount)),
+            _ => {
+                let a = self.to_integer().unwrap_or(0);
+                Ok(Value::Integer(a >> amount.min(63)))
+            }
+        }
+    }
+
+    /// Arithmetic negation; negating the smallest int is an overflow error
+    pub fn negate(&self) -> Result<Value, String> {
+        match self {
+            Value::Number(n) => Ok(Value::Number(-n)),
+            Value::Integer(i) => i
+                .checked_neg()
+                .map(Value::Integer)
+                .ok_or_else(|| format!("Integer overflow: -({}) does not fit in int (use tobigint() for larger values)", i)),
+            Value::BigInt(b) => Ok(Value::bigint(-&**b)),
+            Value::Decimal(d) => Ok(Value::Decimal(-*d)),
+            _ => Ok(Value::Null),
         }
     }
 
-    /// Fast numeric division
+    /// Fast numeric division. Bigints divide to a bigint, rounding towards negative infinity.
     #[inline]
     pub fn divide(&self, other: &Value) -> Result<Value, String> {
+        if let Some(operands) = self.decimal_operands(other) {
+            let (a, b) = operands?;
+            if b.is_zero() {
+                return Err("Division by zero".to_string());
+            }
+            return a.checked_div(b).map(Value::Decimal).ok_or_else(|| decimal_overflow(&a, "/", &b));
+        }
+        if let Some((a, b)) = self.bigint_operands(other) {
+            if b.is_zero() {
+                return Err("Division by zero".to_string());
+            }
+            return Ok(Value::bigint(num_integer::Integer::div_floor(&a, &b)));
+        }
         match (self, other) {
             (Value::Integer(a), Value::Integer(b)) => {
                 if *b == 0 {
@@ -192,6 +601,11 @@ impl Value {
                     Ok(Value::Number(a / *b as f64))
                 }
             }
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => match (self.to_number(), other.to_number()) {
+                (Some(a), Some(b)) if b != 0.0 => Ok(Value::Number(a / b)),
+                (Some(_), Some(_)) => Err("Division by zero".to_string()),
+                _ => Ok(Value::Null),
+            },
             _ => Ok(Value::Null),
         }
     }
@@ -205,6 +619,8 @@ impl Value {
             (Value::Integer(a), Value::Number(b)) => (*a as f64) < *b,
             (Value::Number(a), Value::Integer(b)) => *a < (*b as f64),
             (Value::String(a), Value::String(b)) => a < b,
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => self.decimal_order(other) == Some(Ordering::Less),
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => self.bigint_order(other) == Some(Ordering::Less),
             _ => false,
         }
     }
@@ -217,6 +633,10 @@ impl Value {
             (Value::Integer(a), Value::Number(b)) => (*a as f64) <= *b,
             (Value::Number(a), Value::Integer(b)) => *a <= (*b as f64),
             (Value::String(a), Value::String(b)) => a <= b,
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+                matches!(self.decimal_order(other), Some(Ordering::Less | Ordering::Equal))
+            }
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => matches!(self.bigint_order(other), Some(Ordering::Less | Ordering::Equal)),
             _ => false,
         }
     }
@@ -229,6 +649,8 @@ impl Value {
             (Value::Integer(a), Value::Number(b)) => (*a as f64) > *b,
             (Value::Number(a), Value::Integer(b)) => *a > (*b as f64),
             (Value::String(a), Value::String(b)) => a > b,
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => self.decimal_order(other) == Some(Ordering::Greater),
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => self.bigint_order(other) == Some(Ordering::Greater),
             _ => false,
         }
     }
@@ -241,12 +663,46 @@ impl Value {
             (Value::Integer(a), Value::Number(b)) => (*a as f64) >= *b,
             (Value::Number(a), Value::Integer(b)) => *a >= (*b as f64),
             (Value::String(a), Value::String(b)) => a >= b,
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+                matches!(self.decimal_order(other), Some(Ordering::Greater | Ordering::Equal))
+            }
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => matches!(self.bigint_order(other), Some(Ordering::Greater | Ordering::Equal)),
             _ => false,
         }
     }
 
     #[inline]
     pub fn equal(&self, other: &Value) -> bool {
+        self.equal_within(other, &mut Vec::new())
+    }
+
+    /// `equal`, given the pairs of arrays, maps and structs being compared on the way here.
+    /// Arrays, maps and structs compare by contents; meeting a pair again means both values
+    /// are cyclic, and what is left of the cycle is taken as equal.
+    fn equal_within(&self, other: &Value, comparing: &mut Vec<(usize, usize)>) -> bool {
+        if let (Some(a), Some(b)) = (self.storage_id(), other.storage_id()) {
+            if a == b || comparing.contains(&(a, b)) {
+                return true;
+            }
+            comparing.push((a, b));
+            let same_entries = |a: &HashMap<String, Value>, b: &HashMap<String, Value>, comparing: &mut Vec<(usize, usize)>| {
+                a.len() == b.len()
+                    && a.iter().all(|(key, value)| b.get(key).is_some_and(|other| value.equal_within(other, comparing)))
+            };
+            let equal = match (self, other) {
+                (Value::Array(a), Value::Array(b)) => {
+                    let (a, b) = (a.borrow(), b.borrow());
+                    a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| x.equal_within(y, comparing))
+                }
+                (Value::Map(a), Value::Map(b)) => same_entries(&a.borrow(), &b.borrow(), comparing),
+                (Value::Struct { type_name: a_type, fields: a }, Value::Struct { type_name: b_type, fields: b }) => {
+                    a_type == b_type && same_entries(&a.borrow(), &b.borrow(), comparing)
+                }
+                _ => false,
+            };
+            comparing.pop();
+            return equal;
+        }
         match (self, other) {
             (Value::Integer(a), Value::Integer(b)) => a == b,
             (Value::Number(a), Value::Number(b)) => a == b,
@@ -255,12 +711,34 @@ impl Value {
             (Value::String(a), Value::String(b)) => a == b,
             (Value::Boolean(a), Value::Boolean(b)) => a == b,
             (Value::Null, Value::Null) => true,
+            (Value::Tuple(a), Value::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.equal_within(y, comparing))
+            }
+            (
+                Value::Range { start: a_start, end: a_end, inclusive: a_inclusive },
+                Value::Range { start: b_start, end: b_end, inclusive: b_inclusive },
+            ) => a_start == b_start && a_end == b_end && a_inclusive == b_inclusive,
             (Value::Result { is_ok: a_ok, value: a_val }, Value::Result { is_ok: b_ok, value: b_val }) => {
-                a_ok == b_ok && a_val.equal(b_val)
+                a_ok == b_ok && a_val.equal_within(b_val, comparing)
             }
             (Value::Option { is_some: a_some, value: a_val }, Value::Option { is_some: b_some, value: b_val }) => {
-                a_some == b_some && (*a_some == false || a_val.equal(b_val))
+                a_some == b_some && (*a_some == false || a_val.equal_within(b_val, comparing))
+            }
+            (
+                Value::Enum { type_name: a_type, variant: a_variant, payload: a_payload },
+                Value::Enum { type_name: b_type, variant: b_variant, payload: b_payload },
+            ) => {
+                a_type == b_type
+                    && a_variant == b_variant
+                    && a_payload.len() == b_payload.len()
+                    && a_payload.iter().zip(b_payload).all(|(a, b)| a.equal_within(b, comparing))
+            }
+            (Value::Function { name: a, captures: a_captures }, Value::Function { name: b, captures: b_captures }) => {
+                a == b && a_captures.len() == b_captures.len()
+                    && a_captures.iter().all(|(key, value)| b_captures.get(key).is_some_and(|other| value.equal_within(other, comparing)))
             }
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => self.decimal_order(other) == Some(Ordering::Equal),
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => self.bigint_order(other) == Some(Ordering::Equal),
             _ => false,
         }
     }
@@ -271,6 +749,10 @@ impl Value {
         if let Ok(i) = s.parse::<i64>() {
             return Value::Integer(i);
         }
+        // Integers too large for int
+        if let Ok(b) = s.parse::<BigInt>() {
+            return Value::bigint(b);
+        }
         // Try float
         if let Ok(f) = s.parse::<f64>() {
             return Value::Number(f);
@@ -290,14 +772,21 @@ impl Value {
         match self {
             Value::Number(n) => Some(*n),
             Value::Integer(i) => Some(*i as f64),
+            Value::BigInt(b) => Some(bigint_to_f64(b)),
+            Value::Decimal(d) => d.to_f64(),
             Value::String(s) => s.parse::<f64>().ok(),
             Value::Boolean(true) => Some(1.0),
             Value::Boolean(false) => Some(0.0),
             Value::Array(_) => None,
+            Value::Tuple(_) => None,
+            Value::Range { .. } => None,
+            Value::Iterator(_) => None,
             Value::Map(_) => None,
             Value::Struct { .. } => None,
             Value::Result { .. } => None,
             Value::Option { .. } => None,
+            Value::Enum { .. } => None,
+            Value::Function { .. } => None,
             Value::Null => None,
         }
     }
@@ -307,64 +796,229 @@ impl Value {
     pub fn to_integer(&self) -> Option<i64> {
         match self {
             Value::Integer(i) => Some(*i),
+            Value::BigInt(b) => b.to_i64(),
+            Value::Decimal(d) => d.trunc().to_i64(),
             Value::Number(n) => Some(*n as i64),
             Value::String(s) => s.parse::<i64>().ok(),
             Value::Boolean(true) => Some(1),
             Value::Boolean(false) => Some(0),
             Value::Array(_) => None,
+            Value::Tuple(_) => None,
+            Value::Range { .. } => None,
+            Value::Iterator(_) => None,
             Value::Map(_) => None,
             Value::Struct { .. } => None,
             Value::Result { .. } => None,
             Value::Option { .. } => None,
+            Value::Enum { .. } => None,
+            Value::Function { .. } => None,
             Value::Null => None,
         }
     }
+
+    /// Convert to a bigint, for `tobigint()` and arithmetic on bigints
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        match self {
+            Value::Integer(i) => Some(BigInt::from(*i)),
+            Value::BigInt(b) => Some((**b).clone()),
+            Value::Decimal(d) => d.trunc().to_string().parse::<BigInt>().ok(),
+            Value::Number(n) => num_traits::FromPrimitive::from_f64(n.trunc()),
+            Value::String(s) => s.trim().parse::<BigInt>().ok(),
+            Value::Boolean(b) => Some(BigInt::from(*b as i64)),
+            _ => None,
+        }
+    }
+
+    /// Convert to a decimal, for `decimal()` and arithmetic on decimals. Strings keep
+    /// the places they are written with; floats are rounded to their shortest form.
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Decimal(d) => Some(*d),
+            Value::Integer(i) => Some(Decimal::from(*i)),
+            Value::BigInt(b) => Decimal::from_str_exact(&b.to_string()).ok(),
+            Value::Number(n) => n.to_string().parse::<Decimal>().ok(),
+            Value::String(s) => {
+                let s = s.trim();
+                Decimal::from_str_exact(s).or_else(|_| Decimal::from_scientific(s)).ok()
+            }
+            Value::Boolean(b) => Some(Decimal::from(*b as i64)),
+            _ => None,
+        }
+    }
+}
+
+/// Address of shared storage, identifying it among the values that share it
+fn storage_address<T>(storage: &Shared<T>) -> usize {
+    Rc::as_ptr(storage) as *const () as usize
+}
+
+/// Prefix of the names the compiler gives lambdas
+pub const LAMBDA_PREFIX: &str = "__lambda_";
+
+/// Name of a function as shown to the user: lambdas are all called `<lambda>`
+pub fn function_display_name(name: &str) -> &str {
+    if name.starts_with(LAMBDA_PREFIX) { "<lambda>" } else { name }
+}
+
+/// `base ** exponent` by repeated squaring, or None when it does not fit in a decimal
+fn decimal_pow(base: Decimal, exponent: u32) -> Option<Decimal> {
+    let (mut result, mut base, mut exponent) = (Decimal::ONE, base, exponent);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.checked_mul(base)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(result)
+}
+
+fn decimal_overflow(a: &Decimal, operator: &str, b: &Decimal) -> String {
+    format!("Decimal overflow: {} {} {} does not fit in a decimal", a, operator, b)
+}
+
+/// Nearest float to a bigint (infinite when out of range)
+fn bigint_to_f64(value: &BigInt) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Shift amount of `<<` and `>>`, which must be a non-negative integer
+/// Largest bigint `**` and `<<` will build, so a typo like `x << 100000000000` is an
+/// error rather than an attempt to allocate gigabytes
+const MAX_BIGINT_BITS: u64 = 1 << 24;
+
+fn shift_amount(value: &Value) -> Result<usize, String> {
+    match value.to_integer() {
+        Some(amount) if amount >= 0 => Ok(amount as usize),
+        _ => Err(format!("Shift amount must be a non-negative integer, got {}", value)),
+    }
+}
+
+fn overflow_error(a: i64, operator: &str, b: i64) -> String {
+    format!("Integer overflow: {} {} {} does not fit in int (use tobigint() for larger values)", a, operator, b)
 }
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_within(f, &mut Vec::new())
+    }
+}
+
+impl Value {
+    /// Display the value, given the arrays, maps and structs it sits inside. Meeting one
+    /// of those again means the value contains itself, which is shown as `[...]`
+    /// (`{...}` for maps and structs) instead of being followed forever.
+    fn write_within(&self, f: &mut fmt::Formatter<'_>, enclosing: &mut Vec<usize>) -> fmt::Result {
+        let Some(id) = self.storage_id() else {
+            return self.write_contents(f, enclosing);
+        };
+        if enclosing.contains(&id) {
+            return match self {
+                Value::Array(_) => write!(f, "[...]"),
+                Value::Struct { type_name, .. } => write!(f, "{} {{...}}", type_name),
+                _ => write!(f, "{{...}}"),
+            };
+        }
+        enclosing.push(id);
+        let result = self.write_contents(f, enclosing);
+        enclosing.pop();
+        result
+    }
+
+    fn write_contents(&self, f: &mut fmt::Formatter<'_>, enclosing: &mut Vec<usize>) -> fmt::Result {
+        let mut write_items = |f: &mut fmt::Formatter<'_>, items: &[Value]| -> fmt::Result {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                item.write_within(f, enclosing)?;
+            }
+            Ok(())
+        };
         match self {
             Value::Number(n) => {
                 // Format numbers nicely - remove trailing .0 for whole numbers
-                if n.fract() == 0.0 && n.is_finite() {
+                if n.fract() == 0.0 && n.is_finite() && n.abs() < i64::MAX as f64 {
                     write!(f, "{}", *n as i64)
                 } else {
                     write!(f, "{}", n)
                 }
             }
             Value::Integer(i) => write!(f, "{}", i),
+            Value::BigInt(b) => write!(f, "{}", b),
+            Value::Decimal(d) => write!(f, "{}", d),
             Value::String(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Array(arr) => {
-                let elements: Vec<String> = arr.iter().map(|v| v.to_string()).collect();
-                write!(f, "[{}]", elements.join(", "))
+                write!(f, "[")?;
+                write_items(f, &arr.borrow())?;
+                write!(f, "]")
+            }
+            Value::Tuple(elements) => {
+                write!(f, "(")?;
+                write_items(f, elements)?;
+                if elements.len() == 1 {
+                    write!(f, ",)")
+                } else {
+                    write!(f, ")")
+                }
             }
+            Value::Range { start, end, inclusive } => {
+                if *inclusive {
+                    write!(f, "{}..={}", start, end)
+                } else {
+                    write!(f, "{}..{}", start, end)
+                }
+            }
+            Value::Iterator(_) => write!(f, "<iterator>"),
             Value::Map(map) => {
-                let pairs: Vec<String> = map.iter()
-                    .map(|(k, v)| format!("{}: {}", k, v))
-                    .collect();
-                write!(f, "{{{}}}", pairs.join(", "))
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().iter().enumerate() {
+                    write!(f, "{}{}: ", if i > 0 { ", " } else { "" }, key)?;
+                    value.write_within(f, enclosing)?;
+                }
+                write!(f, "}}")
             }
             Value::Struct { type_name, fields } => {
-                let field_strs: Vec<String> = fields.iter()
-                    .map(|(k, v)| format!("{}: {}", k, v))
-                    .collect();
-                write!(f, "{} {{ {} }}", type_name, field_strs.join(", "))
+                write!(f, "{} {{ ", type_name)?;
+                for (i, (name, value)) in fields.borrow().iter().enumerate() {
+                    write!(f, "{}{}: ", if i > 0 { ", " } else { "" }, name)?;
+                    value.write_within(f, enclosing)?;
+                }
+                write!(f, " }}")
             }
             Value::Result { is_ok, value } => {
-                if *is_ok {
-                    write!(f, "Ok({})", value)
-                } else {
-                    write!(f, "Err({})", value)
-                }
+                write!(f, "{}(", if *is_ok { "Ok" } else { "Err" })?;
+                value.write_within(f, enclosing)?;
+                write!(f, ")")
             }
             Value::Option { is_some, value } => {
                 if *is_some {
-                    write!(f, "Some({})", value)
+                    write!(f, "Some(")?;
+                    value.write_within(f, enclosing)?;
+                    write!(f, ")")
                 } else {
                     write!(f, "None")
                 }
             }
+            Value::Enum { type_name, variant, payload } => {
+                write!(f, "{}.{}", type_name, variant)?;
+                if payload.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                write_items(f, payload)?;
+                write!(f, ")")
+            }
+            Value::Function { name, .. } => {
+                if name.starts_with(LAMBDA_PREFIX) {
+                    write!(f, "<lambda>")
+                } else {
+                    write!(f, "<fn {}>", name)
+                }
+            }
             Value::Null => write!(f, "null"),
         }
     }
@@ -7,6 +7,7 @@
 
 pub mod execution;
 pub mod semantic;
+pub mod source_locator;
 pub mod types;
 pub mod type_checker;
 
@@ -2,7 +2,9 @@
 //! Professional semantic analyzer for the Razen language
 //! Performs type checking, scope analysis, and semantic validation
 
+use crate::backend::source_locator::SourceLocator;
 use crate::backend::type_checker::TypeChecker;
+use crate::backend::types::Type;
 use crate::frontend::diagnostics::{helpers, Diagnostic, DiagnosticKind, Diagnostics, Position, Span};
 use crate::frontend::parser::ast::*;
 use crate::frontend::module_system::{ModuleResolver, VisibilityChecker, ModuleError};
@@ -14,14 +16,19 @@ use std::path::PathBuf;
 pub struct SemanticAnalyzer {
     diagnostics: Diagnostics,
     symbol_table: SymbolTable,
-    _type_checker: TypeChecker, // Reserved for future type checking integration
+    type_checker: TypeChecker,
     current_function: Option<String>,
     in_loop: bool,
+    loop_labels: Vec<(Option<String>, bool)>, // label of each enclosing loop and whether it is a `loop` expression
+    in_defer: bool,
     source_lines: Vec<String>,
+    locator: SourceLocator, // finds declarations in `source_lines`
     module_resolver: Option<ModuleResolver>,
     visibility_checker: VisibilityChecker,
     current_file: Option<PathBuf>,
     type_aliases: HashMap<String, TypeAnnotation>, // type_name -> target_type
+    type_parameters: Vec<String>, // generic parameters of the enclosing declaration
+    hoisted_constants: Vec<String>, // top-level constants declared ahead of their definition
 }
 
 /// Symbol table for tracking variables and functions
@@ -30,7 +37,10 @@ struct SymbolTable {
     scopes: Vec<HashMap<String, Symbol>>,
     functions: HashMap<String, FunctionSymbol>,
     structs: HashMap<String, StructSymbol>,
+    enums: HashMap<String, EnumSymbol>,
     methods: HashMap<String, Vec<MethodSymbol>>, // type_name -> methods
+    traits: HashMap<String, TraitDeclaration>,
+    trait_impls: HashMap<String, Vec<String>>, // type_name -> implemented traits
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +48,7 @@ struct Symbol {
     _name: String,
     symbol_type: SymbolType,
     defined_at: Position,
+    span: Option<Span>, // where the name is declared, when it could be located
     used: bool,
     mutable: bool,
 }
@@ -45,30 +56,111 @@ struct Symbol {
 #[derive(Debug, Clone)]
 struct FunctionSymbol {
     _name: String,
-    parameters: Vec<String>,
-    return_type: Option<String>,
+    signature: CallSignature,
+    return_type: Type,
+    generics: Option<GenericSignature>,
     defined_at: Position,
 }
 
 #[derive(Debug, Clone)]
 struct StructSymbol {
     _name: String,
-    _fields: HashMap<String, String>, // field_name -> type_name
+    type_params: Vec<String>,
+    field_types: HashMap<String, Type>, // field_name -> declared type, in terms of type_params
     _defined_at: Position,
 }
 
+impl StructSymbol {
+    fn from_declaration(name: String, struct_decl: &StructDeclaration) -> Self {
+        let type_params: Vec<String> =
+            struct_decl.type_parameters.iter().map(|t| t.name.clone()).collect();
+        StructSymbol {
+            _name: name,
+            field_types: struct_decl.fields.iter().map(|f| {
+                (f.name.name.clone(), Type::from_annotation_with_params(&f.type_annotation, &type_params))
+            }).collect(),
+            type_params,
+            _defined_at: Position::new(1, 1, 0),
+        }
+    }
+}
+
+/// Parameter and return types of a generic function, in terms of its type parameters.
+/// `params` lines up with `CallSignature::params`.
+#[derive(Debug, Clone)]
+struct GenericSignature {
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+/// What a call has to supply: the parameters that can be passed by position or name
+/// (excluding `self` and a variadic rest parameter), and how many of them have no default
+#[derive(Debug, Clone)]
+struct CallSignature {
+    params: Vec<String>,
+    required: usize,
+    is_variadic: bool,
+}
+
+impl CallSignature {
+    fn from_parameters(parameters: &[Parameter]) -> Self {
+        let fixed: Vec<&Parameter> = parameters.iter()
+            .filter(|p| !p.is_variadic && p.name.name != "self")
+            .collect();
+        CallSignature {
+            params: fixed.iter().map(|p| p.name.name.clone()).collect(),
+            required: fixed.iter().filter(|p| p.default_value.is_none()).count(),
+            is_variadic: parameters.iter().any(|p| p.is_variadic),
+        }
+    }
+
+    fn positional(params: Vec<String>) -> Self {
+        CallSignature { required: params.len(), params, is_variadic: false }
+    }
+}
+
+impl GenericSignature {
+    /// `None` for functions without type parameters
+    fn from_declaration(func_decl: &FunctionDeclaration) -> Option<Self> {
+        if func_decl.type_parameters.is_empty() {
+            return None;
+        }
+        let type_params: Vec<String> =
+            func_decl.type_parameters.iter().map(|t| t.name.clone()).collect();
+        let annotation_type = |annotation: &Option<TypeAnnotation>| {
+            annotation.as_ref()
+                .map(|t| Type::from_annotation_with_params(t, &type_params))
+                .unwrap_or(Type::Any)
+        };
+        Some(GenericSignature {
+            params: func_decl.parameters.iter()
+                .filter(|p| !p.is_variadic && p.name.name != "self")
+                .map(|p| annotation_type(&p.type_annotation))
+                .collect(),
+            return_type: annotation_type(&func_decl.return_type),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EnumSymbol {
+    variants: Vec<(String, usize)>, // (variant_name, payload arity) in declaration order
+}
+
 #[derive(Debug, Clone)]
 struct MethodSymbol {
     _name: String,
-    parameters: Vec<String>,
-    return_type: Option<String>,
+    signature: CallSignature,
+    return_type: Type,
     is_static: bool,
     _defined_at: Position,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 enum SymbolType {
-    Variable(String), // type name
+    Variable(Type),
+    Module, // alias of an imported module
+    Type,   // name of a struct, enum or trait
     #[allow(dead_code)]
     Function,
     #[allow(dead_code)]
@@ -80,52 +172,23 @@ enum SymbolType {
 }
 
 impl SemanticAnalyzer {
-    fn get_type_name_from_annotation(annotation: &Option<TypeAnnotation>) -> String {
-        match annotation {
-            Some(TypeAnnotation::Int) => "int".to_string(),
-            Some(TypeAnnotation::Float) => "float".to_string(),
-            Some(TypeAnnotation::String) => "string".to_string(),
-            Some(TypeAnnotation::Bool) => "bool".to_string(),
-            Some(TypeAnnotation::Char) => "char".to_string(),
-            Some(TypeAnnotation::Array(inner)) => {
-                format!(
-                    "array<{}>",
-                    Self::get_type_name_from_annotation(&Some(*inner.clone()))
-                )
-            }
-            Some(TypeAnnotation::Map(key, value)) => {
-                format!(
-                    "map<{}, {}>",
-                    Self::get_type_name_from_annotation(&Some(*key.clone())),
-                    Self::get_type_name_from_annotation(&Some(*value.clone()))
-                )
-            }
-            Some(TypeAnnotation::Custom(ident)) => ident.name.clone(),
-            Some(TypeAnnotation::Any) => "any".to_string(),
-            None => "unknown".to_string(),
-        }
+    /// Declared return type of a function, unknown when it has no annotation or returns
+    /// one of its own type parameters
+    fn declared_return_type(annotation: &Option<TypeAnnotation>, type_parameters: &[Identifier]) -> Type {
+        let type_params: Vec<String> = type_parameters.iter().map(|t| t.name.clone()).collect();
+        annotation.as_ref()
+            .map(|t| Type::from_annotation_with_params(t, &type_params).substitute(&HashMap::new()))
+            .unwrap_or(Type::Unknown)
     }
 
-    fn get_type_name_from_type_annotation(annotation: &TypeAnnotation) -> String {
-        match annotation {
-            TypeAnnotation::Int => "int".to_string(),
-            TypeAnnotation::Float => "float".to_string(),
-            TypeAnnotation::String => "string".to_string(),
-            TypeAnnotation::Bool => "bool".to_string(),
-            TypeAnnotation::Char => "char".to_string(),
-            TypeAnnotation::Array(inner) => {
-                format!("array<{}>", Self::get_type_name_from_type_annotation(inner))
-            }
-            TypeAnnotation::Map(key, value) => {
-                format!(
-                    "map<{}, {}>",
-                    Self::get_type_name_from_type_annotation(key),
-                    Self::get_type_name_from_type_annotation(value)
-                )
-            }
-            TypeAnnotation::Custom(ident) => ident.name.clone(),
-            TypeAnnotation::Any => "any".to_string(),
-        }
+    /// Type of an annotation written inside the current declaration, with type aliases
+    /// expanded and the enclosing type parameters standing for any type
+    fn annotation_type(&self, annotation: &TypeAnnotation) -> Type {
+        let resolved = self.resolve_type_annotation(annotation);
+        let erased: HashMap<String, Type> = self.type_parameters.iter()
+            .map(|param| (param.clone(), Type::Any))
+            .collect();
+        Type::from_annotation_with_params(&resolved, &self.type_parameters).substitute(&erased)
     }
 
     /// Validate that a type annotation refers to valid types
@@ -134,7 +197,9 @@ impl SemanticAnalyzer {
             TypeAnnotation::Custom(ident) => {
                 // Check if this is a valid type alias or struct
                 let is_valid = self.type_aliases.contains_key(&ident.name) 
-                    || self.symbol_table.structs.contains_key(&ident.name);
+                    || self.symbol_table.structs.contains_key(&ident.name)
+                    || self.symbol_table.traits.contains_key(&ident.name)
+                    || self.type_parameters.contains(&ident.name);
                 
                 if !is_valid {
                     // Check for common case sensitivity mistakes first
@@ -172,6 +237,43 @@ impl SemanticAnalyzer {
                 self.validate_type_annotation(key);
                 self.validate_type_annotation(value);
             },
+            TypeAnnotation::Generic(ident, args) => {
+                let expected = self.symbol_table.structs.get(&ident.name).map(|s| s.type_params.len());
+                // Find this annotation's name, recognised by its written type arguments
+                let written = Type::from_annotation(annotation).to_string().replace(' ', "");
+                let type_arguments = &written[ident.name.len()..];
+                let annotation_span = |analyzer: &mut Self| analyzer.locator.find_next_where(&ident.name, |_, after| {
+                    after.replace(' ', "").starts_with(type_arguments)
+                });
+                match expected {
+                    Some(expected) if expected != args.len() => {
+                        let reason = format!(
+                            "`{}` takes {} type argument{} but {} {} supplied",
+                            ident.name, expected, if expected == 1 { "" } else { "s" },
+                            args.len(), if args.len() == 1 { "was" } else { "were" }
+                        );
+                        let diagnostic = helpers::invalid_type_annotation(
+                            Type::from_annotation(annotation).to_string(),
+                            reason,
+                            annotation_span(self),
+                        );
+                        self.diagnostics.add(diagnostic);
+                    }
+                    Some(_) => {}
+                    None => {
+                        let diagnostic = helpers::type_not_found(&ident.name, annotation_span(self));
+                        self.diagnostics.add(diagnostic);
+                    }
+                }
+                for arg in args {
+                    self.validate_type_annotation(arg);
+                }
+            },
+            TypeAnnotation::Tuple(elements) => {
+                for element in elements {
+                    self.validate_type_annotation(element);
+                }
+            },
             _ => {
                 // Primitive types are always valid
             }
@@ -202,6 +304,15 @@ impl SemanticAnalyzer {
                     Box::new(self.resolve_type_annotation(value))
                 )
             },
+            TypeAnnotation::Generic(ident, args) => {
+                TypeAnnotation::Generic(
+                    ident.clone(),
+                    args.iter().map(|arg| self.resolve_type_annotation(arg)).collect()
+                )
+            },
+            TypeAnnotation::Tuple(elements) => {
+                TypeAnnotation::Tuple(elements.iter().map(|element| self.resolve_type_annotation(element)).collect())
+            },
             _ => {
                 // Primitive types don't need resolution
                 annotation.clone()
@@ -213,14 +324,19 @@ impl SemanticAnalyzer {
         let mut analyzer = SemanticAnalyzer {
             diagnostics: Diagnostics::new(),
             symbol_table: SymbolTable::new(),
-            _type_checker: TypeChecker::new(), // Reserved for future integration
+            type_checker: TypeChecker::new(),
             current_function: None,
             in_loop: false,
+            loop_labels: Vec::new(),
+            in_defer: false,
             source_lines: Vec::new(),
+            locator: SourceLocator::default(),
             module_resolver: None,
             visibility_checker: VisibilityChecker::new(),
             current_file: None,
             type_aliases: HashMap::new(),
+            type_parameters: Vec::new(),
+            hoisted_constants: Vec::new(),
         };
 
         // Add built-in functions
@@ -238,26 +354,52 @@ impl SemanticAnalyzer {
 
     pub fn analyze(&mut self, program: &Program) -> Diagnostics {
         self.diagnostics = Diagnostics::new();
+        let source_id = self.current_file.as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "source".to_string());
+        self.locator = SourceLocator::new(&self.source_lines, source_id.clone());
 
         // Module resolution pass: process use statements and resolve modules
         if self.module_resolver.is_some() {
             self.resolve_modules(program);
         }
 
-        // First pass: collect all function declarations
+        // First pass: collect all function, enum, trait and constant declarations
         for stmt in &program.statements {
             if let Statement::FunctionDeclaration(func_decl) = stmt {
                 self.declare_function(func_decl);
+            } else if let Statement::ConstantDeclaration(const_decl) = stmt {
+                // Functions may refer to (and must not assign) constants defined below them;
+                // a literal value already tells them its type
+                let hoisted_type = match Type::from_literal(&const_decl.initializer) {
+                    Type::Unknown => Type::Any,
+                    literal_type => literal_type,
+                };
+                self.declare_variable(&const_decl.name.name, hoisted_type, Position::new(1, 1, 0), false);
+                self.hoisted_constants.push(const_decl.name.name.clone());
+            } else if let Statement::EnumDeclaration(enum_decl) = stmt {
+                self.declare_enum(enum_decl);
+            } else if let Statement::TraitDeclaration(trait_decl) = stmt {
+                self.declare_trait(trait_decl);
+            } else if let Statement::ImplBlock(impl_block) = stmt
+                && let Some(ref trait_name) = impl_block.trait_name
+            {
+                self.symbol_table.trait_impls
+                    .entry(impl_block.target_type.name.clone())
+                    .or_default()
+                    .push(trait_name.name.clone());
             }
         }
 
-        // Second pass: analyze function bodies and perform type checking
+        // Second pass: analyze scopes, names and function bodies
         for stmt in &program.statements {
             self.analyze_statement(stmt);
         }
 
-        // Note: Removed duplicate type checking pass to prevent duplicate errors
-        // Type checking is now integrated into analyze_statement and analyze_expression
+        // Third pass: check annotations, calls, returns, struct fields and operators
+        self.type_checker.set_source(&self.source_lines, source_id);
+        let type_diagnostics = self.type_checker.check_program(program);
+        self.diagnostics.extend(type_diagnostics);
 
         // Check for unused variables (only warnings)
         self.check_unused_variables();
@@ -324,8 +466,9 @@ impl SemanticAnalyzer {
         // First, register the module alias itself as a symbol so it's recognized in expressions
         let module_symbol = Symbol {
             _name: module_name.to_string(),
-            symbol_type: SymbolType::Variable("module".to_string()),
+            symbol_type: SymbolType::Module,
             defined_at: Position::new(1, 1, 0),
+            span: None,
             used: false,
             mutable: false,
         };
@@ -345,8 +488,9 @@ impl SemanticAnalyzer {
                         // Register the function in the symbol table
                         let func_symbol = FunctionSymbol {
                             _name: qualified_name.clone(),
-                            parameters: func_decl.parameters.iter().map(|p| p.name.name.clone()).collect(),
-                            return_type: func_decl.return_type.as_ref().map(|t| Self::get_type_name_from_type_annotation(t)),
+                            signature: CallSignature::from_parameters(&func_decl.parameters),
+                            return_type: Self::declared_return_type(&func_decl.return_type, &func_decl.type_parameters),
+                            generics: GenericSignature::from_declaration(func_decl),
                             defined_at: Position::new(1, 1, 0),
                         };
                         
@@ -360,8 +504,9 @@ impl SemanticAnalyzer {
                         // Register the constant as a variable in the symbol table
                         let symbol = Symbol {
                             _name: qualified_name.clone(),
-                            symbol_type: SymbolType::Variable("const".to_string()),
+                            symbol_type: SymbolType::Variable(Type::Any),
                             defined_at: Position::new(1, 1, 0),
+                            span: None,
                             used: false,
                             mutable: false,
                         };
@@ -379,8 +524,9 @@ impl SemanticAnalyzer {
                         // Register the variable in the symbol table
                         let symbol = Symbol {
                             _name: qualified_name.clone(),
-                            symbol_type: SymbolType::Variable("var".to_string()),
+                            symbol_type: SymbolType::Variable(Type::Any),
                             defined_at: Position::new(1, 1, 0),
+                            span: None,
                             used: false,
                             mutable: true,
                         };
@@ -396,11 +542,7 @@ impl SemanticAnalyzer {
                         let qualified_name = format!("{}.{}", module_name, struct_decl.name.name);
                         
                         // Register the struct type
-                        let struct_symbol = StructSymbol {
-                            _name: qualified_name.clone(),
-                            _fields: HashMap::new(), // TODO: populate with actual fields
-                            _defined_at: Position::new(1, 1, 0),
-                        };
+                        let struct_symbol = StructSymbol::from_declaration(qualified_name.clone(), struct_decl);
                         
                         self.symbol_table.structs.insert(qualified_name, struct_symbol);
                     }
@@ -459,7 +601,9 @@ impl SemanticAnalyzer {
             ("len", vec!["value"]),
             // Type conversion functions
             ("toint", vec!["value"]),
+            ("tobigint", vec!["value"]),
             ("tofloat", vec!["value"]),
+            ("decimal", vec!["value"]),
             ("tostr", vec!["value"]),
             ("tobool", vec!["value"]),
             ("typeof", vec!["value"]),
@@ -477,6 +621,12 @@ impl SemanticAnalyzer {
             ("None", vec![]),
             ("is_some", vec!["option"]),
             ("is_none", vec!["option"]),
+            // Iterator adapters
+            ("enumerate", vec!["iterable"]),
+            ("zip", vec!["left", "right"]),
+            ("map", vec!["iterable", "function"]),
+            ("filter", vec!["iterable", "function"]),
+            ("take", vec!["iterable", "count"]),
         ];
 
         for (name, params) in builtins {
@@ -484,8 +634,9 @@ impl SemanticAnalyzer {
                 name.to_string(),
                 FunctionSymbol {
                     _name: name.to_string(),
-                    parameters: params.into_iter().map(|s| s.to_string()).collect(),
-                    return_type: None,
+                    signature: CallSignature::positional(params.into_iter().map(|s| s.to_string()).collect()),
+                    return_type: Type::Unknown,
+                    generics: None,
                     defined_at: Position::new(0, 0, 0),
                 },
             );
@@ -495,9 +646,11 @@ impl SemanticAnalyzer {
     fn declare_function(&mut self, func_decl: &FunctionDeclaration) {
         let func_name = &func_decl.name.name;
 
-        // Check for duplicate function definitions
-        if self.symbol_table.functions.contains_key(func_name) {
-            let existing = &self.symbol_table.functions[func_name];
+        // Check for duplicate function definitions; builtins may be redefined, since the iterator
+        // adapters (map, filter, take, zip, ...) claim names user code commonly defines itself
+        if let Some(existing) = self.symbol_table.functions.get(func_name)
+            && existing.defined_at.line != 0
+        {
             let diagnostic = helpers::duplicate_definition(
                 func_name,
                 self.create_span_from_identifier(&func_decl.name),
@@ -507,21 +660,13 @@ impl SemanticAnalyzer {
             return;
         }
 
-        let params: Vec<String> = func_decl
-            .parameters
-            .iter()
-            .map(|p| p.name.name.clone())
-            .collect();
-
         self.symbol_table.functions.insert(
             func_name.clone(),
             FunctionSymbol {
                 _name: func_name.clone(),
-                parameters: params,
-                return_type: func_decl
-                    .return_type
-                    .as_ref()
-                    .map(|_| "unknown".to_string()),
+                signature: CallSignature::from_parameters(&func_decl.parameters),
+                return_type: Self::declared_return_type(&func_decl.return_type, &func_decl.type_parameters),
+                generics: GenericSignature::from_declaration(func_decl),
                 defined_at: Position::new(1, 1, 0), // TODO: get actual position
             },
         );
@@ -530,6 +675,12 @@ impl SemanticAnalyzer {
     fn analyze_statement(&mut self, stmt: &Statement) {
         match stmt {
             Statement::FunctionDeclaration(func_decl) => {
+                // Nested functions are not seen by the top-level declaration pass
+                if self.current_function.is_some()
+                    && !self.symbol_table.functions.contains_key(&func_decl.name.name)
+                {
+                    self.declare_function(func_decl);
+                }
                 self.analyze_function_declaration(func_decl);
             }
             Statement::VariableDeclaration(var_decl) => {
@@ -540,10 +691,10 @@ impl SemanticAnalyzer {
             }
             Statement::ReturnStatement(ret_stmt) => {
                 if self.current_function.is_none() {
-                    let diagnostic = helpers::syntax_error(
-                        "return statement outside function",
-                        Span::new(Position::new(1, 1, 0), Position::new(1, 6, 5)),
-                    );
+                    let diagnostic = helpers::syntax_error("return statement outside function", self.locator.find("return"));
+                    self.diagnostics.add(diagnostic);
+                } else if self.in_defer {
+                    let diagnostic = helpers::syntax_error("return statement inside defer block", self.locator.find("return"));
                     self.diagnostics.add(diagnostic);
                 }
 
@@ -559,34 +710,13 @@ impl SemanticAnalyzer {
                 self.symbol_table.pop_scope();
             }
             Statement::IfStatement(if_stmt) => {
-                // Validate condition is boolean
-                let condition_type = self.analyze_expression(&if_stmt.condition);
-                if let Some(cond_type) = condition_type {
-                    if cond_type != "bool" && cond_type != "any" {
-                        let span = self.create_span_for_pattern("if", "");
-                        let diagnostic = helpers::invalid_condition(
-                            &cond_type,
-                            span,
-                        );
-                        self.diagnostics.add(diagnostic);
-                    }
-                }
-                
+                self.check_condition(&if_stmt.condition, "if");
+
                 self.analyze_statement(&if_stmt.then_branch);
 
                 // Analyze elif branches
                 for elif_branch in &if_stmt.elif_branches {
-                    let elif_condition_type = self.analyze_expression(&elif_branch.condition);
-                    if let Some(cond_type) = elif_condition_type {
-                        if cond_type != "bool" && cond_type != "any" {
-                            let span = self.create_span_for_pattern("elif", "");
-                            let diagnostic = helpers::invalid_condition(
-                                &cond_type,
-                                span,
-                            );
-                            self.diagnostics.add(diagnostic);
-                        }
-                    }
+                    self.check_condition(&elif_branch.condition, "elif");
                     self.analyze_statement(&elif_branch.body);
                 }
 
@@ -596,38 +726,35 @@ impl SemanticAnalyzer {
                 }
             }
             Statement::WhileStatement(while_stmt) => {
-                // Validate condition is boolean
-                let condition_type = self.analyze_expression(&while_stmt.condition);
-                if let Some(cond_type) = condition_type {
-                    if cond_type != "bool" && cond_type != "any" {
-                        let span = self.create_span_for_pattern("while", "");
-                        let diagnostic = helpers::invalid_condition(
-                            &cond_type,
-                            span,
-                        );
-                        self.diagnostics.add(diagnostic);
-                    }
-                }
-                
+                self.check_condition(&while_stmt.condition, "while");
+
                 let was_in_loop = self.in_loop;
                 self.in_loop = true;
+                self.loop_labels.push((while_stmt.label.as_ref().map(|l| l.name.clone()), false));
                 self.analyze_statement(&while_stmt.body);
+                self.loop_labels.pop();
                 self.in_loop = was_in_loop;
             }
-            Statement::BreakStatement(_) => {
+            Statement::BreakStatement(break_stmt) => {
+                // Locate every break, so the next one is searched for after it
+                let span = self.locator.find_next_where("break", |_, _| true);
                 if !self.in_loop {
-                    let diagnostic = helpers::break_outside_loop(
-                        Span::new(Position::new(1, 1, 0), Position::new(1, 6, 5))
-                    );
-                    self.diagnostics.add(diagnostic);
+                    self.diagnostics.add(helpers::break_outside_loop(span));
+                } else if let Some(is_loop_expression) = self.loop_target(&break_stmt.label) {
+                    if break_stmt.value.is_some() && !is_loop_expression {
+                        self.diagnostics.add(helpers::break_value_outside_loop(span));
+                    }
+                }
+                if let Some(ref value) = break_stmt.value {
+                    self.analyze_expression(value);
                 }
             }
-            Statement::ContinueStatement(_) => {
+            Statement::ContinueStatement(continue_stmt) => {
+                let span = self.locator.find_next_where("continue", |_, _| true);
                 if !self.in_loop {
-                    let diagnostic = helpers::continue_outside_loop(
-                        Span::new(Position::new(1, 1, 0), Position::new(1, 9, 8))
-                    );
-                    self.diagnostics.add(diagnostic);
+                    self.diagnostics.add(helpers::continue_outside_loop(span));
+                } else {
+                    self.loop_target(&continue_stmt.label);
                 }
             }
             // Handle other statement types (not yet implemented)
@@ -641,7 +768,18 @@ impl SemanticAnalyzer {
                 // Similar to variable declaration but immutable
                 let const_name = &const_decl.name.name;
 
-                if let Some(existing) = self.symbol_table.lookup_in_current_scope(const_name) {
+                // The first pass already declared top-level constants
+                let hoisted_used = match self.hoisted_constants.iter().position(|name| name == const_name) {
+                    Some(index) if self.current_function.is_none() => {
+                        self.hoisted_constants.swap_remove(index);
+                        Some(self.symbol_table.lookup_in_current_scope(const_name).is_some_and(|s| s.used))
+                    }
+                    _ => None,
+                };
+
+                if hoisted_used.is_none()
+                    && let Some(existing) = self.symbol_table.lookup_in_current_scope(const_name)
+                {
                     let diagnostic = helpers::shadowed_variable(
                         const_name,
                         self.create_span_from_identifier(&const_decl.name),
@@ -651,29 +789,18 @@ impl SemanticAnalyzer {
                 }
 
                 // Analyze initializer and infer type
-                let inferred_type = self
-                    .analyze_expression(&const_decl.initializer)
-                    .unwrap_or_else(|| "any".to_string());
+                let inferred_type = self.analyze_expression(&const_decl.initializer);
 
-                // If there's a type annotation, validate it and check compatibility
+                // The type checker compares the initializer with the annotation
                 if let Some(ref type_ann) = const_decl.type_annotation {
                     self.validate_type_annotation(type_ann);
-                    let resolved_type_ann = self.resolve_type_annotation(type_ann);
-                    let declared_type = Self::get_type_name_from_type_annotation(&resolved_type_ann);
-                    
-                    // Check type compatibility
-                    if !self.types_compatible(&inferred_type, &declared_type) {
-                        let diagnostic = helpers::type_mismatch(
-                            &declared_type,
-                            &inferred_type,
-                            self.create_span_from_identifier(&const_decl.name),
-                        );
-                        self.diagnostics.add(diagnostic);
-                    }
                 }
 
                 // Declare the constant with the inferred type (immutable)
-                self.declare_variable(const_name, &inferred_type, Position::new(1, 1, 0), false);
+                self.declare_variable(const_name, inferred_type, Position::new(1, 1, 0), false);
+                if hoisted_used == Some(true) {
+                    self.symbol_table.mark_used(const_name);
+                }
             }
             Statement::TypeAliasDeclaration(type_alias) => {
                 // Register the type alias for resolution
@@ -688,6 +815,7 @@ impl SemanticAnalyzer {
                     _name: type_alias.name.name.clone(),
                     symbol_type: SymbolType::Builtin, // Mark as builtin so it's not flagged as unused
                     defined_at: Position::new(1, 1, 0),
+                    span: None,
                     used: true, // Mark as used by default
                     mutable: false,
                 };
@@ -698,78 +826,63 @@ impl SemanticAnalyzer {
             }
             Statement::StructDeclaration(struct_decl) => {
                 // Register struct type in symbol table
-                self.declare_variable(
-                    &struct_decl.name.name,
-                    "type",
-                    Position::new(1, 1, 0),
-                    false,
-                );
+                self.declare_type_name(&struct_decl.name.name);
                 
                 // Also register in structs map for method resolution
-                let struct_symbol = StructSymbol {
-                    _name: struct_decl.name.name.clone(),
-                    _fields: struct_decl.fields.iter().map(|f| {
-                        let type_name = Self::get_type_name_from_type_annotation(&f.type_annotation);
-                        (f.name.name.clone(), type_name)
-                    }).collect(),
-                    _defined_at: Position::new(1, 1, 0),
-                };
+                let struct_symbol = StructSymbol::from_declaration(struct_decl.name.name.clone(), struct_decl);
                 self.symbol_table.structs.insert(struct_decl.name.name.clone(), struct_symbol);
             }
             Statement::EnumDeclaration(enum_decl) => {
                 // Register enum type in symbol table
-                self.declare_variable(&enum_decl.name.name, "type", Position::new(1, 1, 0), false);
+                self.declare_type_name(&enum_decl.name.name);
+                self.declare_enum(enum_decl);
+            }
+            Statement::TraitDeclaration(trait_decl) => {
+                self.analyze_trait_declaration(trait_decl);
             }
             Statement::ImplBlock(impl_block) => {
                 self.analyze_impl_block(impl_block);
             }
             Statement::ForStatement(for_stmt) => {
                 // Analyze iterable
-                self.analyze_expression(&for_stmt.iterable);
+                let iterable_type = self.analyze_expression(&for_stmt.iterable);
 
                 // Create new scope for loop variable
                 self.symbol_table.push_scope();
 
                 // Determine loop variable type based on iterable
-                let loop_var_type = match &for_stmt.iterable {
-                    Expression::RangeExpression(_) => "int", // Range produces integers
-                    Expression::ArrayLiteral(array) => {
-                        // Infer type from first array element if possible
-                        if !array.elements.is_empty() {
-                            match &array.elements[0] {
-                                Expression::IntegerLiteral(_) => "int",
-                                Expression::FloatLiteral(_) => "float",
-                                Expression::StringLiteral(_) => "str",
-                                Expression::CharacterLiteral(_) => "char",
-                                Expression::BooleanLiteral(_) => "bool",
-                                _ => "var",
-                            }
-                        } else {
-                            "var"
-                        }
-                    }
-                    _ => "var", // Default to var for other types
+                let loop_var_type = match (&for_stmt.iterable, iterable_type) {
+                    (Expression::RangeExpression(_), _) => Type::Int, // Range produces integers
+                    (_, Type::Array(element)) => *element,
+                    _ => Type::Unknown,
                 };
 
-                self.declare_variable(
-                    &for_stmt.variable.name,
-                    loop_var_type,
-                    Position::new(1, 1, 0),
-                    true,
-                );
+                if for_stmt.destructure.is_empty() {
+                    self.declare_variable(
+                        &for_stmt.variable.name,
+                        loop_var_type,
+                        Position::new(1, 1, 0),
+                        true,
+                    );
+                } else {
+                    // Element types of destructured loop items are not tracked
+                    let spans = self.locate_destructured_names(&for_stmt.destructure);
+                    for (name, span) in for_stmt.destructure.iter().zip(spans) {
+                        self.declare_variable_at(&name.name, Type::Any, span, true);
+                    }
+                }
 
                 let was_in_loop = self.in_loop;
                 self.in_loop = true;
+                self.loop_labels.push((for_stmt.label.as_ref().map(|l| l.name.clone()), false));
                 self.analyze_statement(&for_stmt.body);
+                self.loop_labels.pop();
                 self.in_loop = was_in_loop;
 
                 self.symbol_table.pop_scope();
             }
             Statement::MatchStatement(match_stmt) => {
-                self.analyze_expression(&match_stmt.expression);
-                for arm in &match_stmt.arms {
-                    self.analyze_expression(&arm.body);
-                }
+                self.analyze_match(&match_stmt.expression, &match_stmt.arms);
             }
             Statement::TryStatement(try_stmt) => {
                 self.analyze_statement(&Statement::BlockStatement(try_stmt.body.clone()));
@@ -778,7 +891,7 @@ impl SemanticAnalyzer {
                     if let Some(ref param) = catch_clause.parameter {
                         self.declare_variable(
                             &param.name,
-                            "exception",
+                            Type::Any,
                             Position::new(1, 1, 0),
                             true,
                         );
@@ -790,40 +903,52 @@ impl SemanticAnalyzer {
             Statement::ThrowStatement(throw_stmt) => {
                 self.analyze_expression(&throw_stmt.value);
             }
+            Statement::DeferStatement(defer_stmt) => {
+                if self.current_function.is_none() {
+                    let diagnostic = helpers::syntax_error("defer statement outside function", self.locator.find("defer"));
+                    self.diagnostics.add(diagnostic);
+                }
+
+                // Deferred code runs after the function body, so it cannot leave it early
+                let was_in_loop = self.in_loop;
+                let was_in_defer = self.in_defer;
+                let old_loop_labels = std::mem::take(&mut self.loop_labels);
+                self.in_loop = false;
+                self.in_defer = true;
+                self.analyze_statement(&Statement::BlockStatement(defer_stmt.body.clone()));
+                self.in_loop = was_in_loop;
+                self.in_defer = was_in_defer;
+                self.loop_labels = old_loop_labels;
+            }
+        }
+    }
+
+    /// Analyze an `if`, `elif` or `while` condition, reporting one known not to be a bool
+    fn check_condition(&mut self, condition: &Expression, keyword: &str) {
+        let condition_type = self.analyze_expression(condition);
+        if !matches!(condition_type, Type::Bool | Type::Any | Type::Unknown) {
+            let span = self.create_span_for_pattern(keyword, "");
+            let diagnostic = helpers::invalid_condition(condition_type.to_string(), span);
+            self.diagnostics.add(diagnostic);
         }
     }
 
     fn analyze_function_declaration(&mut self, func_decl: &FunctionDeclaration) {
         let old_function = self.current_function.clone();
         self.current_function = Some(func_decl.name.name.clone());
+        let old_type_parameters = self.type_parameters.clone();
+        self.type_parameters.extend(func_decl.type_parameters.iter().map(|t| t.name.clone()));
 
         // Create new scope for function
         self.symbol_table.push_scope();
 
         // Add parameters to scope with their proper types
         for param in &func_decl.parameters {
-            let param_type_string;
-            let param_type = if let Some(ref type_ann) = param.type_annotation {
-                // Validate the type annotation
-                self.validate_type_annotation(type_ann);
-                
-                // Resolve type aliases
-                let resolved_type_ann = self.resolve_type_annotation(type_ann);
-                
-                param_type_string = match resolved_type_ann {
-                    TypeAnnotation::Int => "int".to_string(),
-                    TypeAnnotation::Float => "float".to_string(),
-                    TypeAnnotation::String => "str".to_string(),
-                    TypeAnnotation::Bool => "bool".to_string(),
-                    TypeAnnotation::Char => "char".to_string(),
-                    TypeAnnotation::Any => "any".to_string(),
-                    TypeAnnotation::Custom(id) => id.name.clone(),
-                    _ => "any".to_string(),
-                };
-                &param_type_string
-            } else {
-                "any" // Parameters without type annotations are flexible
-            };
+            let param_type = self.parameter_type(param);
+            // Defaults are evaluated in the function scope and may use earlier parameters
+            if let Some(ref default_value) = param.default_value {
+                self.analyze_expression(default_value);
+            }
             self.declare_variable(&param.name.name, param_type, Position::new(1, 1, 0), true);
         }
 
@@ -837,284 +962,611 @@ impl SemanticAnalyzer {
 
         self.symbol_table.pop_scope();
         self.current_function = old_function;
+        self.type_parameters = old_type_parameters;
     }
 
-    fn analyze_variable_declaration(&mut self, var_decl: &VariableDeclaration) {
-        let var_name = &var_decl.name.name;
-
-        // Check for variable shadowing
-        if let Some(existing) = self.symbol_table.lookup_in_current_scope(var_name) {
-            let diagnostic = helpers::shadowed_variable(
-                var_name,
-                self.create_span_from_identifier(&var_decl.name),
-                existing.defined_at.line,
-            );
-            self.diagnostics.add(diagnostic);
+    /// Type of a parameter inside its function: its validated annotation, any when it has
+    /// none, and an array of those for a variadic parameter
+    fn parameter_type(&mut self, param: &Parameter) -> Type {
+        let param_type = match param.type_annotation {
+            Some(ref type_ann) => {
+                self.validate_type_annotation(type_ann);
+                self.annotation_type(type_ann)
+            }
+            None => Type::Any, // Parameters without type annotations are flexible
+        };
+        // A variadic parameter holds the collected arguments; its annotation names their type
+        if param.is_variadic {
+            Type::Array(Box::new(param_type))
+        } else {
+            param_type
         }
+    }
 
-        // Analyze initializer and infer type if present
-        let inferred_type = if let Some(ref expr) = var_decl.initializer {
-            self.analyze_expression(expr)
-                .unwrap_or_else(|| "any".to_string())
-        } else {
-            "any".to_string()
-        };
+    fn analyze_lambda_expression(&mut self, lambda: &LambdaExpression) -> Type {
+        // Lambdas behave like functions: `return` is allowed, loop context is not inherited
+        let old_function = self.current_function.replace("<lambda>".to_string());
+        let old_in_loop = self.in_loop;
+        let old_in_defer = self.in_defer;
+        let old_loop_labels = std::mem::take(&mut self.loop_labels);
+        self.in_loop = false;
+        self.in_defer = false;
 
-        // Use explicit type annotation if provided, otherwise use inferred type
-        let var_type_string;
-        let var_type = if let Some(ref type_ann) = var_decl.type_annotation {
-            // First validate that Custom types exist
-            self.validate_type_annotation(type_ann);
-            
-            // Resolve type aliases
-            let resolved_type_ann = self.resolve_type_annotation(type_ann);
-            
-            var_type_string = match resolved_type_ann {
-                TypeAnnotation::Int => "int".to_string(),
-                TypeAnnotation::Float => "float".to_string(),
-                TypeAnnotation::String => "str".to_string(),
-                TypeAnnotation::Bool => "bool".to_string(),
-                TypeAnnotation::Char => "char".to_string(),
-                TypeAnnotation::Any => "any".to_string(),
-                TypeAnnotation::Custom(id) => {
-                    // Check if this is a valid type (not a case mismatch)
-                    let lowercase_name = id.name.to_lowercase();
-                    if matches!(lowercase_name.as_str(), "int" | "float" | "str" | "string" | "bool" | "char") {
-                        // This is a case mismatch, skip type checking (error already reported)
-                        "any".to_string()
-                    } else {
-                        id.name.clone()
-                    }
-                },
-                _ => "any".to_string(),
-            };
+        self.symbol_table.push_scope();
 
-            // Check type compatibility if both declared type and initializer exist
-            // Skip if the type annotation was invalid (already reported)
-            if let Some(ref _expr) = var_decl.initializer {
-                if var_type_string != "any" && !self.types_compatible(&inferred_type, &var_type_string) {
-                    let diagnostic = helpers::type_mismatch(
-                        &var_type_string,
-                        &inferred_type,
-                        self.create_span_from_identifier(&var_decl.name),
-                    );
-                    self.diagnostics.add(diagnostic);
+        let mut param_types = Vec::new();
+        for param in &lambda.parameters {
+            let param_type = self.parameter_type(param);
+            param_types.push(param_type.clone());
+            let span = self.locator.at(param.name.span.as_ref()).unwrap_or_else(|| {
+                self.locator.find_next_where(&param.name.name, |before, after| {
+                    let before = before.trim_end();
+                    (before.ends_with(['|', ',']) || before.ends_with("..."))
+                        && after.trim_start().starts_with([',', '|', ':'])
+                })
+            });
+            self.declare_variable_at(&param.name.name, param_type, span, true);
+        }
+
+        match &lambda.body {
+            LambdaBody::Expression(expr) => {
+                self.analyze_expression(expr);
+            }
+            LambdaBody::Block(block) => {
+                for stmt in &block.statements {
+                    self.analyze_statement(stmt);
                 }
             }
+        }
 
-            &var_type_string
-        } else {
-            &inferred_type
-        };
+        self.check_unused_variables_in_current_scope();
+        self.symbol_table.pop_scope();
 
-        // Declare the variable
-        self.declare_variable(
-            var_name,
-            var_type,
-            Position::new(1, 1, 0), // TODO: get actual position
-            true,
-        );
+        self.in_loop = old_in_loop;
+        self.in_defer = old_in_defer;
+        self.loop_labels = old_loop_labels;
+        self.current_function = old_function;
+
+        Type::Function(param_types, Box::new(Type::Unknown))
     }
 
-    fn analyze_expression(&mut self, expr: &Expression) -> Option<String> {
-        match expr {
-            Expression::Identifier(ident) => self.analyze_identifier(ident),
-            Expression::IntegerLiteral(_) => Some("int".to_string()),
-            Expression::StringLiteral(_) => Some("str".to_string()),
-            Expression::CharacterLiteral(_) => Some("char".to_string()),
-            Expression::BooleanLiteral(_) => Some("bool".to_string()),
-            Expression::BinaryExpression(bin_expr) => {
-                let left_type = self.analyze_expression(&bin_expr.left);
-                let right_type = self.analyze_expression(&bin_expr.right);
+    fn analyze_match(&mut self, subject: &Expression, arms: &[MatchArm]) -> Type {
+        self.analyze_expression(subject);
 
-                // Simple type checking for binary operations
-                match &bin_expr.operator {
-                    BinaryOperator::Add
-                    | BinaryOperator::Subtract
-                    | BinaryOperator::Multiply
-                    | BinaryOperator::Divide
-                    | BinaryOperator::Modulo
-                    | BinaryOperator::Power => {
-                        if let (Some(left), Some(right)) = (&left_type, &right_type) {
-                            // Allow "any" type to be compatible with anything
-                            if left == "any" || right == "any" {
-                                Some("any".to_string()) // Any type is flexible
-                            } else if left == "str" || right == "str" {
-                                Some("str".to_string()) // String concatenation
-                            } else if left == "int" && right == "int" {
-                                Some("int".to_string())
-                            } else if left == "float" || right == "float" {
-                                Some("float".to_string()) // Promote to float
-                            } else {
-                                let diagnostic = helpers::type_mismatch(
-                                    "compatible types",
-                                    &format!("{} and {}", left, right),
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    }
-                    BinaryOperator::Equal
-                    | BinaryOperator::NotEqual
-                    | BinaryOperator::Less
-                    | BinaryOperator::Greater
-                    | BinaryOperator::LessEqual
-                    | BinaryOperator::GreaterEqual => Some("bool".to_string()),
-                    BinaryOperator::And | BinaryOperator::Or => {
-                        // Check that both operands are boolean
-                        if let (Some(left), Some(right)) = (&left_type, &right_type) {
-                            if left != "bool" || right != "bool" {
-                                let diagnostic = helpers::type_mismatch(
-                                    "bool",
-                                    &format!("{} and {}", left, right),
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                        }
-                        Some("bool".to_string())
-                    }
-                    BinaryOperator::BitwiseAnd
-                    | BinaryOperator::BitwiseOr
-                    | BinaryOperator::BitwiseXor
-                    | BinaryOperator::LeftShift
-                    | BinaryOperator::RightShift => {
-                        // Check that both operands are integers
-                        if let (Some(left), Some(right)) = (&left_type, &right_type) {
-                            if left != "int" || right != "int" {
-                                let diagnostic = helpers::type_mismatch(
-                                    "int",
-                                    &format!("{} and {}", left, right),
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                        }
-                        Some("int".to_string())
-                    }
-                    _ => None,
-                }
+        let mut arm_types = Vec::new();
+        for arm in arms {
+            // Each arm gets its own scope for the variables its pattern binds
+            self.symbol_table.push_scope();
+            self.declare_pattern_bindings(&arm.pattern);
+
+            if let Some(ref guard) = arm.guard {
+                self.analyze_expression(guard);
             }
-            Expression::UnaryExpression(unary_expr) => {
-                let operand_type = self.analyze_expression(&unary_expr.operand);
-                match &unary_expr.operator {
-                    UnaryOperator::Not => {
-                        if let Some(ref op_type) = operand_type {
-                            if op_type != "bool" {
-                                let diagnostic = helpers::type_mismatch(
-                                    "bool",
-                                    &op_type,
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                        }
-                        Some("bool".to_string())
-                    }
-                    UnaryOperator::Minus | UnaryOperator::Plus => {
-                        if let Some(ref op_type) = operand_type {
-                            // Accept both int and float for unary +/-
-                            if op_type != "int" && op_type != "float" {
-                                let diagnostic = helpers::type_mismatch(
-                                    "int or float",
-                                    &op_type,
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                            // Return the same type as operand
-                            operand_type.clone()
-                        } else {
-                            None
-                        }
-                    }
-                    UnaryOperator::BitwiseNot => {
-                        if let Some(ref op_type) = operand_type {
-                            if op_type != "int" {
-                                let diagnostic = helpers::type_mismatch(
-                                    "int",
-                                    &op_type,
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                        }
-                        Some("int".to_string())
-                    }
-                    UnaryOperator::PreIncrement
-                    | UnaryOperator::PostIncrement
-                    | UnaryOperator::PreDecrement
-                    | UnaryOperator::PostDecrement => {
-                        if let Some(ref op_type) = operand_type {
-                            if op_type != "int" {
-                                let diagnostic = helpers::type_mismatch(
-                                    "int",
-                                    &op_type,
-                                    Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                        }
-                        Some("int".to_string())
+
+            match &arm.body {
+                MatchArmBody::Expression(expr) => {
+                    arm_types.push(self.analyze_expression(expr));
+                }
+                MatchArmBody::Block(block) => {
+                    for stmt in &block.statements {
+                        self.analyze_statement(stmt);
                     }
+                    arm_types.push(Type::Unknown);
                 }
             }
-            Expression::CallExpression(call_expr) => self.analyze_call_expression(call_expr),
-            Expression::AssignmentExpression(assign_expr) => {
-                // Check if the target is a valid lvalue
-                match assign_expr.left.as_ref() {
-                    Expression::Identifier(ident) => {
-                        let var_type = if let Some(symbol) = self.symbol_table.lookup(&ident.name) {
-                            if !symbol.mutable {
-                                let diagnostic = helpers::immutable_assignment(
-                                    &ident.name,
-                                    self.create_span_from_identifier(&ident),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                            
-                            // Get the variable's declared type for type checking
-                            if let SymbolType::Variable(type_name) = &symbol.symbol_type {
-                                Some(type_name.clone())
-                            } else {
-                                None
-                            }
-                        } else {
-                            let diagnostic = helpers::undefined_variable(
-                                &ident.name,
-                                self.create_span_from_identifier(&ident),
-                            );
-                            self.diagnostics.add(diagnostic);
-                            None
-                        };
 
-                        // Analyze the right side to get its type
-                        let right_type = self.analyze_expression(&assign_expr.right);
-                        
-                        // Type check the assignment if we have both types
-                        if let (Some(var_type_name), Some(right_type_name)) = (var_type, right_type.as_ref()) {
-                            // Skip type checking for 'any' type variables
-                            if var_type_name != "any" && !self.types_compatible(right_type_name, &var_type_name) {
-                                let diagnostic = helpers::type_mismatch(
-                                    &var_type_name,
-                                    right_type_name,
-                                    self.create_span_from_identifier(&ident),
-                                );
-                                self.diagnostics.add(diagnostic);
-                            }
-                        }
+            self.check_unused_variables_in_current_scope();
+            self.symbol_table.pop_scope();
+        }
 
-                        // Mark as used
-                        self.symbol_table.mark_used(&ident.name);
-                        
+        // A match has a known type only when every arm agrees
+        match arm_types.first() {
+            Some(first) if *first != Type::Unknown && arm_types.iter().all(|t| t == first) => first.clone(),
+            _ => Type::Any,
+        }
+    }
+
+    /// Declare the variables bound by a match pattern and analyze its literal parts
+    fn declare_pattern_bindings(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Wildcard => {}
+            Pattern::Identifier(ident) => {
+                self.declare_variable(&ident.name, Type::Any, Position::new(1, 1, 0), true);
+                // `Red` reads like a variant but would silently match everything
+                if ident.name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    let span = self.locator.at(ident.span.as_ref()).unwrap_or_else(|| {
+                        self.locator.find_next_where(&ident.name, |before, after| {
+                            !before.ends_with('.') && after.contains("=>")
+                        })
+                    });
+                    let variant = self.symbol_table.enums.iter()
+                        .find(|(_, enum_symbol)| enum_symbol.variants.iter().any(|(name, _)| *name == ident.name))
+                        .map(|(enum_name, _)| format!("{}.{}", enum_name, ident.name));
+                    self.diagnostics.add(helpers::capitalized_binding(&ident.name, span, variant));
+                    self.symbol_table.mark_used(&ident.name); // not also reported as unused
+                }
+            }
+            Pattern::Literal(expr) => {
+                self.analyze_expression(expr);
+            }
+            Pattern::Range { start, end, .. } => {
+                self.analyze_expression(start);
+                self.analyze_expression(end);
+            }
+            Pattern::Variant { enum_name, variant, payload } => {
+                if let Some(enum_name) = enum_name {
+                    self.symbol_table.mark_used(&enum_name.name);
+                    self.check_enum_variant(&enum_name.name, &variant.name, payload.len());
+                }
+                for sub_pattern in payload {
+                    self.declare_pattern_bindings(sub_pattern);
+                }
+            }
+            Pattern::Struct { fields, .. } => {
+                for field in fields {
+                    self.declare_pattern_bindings(&field.pattern);
+                }
+            }
+            Pattern::Array { elements, .. } | Pattern::Tuple(elements) => {
+                for element in elements {
+                    self.declare_pattern_bindings(element);
+                }
+            }
+            Pattern::Or(alternatives) => {
+                for alternative in alternatives {
+                    self.declare_pattern_bindings(alternative);
+                }
+            }
+        }
+    }
+
+    /// Register a trait and expose its methods under the trait name, so calls on
+    /// trait-typed values (and on `self` in default bodies) are checked
+    fn declare_trait(&mut self, trait_decl: &TraitDeclaration) {
+        let trait_name = &trait_decl.name.name;
+        if self.symbol_table.traits.contains_key(trait_name) {
+            let diagnostic = helpers::duplicate_definition(
+                trait_name,
+                self.create_span_from_identifier(&trait_decl.name),
+                None,
+            );
+            self.diagnostics.add(diagnostic);
+            return;
+        }
+
+        let methods = trait_decl.methods.iter().map(Self::trait_method_symbol).collect();
+        self.symbol_table.methods.insert(trait_name.clone(), methods);
+        self.symbol_table.traits.insert(trait_name.clone(), trait_decl.clone());
+    }
+
+    fn trait_method_symbol(method: &TraitMethod) -> MethodSymbol {
+        MethodSymbol {
+            _name: method.name.name.clone(),
+            signature: CallSignature::from_parameters(&method.parameters),
+            return_type: method.return_type.as_ref().map(Type::from_annotation).unwrap_or(Type::Unknown),
+            is_static: method.is_static,
+            _defined_at: Position::new(1, 1, 0),
+        }
+    }
+
+    /// Analyze the default method bodies of a trait, with `self` typed as the trait
+    fn analyze_trait_declaration(&mut self, trait_decl: &TraitDeclaration) {
+        // Traits declared inside a block are not seen by the top-level declaration pass
+        if !self.symbol_table.traits.contains_key(&trait_decl.name.name) {
+            self.declare_trait(trait_decl);
+        }
+        self.declare_type_name(&trait_decl.name.name);
+        self.symbol_table.mark_used(&trait_decl.name.name);
+
+        for method in &trait_decl.methods {
+            if let Some(ref body) = method.default_body {
+                let method_decl = MethodDeclaration::new(
+                    method.name.clone(),
+                    method.parameters.clone(),
+                    method.return_type.clone(),
+                    body.clone(),
+                    method.is_static,
+                );
+                self.analyze_method(&trait_decl.name.name, &method_decl);
+            }
+        }
+    }
+
+    /// Check an `impl Trait for Type` block against the trait: every method must be
+    /// declared by the trait with the same shape, and every method without a default
+    /// must be implemented. Inherited defaults become methods of the type.
+    fn check_trait_impl(&mut self, trait_ident: &Identifier, impl_block: &ImplBlock, methods: &mut Vec<MethodSymbol>) {
+        let trait_name = &trait_ident.name;
+        let type_name = &impl_block.target_type.name;
+        let Some(trait_decl) = self.symbol_table.traits.get(trait_name).cloned() else {
+            let diagnostic = helpers::undefined_trait(trait_name, self.create_span_from_identifier(trait_ident));
+            self.diagnostics.add(diagnostic);
+            return;
+        };
+
+        for method in &impl_block.methods {
+            let Some(required) = trait_decl.methods.iter().find(|m| m.name.name == method.name.name) else {
+                let diagnostic = helpers::method_not_in_trait(
+                    &method.name.name,
+                    trait_name,
+                    self.create_span_from_identifier(&method.name),
+                );
+                self.diagnostics.add(diagnostic);
+                continue;
+            };
+            if let Some(reason) = Self::trait_signature_mismatch(required, method) {
+                let diagnostic = helpers::trait_method_mismatch(
+                    trait_name.clone(),
+                    method.name.name.clone(),
+                    reason,
+                    self.create_span_from_identifier(&method.name),
+                    self.create_span_from_identifier(&required.name),
+                );
+                self.diagnostics.add(diagnostic);
+            }
+        }
+
+        for required in &trait_decl.methods {
+            if impl_block.methods.iter().any(|m| m.name.name == required.name.name) {
+                continue;
+            }
+            if required.default_body.is_some() {
+                methods.push(Self::trait_method_symbol(required));
+            } else {
+                let diagnostic = helpers::missing_trait_method(
+                    trait_name,
+                    &required.name.name,
+                    type_name,
+                    self.create_span_from_identifier(&impl_block.target_type),
+                );
+                self.diagnostics.add(diagnostic);
+            }
+        }
+    }
+
+    /// Describe how an implementation differs from the trait's declaration, if it does
+    fn trait_signature_mismatch(required: &TraitMethod, method: &MethodDeclaration) -> Option<String> {
+        if required.is_static != method.is_static {
+            return Some(if required.is_static {
+                "expected a static method without `self`".to_string()
+            } else {
+                "expected a method taking `self`".to_string()
+            });
+        }
+
+        let params = |parameters: &[Parameter]| -> Vec<Parameter> {
+            parameters.iter().filter(|p| p.name.name != "self").cloned().collect()
+        };
+        // Spelled like the type checker spells types; a missing annotation accepts anything
+        let type_name = |annotation: &Option<TypeAnnotation>| {
+            annotation.as_ref().map_or_else(|| "any".to_string(), |t| Type::from_annotation(t).to_string())
+        };
+        let (expected, found) = (params(&required.parameters), params(&method.parameters));
+        if expected.len() != found.len() {
+            return Some(format!(
+                "expected {} parameter{}, found {}",
+                expected.len(), if expected.len() == 1 { "" } else { "s" }, found.len()
+            ));
+        }
+        for (expected, found) in expected.iter().zip(&found) {
+            let expected_type = type_name(&expected.type_annotation);
+            let found_type = type_name(&found.type_annotation);
+            if expected_type != found_type || expected.is_variadic != found.is_variadic {
+                return Some(format!(
+                    "parameter `{}` expected type `{}`, found `{}`",
+                    found.name.name, expected_type, found_type
+                ));
+            }
+        }
+
+        let expected_return = type_name(&required.return_type);
+        let found_return = type_name(&method.return_type);
+        if expected_return != found_return {
+            return Some(format!("expected return type `{}`, found `{}`", expected_return, found_return));
+        }
+        None
+    }
+
+    fn declare_enum(&mut self, enum_decl: &EnumDeclaration) {
+        let variants = enum_decl.variants.iter()
+            .map(|v| (v.name.name.clone(), v.fields.as_ref().map_or(0, |fields| fields.len())))
+            .collect();
+        self.symbol_table.enums.insert(enum_decl.name.name.clone(), EnumSymbol { variants });
+    }
+
+    /// Check that `enum_name.variant` exists and is given the right number of payload values
+    fn check_enum_variant(&mut self, enum_name: &str, variant: &str, payload_len: usize) {
+        let Some(enum_symbol) = self.symbol_table.enums.get(enum_name) else {
+            return;
+        };
+
+        let qualified = format!("{}.{}", enum_name, variant);
+        match enum_symbol.variants.iter().find(|(name, _)| name == variant) {
+            Some((_, arity)) if *arity != payload_len => {
+                let diagnostic = helpers::variant_payload_mismatch(
+                    enum_name,
+                    variant,
+                    *arity,
+                    payload_len,
+                    self.create_span_for_pattern(&qualified, ""),
+                );
+                self.diagnostics.add(diagnostic);
+            }
+            Some(_) => {}
+            None => {
+                let variant_names: Vec<String> = enum_symbol.variants.iter().map(|(name, _)| name.clone()).collect();
+                let diagnostic = helpers::undefined_variant(
+                    variant,
+                    enum_name,
+                    self.create_span_for_pattern(&qualified, ""),
+                    &variant_names,
+                );
+                self.diagnostics.add(diagnostic);
+            }
+        }
+    }
+
+    fn analyze_variable_declaration(&mut self, var_decl: &VariableDeclaration) {
+        if !var_decl.destructure.is_empty() {
+            self.analyze_destructuring_declaration(var_decl);
+            return;
+        }
+        let var_name = &var_decl.name.name;
+
+        // Check for variable shadowing
+        if let Some(existing) = self.symbol_table.lookup_in_current_scope(var_name) {
+            let diagnostic = helpers::shadowed_variable(
+                var_name,
+                self.create_span_from_identifier(&var_decl.name),
+                existing.defined_at.line,
+            );
+            self.diagnostics.add(diagnostic);
+        }
+
+        // Analyze initializer and infer type if present
+        let inferred_type = match var_decl.initializer {
+            Some(ref expr) => self.analyze_expression(expr),
+            None => Type::Any,
+        };
+
+        // Use explicit type annotation if provided, otherwise use inferred type
+        let var_type = match var_decl.type_annotation {
+            Some(ref type_ann) => {
+                // Misspelled or unknown types are reported and leave the variable unchecked
+                let errors_before = self.diagnostics.error_count();
+                self.validate_type_annotation(type_ann);
+                if self.diagnostics.error_count() == errors_before {
+                    self.annotation_type(type_ann)
+                } else {
+                    Type::Any
+                }
+            }
+            None => inferred_type,
+        };
+
+        // Declare the variable
+        self.declare_variable(
+            var_name,
+            var_type,
+            Position::new(1, 1, 0), // TODO: get actual position
+            true,
+        );
+    }
+
+    /// Find the loop a break/continue targets, returning whether it is a `loop`
+    /// expression. Reports labels that name no enclosing loop of this function.
+    fn loop_target(&mut self, label: &Option<Identifier>) -> Option<bool> {
+        match label {
+            Some(label) => {
+                let target = self.loop_labels.iter().rev()
+                    .find(|(name, _)| name.as_deref() == Some(label.name.as_str()))
+                    .map(|(_, is_loop_expression)| *is_loop_expression);
+                if target.is_none() {
+                    let span = self.locator.find_next_where(&label.name, |before, _| {
+                        let before = before.trim_end();
+                        before.ends_with("break") || before.ends_with("continue")
+                    });
+                    let mut diagnostic = helpers::undeclared_label(&label.name, span);
+                    if self.symbol_table.lookup(&label.name).is_some() {
+                        self.symbol_table.mark_used(&label.name);
+                        diagnostic = diagnostic.with_note(format!(
+                            "to break with the value of `{}`, write `break ({})`", label.name, label.name
+                        ));
+                    }
+                    self.diagnostics.add(diagnostic);
+                }
+                target
+            }
+            None => self.loop_labels.last().map(|(_, is_loop_expression)| *is_loop_expression),
+        }
+    }
+
+    /// Report a write to a constant, either directly, through one of its elements
+    /// or fields (`LIMIT++`, `ITEMS[0] = 1`, `config.PORT = 80`) or by calling
+    /// `method` on it (`ITEMS.push(3)`)
+    fn check_mutable_target(&mut self, target: &Expression, method: Option<&str>) {
+        let mut root = target;
+        loop {
+            root = match root {
+                Expression::IndexExpression(index_expr) => &index_expr.object,
+                Expression::TupleIndexExpression(tuple_index) => &tuple_index.object,
+                Expression::GroupingExpression(group_expr) => &group_expr.expression,
+                Expression::MemberExpression(member_expr) => {
+                    // Members of an imported module are checked by their qualified name
+                    if let Expression::Identifier(module) = &*member_expr.object {
+                        let qualified_name = format!("{}.{}", module.name, member_expr.property.name);
+                        if let Some(symbol) = self.symbol_table.lookup(&qualified_name) {
+                            if !symbol.mutable {
+                                let diagnostic = helpers::immutable_assignment(
+                                    &member_expr.property.name,
+                                    self.create_span_from_identifier(&member_expr.property),
+                                )
+                                .with_note(format!("`{}` is a constant of module `{}`", member_expr.property.name, module.name));
+                                self.diagnostics.add(diagnostic);
+                            }
+                            return;
+                        }
+                    }
+                    &member_expr.object
+                }
+                _ => break,
+            };
+        }
+
+        if let Expression::Identifier(ident) = root
+            && let Some(symbol) = self.symbol_table.lookup(&ident.name)
+            && !symbol.mutable
+            && matches!(symbol.symbol_type, SymbolType::Variable(_))
+        {
+            let span = self.locate_write(ident);
+            let mut diagnostic = helpers::immutable_assignment(&ident.name, span);
+            if let Some(method) = method {
+                diagnostic = diagnostic.with_note(format!("`{}()` modifies constant `{}` in place", method, ident.name));
+            } else if !std::ptr::eq(root, target) {
+                diagnostic = diagnostic.with_note(format!("the elements and fields of constant `{}` cannot be modified either", ident.name));
+            }
+            self.diagnostics.add(diagnostic);
+        }
+    }
+
+    /// Span of a use of `name` that is not its declaration
+    fn locate_write(&mut self, name: &Identifier) -> Span {
+        self.locator.at(name.span.as_ref()).unwrap_or_else(|| {
+            self.locator.find_next_where(&name.name, |before, _| {
+                let before = before.trim_end();
+                !before.ends_with("const") && !before.ends_with("var")
+            })
+        })
+    }
+
+    /// Analyze `var (a, b) = expr`, giving each name its tuple element type when known
+    fn analyze_destructuring_declaration(&mut self, var_decl: &VariableDeclaration) {
+        let spans = self.locate_destructured_names(&var_decl.destructure);
+        let initializer_type = match var_decl.initializer {
+            Some(ref expr) => self.analyze_expression(expr),
+            None => Type::Unknown,
+        };
+
+        let element_types = match initializer_type {
+            Type::Tuple(elements) if elements.len() == var_decl.destructure.len() => elements,
+            Type::Tuple(elements) => {
+                let diagnostic = helpers::type_mismatch(
+                    format!("{}-element tuple", var_decl.destructure.len()),
+                    Type::Tuple(elements).to_string(),
+                    spans[0].clone(),
+                );
+                self.diagnostics.add(diagnostic);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+
+        for (i, (name, span)) in var_decl.destructure.iter().zip(spans).enumerate() {
+            if let Some(existing) = self.symbol_table.lookup_in_current_scope(&name.name) {
+                let diagnostic = helpers::shadowed_variable(&name.name, span.clone(), existing.defined_at.line);
+                self.diagnostics.add(diagnostic);
+            }
+            let var_type = match element_types.get(i) {
+                Some(Type::Unknown) | None => Type::Any,
+                Some(ty) => ty.clone(),
+            };
+            self.declare_variable_at(&name.name, var_type, span, true);
+        }
+    }
+
+    /// Span of `.N` in a tuple index, skipping float literals such as `1.5`
+    fn locate_tuple_index(&mut self, index: usize) -> Span {
+        self.locator.find_where(&format!(".{}", index), |before, _| {
+            let operand = before.rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).next().unwrap_or("");
+            !operand.starts_with(|c: char| c.is_ascii_digit())
+        })
+    }
+
+    /// Spans of the names in a `(a, b)` destructuring, in order
+    fn locate_destructured_names(&mut self, names: &[Identifier]) -> Vec<Span> {
+        let accept = |before: &str, after: &str| {
+            before.trim_end().ends_with(['(', ',']) && after.trim_start().starts_with([',', ')'])
+        };
+        names.iter().enumerate().map(|(i, name)| match (self.locator.at(name.span.as_ref()), i) {
+            (Some(span), _) => span,
+            (None, 0) => self.locator.find_where(&name.name, accept),
+            (None, _) => self.locator.find_next_where(&name.name, accept),
+        }).collect()
+    }
+
+    fn analyze_expression(&mut self, expr: &Expression) -> Type {
+        match expr {
+            Expression::Identifier(ident) => self.analyze_identifier(ident),
+            Expression::IntegerLiteral(_)
+            | Expression::FloatLiteral(_)
+            | Expression::DecimalLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::CharacterLiteral(_)
+            | Expression::BooleanLiteral(_)
+            | Expression::NullLiteral(_) => Type::from_literal(expr),
+            Expression::BinaryExpression(bin_expr) => {
+                let left_type = self.analyze_expression(&bin_expr.left);
+                let right_type = self.analyze_expression(&bin_expr.right);
+
+                // Result types only; the type checker reports operands that do not fit
+                let overloadable = matches!(
+                    bin_expr.operator,
+                    BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply |
+                    BinaryOperator::Divide | BinaryOperator::Modulo | BinaryOperator::Power
+                );
+                match &left_type {
+                    // Structs overload operators with add/sub/mul methods
+                    Type::Custom(name) | Type::Generic(name, _)
+                        if overloadable
+                            && !matches!(right_type, Type::Any | Type::Unknown)
+                            && self.symbol_table.structs.contains_key(name) =>
+                    {
+                        self.operator_result_type(name, &bin_expr.operator)
+                    }
+                    _ => Type::binary_result(&bin_expr.operator, &left_type, &right_type).unwrap_or(Type::Unknown),
+                }
+            }
+            Expression::UnaryExpression(unary_expr) => {
+                if matches!(
+                    unary_expr.operator,
+                    UnaryOperator::PreIncrement | UnaryOperator::PostIncrement |
+                    UnaryOperator::PreDecrement | UnaryOperator::PostDecrement
+                ) {
+                    self.check_mutable_target(&unary_expr.operand, None);
+                }
+                // The type checker reports operands the operator does not apply to
+                let operand_type = self.analyze_expression(&unary_expr.operand);
+                Type::unary_result(&unary_expr.operator, &operand_type).unwrap_or(Type::Unknown)
+            }
+            Expression::CallExpression(call_expr) => self.analyze_call_expression(call_expr),
+            Expression::AssignmentExpression(assign_expr) => {
+                // Check if the target is a valid lvalue
+                match assign_expr.left.as_ref() {
+                    Expression::Identifier(ident) => {
+                        if let Some(symbol) = self.symbol_table.lookup(&ident.name) {
+                            if !symbol.mutable {
+                                let diagnostic = helpers::immutable_assignment(&ident.name, self.locate_write(ident));
+                                self.diagnostics.add(diagnostic);
+                            }
+                        } else {
+                            let diagnostic = helpers::undefined_variable(
+                                &ident.name,
+                                self.create_span_from_identifier(&ident),
+                            );
+                            self.diagnostics.add(diagnostic);
+                        }
+
+                        // The type checker compares the value with the variable's type
+                        let right_type = self.analyze_expression(&assign_expr.right);
+
+                        // Mark as used
+                        self.symbol_table.mark_used(&ident.name);
+                        
                         right_type
                     }
                     Expression::MemberExpression(_) | Expression::IndexExpression(_) => {
                         // These are valid lvalues, analyze them
+                        self.check_mutable_target(&assign_expr.left, None);
                         self.analyze_expression(&assign_expr.left);
                         self.analyze_expression(&assign_expr.right)
                     }
@@ -1132,45 +1584,177 @@ impl SemanticAnalyzer {
                 }
             }
             // Handle other expression types
-            Expression::FloatLiteral(_) => Some("float".to_string()),
-            Expression::NullLiteral(_) => Some("null".to_string()),
+            Expression::LambdaExpression(lambda) => self.analyze_lambda_expression(lambda),
+            Expression::MatchExpression(match_expr) => self.analyze_match(&match_expr.expression, &match_expr.arms),
+            Expression::LoopExpression(loop_expr) => {
+                let was_in_loop = self.in_loop;
+                self.in_loop = true;
+                self.loop_labels.push((loop_expr.label.as_ref().map(|l| l.name.clone()), true));
+                self.analyze_statement(&Statement::BlockStatement(loop_expr.body.clone()));
+                self.loop_labels.pop();
+                self.in_loop = was_in_loop;
+                // Break values are not tracked, so the result may be anything
+                Type::Any
+            }
+            Expression::OptionalChainExpression(chain) => {
+                self.analyze_expression(&chain.object);
+                if let Some(ref arguments) = chain.arguments {
+                    for arg in arguments {
+                        self.analyze_argument(arg);
+                    }
+                }
+                // The result may always be null
+                Type::Unknown
+            }
+            Expression::SpreadExpression(spread) => {
+                let diagnostic = helpers::syntax_error(
+                    "spread `...` can only be used in call arguments or array literals",
+                    self.create_span_for_pattern("...", ""),
+                );
+                self.diagnostics.add(diagnostic);
+                self.analyze_expression(&spread.expression);
+                Type::Unknown
+            }
+            Expression::NamedArgument(named) => {
+                let diagnostic = helpers::syntax_error(
+                    "named arguments can only be used in function calls",
+                    self.create_span_from_identifier(&named.name),
+                );
+                self.diagnostics.add(diagnostic);
+                self.analyze_expression(&named.value);
+                Type::Unknown
+            }
+            Expression::PropagateExpression(propagate) => {
+                if self.current_function.is_none() {
+                    let diagnostic = helpers::syntax_error(
+                        "the `?` operator can only be used inside a function",
+                        self.create_span_for_pattern("?", ""),
+                    )
+                    .with_help("`?` returns early from the enclosing function on `Err` or `None`");
+                    self.diagnostics.add(diagnostic);
+                }
+
+                self.analyze_expression(&propagate.expression);
+                // The unwrapped payload type is not tracked
+                Type::Unknown
+            }
             Expression::MemberExpression(member_expr) => {
-                self.analyze_expression(&member_expr.object);
-                // For now, assume member access returns the same type
-                None
+                // Unit enum variant: Color.Red
+                if let Expression::Identifier(enum_ident) = &*member_expr.object
+                    && self.symbol_table.enums.contains_key(&enum_ident.name)
+                {
+                    self.symbol_table.mark_used(&enum_ident.name);
+                    self.check_enum_variant(&enum_ident.name, &member_expr.property.name, 0);
+                    return Type::Custom(enum_ident.name.clone());
+                }
+                let object_type = self.analyze_expression(&member_expr.object);
+                self.struct_field_type(&object_type, &member_expr.property)
             }
             Expression::MethodCallExpression(method_call) => self.analyze_method_call(method_call),
             Expression::SelfExpression(_) => {
-                // Return the type of the current context (if we're in a method)
-                // For now, we'll return None and let the type checker handle it
-                None
+                // Inside a method, self has the type the impl block is for
+                match self.symbol_table.lookup("self").map(|symbol| &symbol.symbol_type) {
+                    Some(SymbolType::Variable(self_type)) => self_type.clone(),
+                    _ => Type::Unknown,
+                }
             }
             Expression::IndexExpression(index_expr) => {
                 self.analyze_expression(&index_expr.object);
                 self.analyze_expression(&index_expr.index);
-                // For now, assume index access returns the element type
-                None
+                // Elements may change type after creation, so indexing is not tracked
+                Type::Unknown
+            }
+            Expression::SliceExpression(slice_expr) => {
+                let object_type = self.analyze_expression(&slice_expr.object);
+                for bound in [&slice_expr.start, &slice_expr.end].into_iter().flatten() {
+                    self.analyze_expression(bound);
+                }
+                // A slice has the type of what it was taken from
+                match object_type {
+                    Type::Array(_) | Type::String => object_type,
+                    _ => Type::Unknown,
+                }
             }
             Expression::ArrayLiteral(array_lit) => {
+                // Elements of one type give an array of that type
+                let mut element_type: Option<Type> = None;
                 for element in &array_lit.elements {
-                    self.analyze_expression(element);
+                    let current = match element {
+                        Expression::SpreadExpression(_) => {
+                            self.analyze_argument(element);
+                            Type::Unknown
+                        }
+                        _ => self.analyze_expression(element),
+                    };
+                    element_type = match element_type {
+                        None => Some(current),
+                        Some(previous) if previous == current => Some(previous),
+                        Some(_) => Some(Type::Unknown),
+                    };
                 }
-                Some("array".to_string())
+                Type::Array(Box::new(element_type.unwrap_or(Type::Unknown)))
             }
             Expression::MapLiteral(map_lit) => {
                 for pair in &map_lit.pairs {
                     self.analyze_expression(&pair.key);
                     self.analyze_expression(&pair.value);
                 }
-                Some("map".to_string())
+                Type::Map(Box::new(Type::Unknown), Box::new(Type::Unknown))
+            }
+            Expression::TupleLiteral(tuple_lit) => {
+                let element_types = tuple_lit.elements.iter()
+                    .map(|element| self.analyze_expression(element))
+                    .collect();
+                Type::Tuple(element_types)
+            }
+            Expression::TupleIndexExpression(tuple_index) => {
+                let object_type = self.analyze_expression(&tuple_index.object);
+                match object_type {
+                    Type::Tuple(mut elements) if tuple_index.index < elements.len() => {
+                        elements.swap_remove(tuple_index.index)
+                    }
+                    Type::Tuple(elements) => {
+                        let diagnostic = helpers::index_out_of_bounds(
+                            tuple_index.index as i64,
+                            elements.len(),
+                            "tuple",
+                            self.locate_tuple_index(tuple_index.index),
+                        )
+                        .with_note("Tuple fields are accessed by position: `.0`, `.1`, ...");
+                        self.diagnostics.add(diagnostic);
+                        Type::Unknown
+                    }
+                    _ => Type::Unknown,
+                }
             }
             Expression::StructInstantiation(struct_inst) => {
-                // Analyze all field values
+                let generic_fields = self.symbol_table.structs.get(&struct_inst.name.name)
+                    .filter(|s| !s.type_params.is_empty())
+                    .map(|s| (s.type_params.clone(), s.field_types.clone()));
+
+                let Some((type_params, field_types)) = generic_fields else {
+                    // Analyze all field values
+                    for field in &struct_inst.fields {
+                        self.analyze_expression(&field.value);
+                    }
+                    // Return the struct type
+                    return Type::Custom(struct_inst.name.name.clone());
+                };
+
+                // Generic struct: infer the type arguments from the field values;
+                // the type checker reports values that do not fit
+                let mut bindings = HashMap::new();
                 for field in &struct_inst.fields {
-                    self.analyze_expression(&field.value);
+                    let value_type = self.analyze_expression(&field.value);
+                    if let Some(field_type) = field_types.get(&field.name.name) {
+                        // A field that does not fit is reported by `TypeChecker::check_struct_instantiation`
+                        let _ = field_type.unify(&value_type, &mut bindings);
+                    }
                 }
-                // Return the struct type name
-                Some(struct_inst.name.name.clone())
+                let type_args = type_params.iter()
+                    .map(|param| bindings.get(param).cloned().unwrap_or(Type::Unknown))
+                    .collect();
+                Type::Generic(struct_inst.name.name.clone(), type_args)
             }
             Expression::QualifiedStructInstantiation(qualified_struct_inst) => {
                 // Analyze all field values
@@ -1179,8 +1763,8 @@ impl SemanticAnalyzer {
                 }
                 // Analyze the qualified name expression
                 self.analyze_expression(&qualified_struct_inst.qualified_name);
-                // Return a generic struct type for now
-                Some("struct".to_string())
+                // The struct is resolved in its module, so its type is not known here
+                Type::Unknown
             }
             Expression::InterpolatedString(interp_str) => {
                 for part in &interp_str.parts {
@@ -1188,94 +1772,101 @@ impl SemanticAnalyzer {
                         self.analyze_expression(expr);
                     }
                 }
-                Some("str".to_string())
+                Type::String
             }
             Expression::RangeExpression(range_expr) => {
                 self.analyze_expression(&range_expr.start);
                 self.analyze_expression(&range_expr.end);
-                Some("range".to_string())
+                Type::Unknown
             }
             Expression::GroupingExpression(group_expr) => {
                 self.analyze_expression(&group_expr.expression)
             }
             Expression::ModuleCallExpression(module_call) => {
                 let module_name = module_call.module.name.clone();
+
+                // Enum variant constructor: Shape.Circle(2.0)
+                if self.symbol_table.enums.contains_key(&module_name) {
+                    self.symbol_table.mark_used(&module_name);
+                    self.check_enum_variant(&module_name, &module_call.function.name, module_call.arguments.len());
+                    for arg in &module_call.arguments {
+                        self.analyze_argument(arg);
+                    }
+                    return Type::Custom(module_name);
+                }
                 
                 // Check if this is actually a variable (instance method call like person.greet())
-                let var_type_name = if let Some(symbol) = self.symbol_table.lookup(&module_name) {
-                    if let SymbolType::Variable(type_name) = &symbol.symbol_type {
-                        Some(type_name.clone())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
+                let var_type = match self.symbol_table.lookup(&module_name).map(|symbol| &symbol.symbol_type) {
+                    Some(SymbolType::Variable(var_type)) => Some(var_type.clone()),
+                    _ => None,
                 };
                 
-                if let Some(type_name) = var_type_name {
+                if let Some(var_type) = var_type {
                     // Mark the variable as used (it's the object for the method call)
                     self.symbol_table.mark_used(&module_name);
+                    let receiver = Expression::Identifier(module_call.module.clone());
+                    self.check_mutating_method(&receiver, &var_type, &module_call.function.name);
                     
                     // This is an instance method call on a variable
                     // Clone the method info we need before analyzing arguments (to avoid borrow issues)
-                    let method_info = self.symbol_table.methods.get(&type_name)
+                    // Methods of a generic struct are registered under its bare name
+                    let base_type_name = struct_type_name(&var_type).unwrap_or_default();
+                    let method_info = self.symbol_table.methods.get(base_type_name)
                             .and_then(|methods| {
                                 let method_name = &module_call.function.name;
                                 methods.iter()
                                     .find(|m| m._name == *method_name && !m.is_static)
-                                    .map(|m| (m.parameters.len(), m.return_type.clone()))
+                                    .map(|m| (m.signature.clone(), m.return_type.clone()))
                             });
                         
-                        if let Some((param_count, return_type)) = method_info {
-                            // Validate argument count (excluding self parameter)
-                            let expected_args = param_count.saturating_sub(1);
-                            if module_call.arguments.len() != expected_args {
-                                let diagnostic = Diagnostic::new(
-                                    crate::frontend::diagnostics::DiagnosticKind::ArgumentCountMismatch {
-                                        expected: expected_args,
-                                        found: module_call.arguments.len(),
-                                    },
-                                )
-                                .with_code("E0012");
-                                self.diagnostics.add(diagnostic);
-                            }
+                        if let Some((signature, return_type)) = method_info {
+                            // Validate arguments (excluding self parameter)
+                            self.check_method_arguments(&module_call.function, &signature, &module_call.arguments);
                             
                             // Analyze arguments
                             for arg in &module_call.arguments {
-                                self.analyze_expression(arg);
+                                self.analyze_argument(arg);
                             }
                             
                             return return_type;
                         }
+
+                    // Any value can be copied with clone() unless its type defines its own
+                    if module_call.function.name == "clone" && module_call.arguments.is_empty() {
+                        return var_type;
+                    }
+
+                    self.check_builtin_method(&var_type, &module_call.function);
+
+                    // Values whose type is only known at runtime (loop variables, untyped
+                    // parameters) are dispatched on their runtime type
+                    if !self.symbol_table.methods.contains_key(base_type_name) {
+                        for arg in &module_call.arguments {
+                            self.analyze_argument(arg);
+                        }
+                        return Type::Unknown;
+                    }
                 }
                 
                 // Check if this is a static method call on a struct type (like Person.new())
                 if self.symbol_table.structs.contains_key(&module_name) {
+                    self.symbol_table.mark_used(&module_name);
                     // This is a static method call
                     let method_info = self.symbol_table.methods.get(&module_name)
                         .and_then(|methods| {
                             let method_name = &module_call.function.name;
                             methods.iter()
                                 .find(|m| m._name == *method_name && m.is_static)
-                                .map(|m| (m.parameters.len(), m.return_type.clone()))
+                                .map(|m| (m.signature.clone(), m.return_type.clone()))
                         });
                     
-                    if let Some((expected_params, return_type)) = method_info {
-                        // Validate argument count
-                        if module_call.arguments.len() != expected_params {
-                            let diagnostic = Diagnostic::new(
-                                crate::frontend::diagnostics::DiagnosticKind::ArgumentCountMismatch {
-                                    expected: expected_params,
-                                    found: module_call.arguments.len(),
-                                },
-                            )
-                            .with_code("E0012");
-                            self.diagnostics.add(diagnostic);
-                        }
+                    if let Some((signature, return_type)) = method_info {
+                        // Validate arguments
+                        self.check_method_arguments(&module_call.function, &signature, &module_call.arguments);
                         
                         // Analyze arguments
                         for arg in &module_call.arguments {
-                            self.analyze_expression(arg);
+                            self.analyze_argument(arg);
                         }
                         
                         return return_type;
@@ -1288,24 +1879,29 @@ impl SemanticAnalyzer {
                         )
                         .with_code("E0013");
                         self.diagnostics.add(diagnostic);
-                        return None;
+                        return Type::Unknown;
                     }
                 }
                 
+                // `arr.push(ITEMS, 5)` changes ITEMS just like `ITEMS.push(5)`
+                if crate::stdlib::mutates_first_argument(&module_name, &module_call.function.name)
+                    && let Some(target) = module_call.arguments.first()
+                {
+                    let function = format!("{}.{}", module_name, module_call.function.name);
+                    self.check_mutable_target(target, Some(&function));
+                }
+
                 // Otherwise, treat it as a regular module call
                 self.analyze_module_call(module_call)
             }
         }
     }
 
-    fn analyze_identifier(&mut self, ident: &Identifier) -> Option<String> {
+    fn analyze_identifier(&mut self, ident: &Identifier) -> Type {
         let result = if let Some(symbol) = self.symbol_table.lookup(&ident.name) {
             match &symbol.symbol_type {
-                SymbolType::Variable(type_name) => Some(type_name.clone()),
-                SymbolType::Function => Some("function".to_string()),
-                SymbolType::Builtin => Some("builtin".to_string()),
-                SymbolType::Struct => Some("struct".to_string()),
-                SymbolType::Method => Some("method".to_string()),
+                SymbolType::Variable(var_type) => var_type.clone(),
+                _ => Type::Unknown,
             }
         } else {
             // Collect similar variable names for suggestions
@@ -1316,7 +1912,7 @@ impl SemanticAnalyzer {
                 &similar_names
             );
             self.diagnostics.add(diagnostic);
-            None
+            Type::Unknown
         };
 
         // Mark as used after borrowing is done
@@ -1324,35 +1920,63 @@ impl SemanticAnalyzer {
         result
     }
 
-    fn analyze_call_expression(&mut self, call_expr: &CallExpression) -> Option<String> {
+    /// Analyze a call argument or array element, where a `...` spread is allowed
+    fn analyze_argument(&mut self, arg: &Expression) -> Type {
+        match arg {
+            Expression::SpreadExpression(spread) => {
+                self.analyze_expression(&spread.expression);
+                Type::Unknown
+            }
+            Expression::NamedArgument(named) => self.analyze_argument(&named.value),
+            _ => self.analyze_expression(arg),
+        }
+    }
+
+    fn analyze_call_expression(&mut self, call_expr: &CallExpression) -> Type {
         if let Expression::Identifier(func_name) = call_expr.callee.as_ref() {
             // Check if function exists and get info
             let func_info =
                 if let Some(func_symbol) = self.symbol_table.functions.get(&func_name.name) {
                     Some((
-                        func_symbol.parameters.len(),
+                        func_symbol.signature.clone(),
                         func_symbol.return_type.clone(),
+                        func_symbol.generics.clone(),
                     ))
                 } else {
                     None
                 };
 
-            if let Some((expected_args, return_type)) = func_info {
-                // Check argument count
-                let provided_args = call_expr.arguments.len();
+            // Variables holding function values (lambdas, callbacks) are callable too
+            let is_function_variable = func_info.is_none()
+                && matches!(
+                    self.symbol_table.lookup(&func_name.name).map(|s| &s.symbol_type),
+                    Some(SymbolType::Variable(_))
+                );
+            if is_function_variable {
+                self.symbol_table.mark_used(&func_name.name);
+                for arg in &call_expr.arguments {
+                    self.analyze_argument(arg);
+                }
+                return Type::Unknown;
+            }
 
-                if expected_args != provided_args {
-                    let diagnostic = helpers::wrong_argument_count(
-                        expected_args,
-                        provided_args,
-                        self.create_span_from_identifier(&func_name),
-                    );
+            if let Some((signature, return_type, generics)) = func_info {
+                // Check arguments against the function's parameters
+                let span = self.create_span_from_identifier(func_name);
+                if let Some((expected, found)) =
+                    self.check_call_arguments(&func_name.name, &signature, &call_expr.arguments, span.clone())
+                {
+                    let diagnostic = helpers::wrong_argument_count(expected, found, span.clone());
                     self.diagnostics.add(diagnostic);
                 }
 
+                if let Some(generics) = generics {
+                    return self.instantiate_generic_call(&signature, &generics, &call_expr.arguments);
+                }
+
                 // Analyze arguments
                 for arg in &call_expr.arguments {
-                    self.analyze_expression(arg);
+                    self.analyze_argument(arg);
                 }
 
                 return_type
@@ -1365,25 +1989,90 @@ impl SemanticAnalyzer {
                     &similar_names
                 );
                 self.diagnostics.add(diagnostic);
-                None
+                Type::Unknown
             }
         } else {
             // Complex callee expression
             self.analyze_expression(&call_expr.callee);
             for arg in &call_expr.arguments {
-                self.analyze_expression(arg);
+                self.analyze_argument(arg);
+            }
+            Type::Unknown
+        }
+    }
+
+    /// Bind a generic function's type parameters from the argument types and return the
+    /// instantiated return type. Conflicting arguments are left to the type checker.
+    fn instantiate_generic_call(
+        &mut self,
+        signature: &CallSignature,
+        generics: &GenericSignature,
+        arguments: &[Expression],
+    ) -> Type {
+        let mut bindings = HashMap::new();
+        let mut position = 0;
+        let mut spread_seen = false;
+
+        for arg in arguments {
+            let param_index = match arg {
+                Expression::NamedArgument(named) => {
+                    signature.params.iter().position(|p| *p == named.name.name)
+                }
+                Expression::SpreadExpression(_) => {
+                    spread_seen = true;
+                    None
+                }
+                _ if spread_seen => None,
+                _ => {
+                    position += 1;
+                    Some(position - 1)
+                }
+            };
+
+            let arg_type = self.analyze_argument(arg);
+            if let Some(param_type) = param_index.and_then(|i| generics.params.get(i)) {
+                // An argument that does not fit is reported by `TypeChecker::check_arguments`
+                let _ = param_type.unify(&arg_type, &mut bindings);
             }
-            None
         }
+
+        generics.return_type.substitute(&bindings)
+    }
+
+    fn declare_variable(&mut self, name: &str, var_type: Type, pos: Position, mutable: bool) {
+        let symbol = Symbol {
+            _name: name.to_string(),
+            symbol_type: SymbolType::Variable(var_type),
+            defined_at: pos,
+            span: None,
+            used: false,
+            mutable,
+        };
+        self.symbol_table.declare(name.to_string(), symbol);
+    }
+
+    /// Declare a variable whose declaration was located in the source
+    fn declare_variable_at(&mut self, name: &str, var_type: Type, span: Span, mutable: bool) {
+        let symbol = Symbol {
+            _name: name.to_string(),
+            symbol_type: SymbolType::Variable(var_type),
+            defined_at: span.start,
+            span: Some(span),
+            used: false,
+            mutable,
+        };
+        self.symbol_table.declare(name.to_string(), symbol);
     }
 
-    fn declare_variable(&mut self, name: &str, var_type: &str, pos: Position, mutable: bool) {
+    /// Declare a struct, enum or trait name so that it resolves like a value
+    fn declare_type_name(&mut self, name: &str) {
         let symbol = Symbol {
             _name: name.to_string(),
-            symbol_type: SymbolType::Variable(var_type.to_string()),
-            defined_at: pos,
+            symbol_type: SymbolType::Type,
+            defined_at: Position::new(1, 1, 0),
+            span: None,
             used: false,
-            mutable,
+            mutable: false,
         };
         self.symbol_table.declare(name.to_string(), symbol);
     }
@@ -1391,7 +2080,7 @@ impl SemanticAnalyzer {
     fn check_unused_variables(&mut self) {
         for scope in &self.symbol_table.scopes {
             for (name, symbol) in scope {
-                if !symbol.used && matches!(symbol.symbol_type, SymbolType::Variable(_)) {
+                if !symbol.used && matches!(symbol.symbol_type, SymbolType::Variable(_) | SymbolType::Type) {
                     // Skip unused variable warnings for names starting with underscore
                     if name.starts_with('_') {
                         continue;
@@ -1402,19 +2091,14 @@ impl SemanticAnalyzer {
                         continue;
                     }
                     
-                    // Skip module type symbols
-                    if let SymbolType::Variable(type_name) = &symbol.symbol_type {
-                        if type_name == "module" {
-                            continue;
-                        }
-                    }
-                    
                     // Create span with proper source file information
-                    let (line, column) = self.find_identifier_position(name);
-                    let start_pos = Position::new(line, column, 0);
-                    let end_pos = Position::new(line, column + name.len(), name.len());
-                    let span = Span::new(start_pos, end_pos)
-                        .with_source(self.current_file.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|| "source".to_string()));
+                    let span = symbol.span.clone().unwrap_or_else(|| {
+                        let (line, column) = self.find_identifier_position(name);
+                        let start_pos = Position::new(line, column, 0);
+                        let end_pos = Position::new(line, column + name.len(), name.len());
+                        Span::new(start_pos, end_pos)
+                            .with_source(self.current_file.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|| "source".to_string()))
+                    });
                     
                     let diagnostic = helpers::unused_variable(name, span);
                     self.diagnostics.add(diagnostic);
@@ -1426,7 +2110,7 @@ impl SemanticAnalyzer {
     fn check_unused_variables_in_current_scope(&mut self) {
         if let Some(current_scope) = self.symbol_table.scopes.last() {
             for (name, symbol) in current_scope {
-                if !symbol.used && matches!(symbol.symbol_type, SymbolType::Variable(_)) {
+                if !symbol.used && matches!(symbol.symbol_type, SymbolType::Variable(_) | SymbolType::Type) {
                     // Skip unused variable warnings for names starting with underscore
                     if name.starts_with('_') {
                         continue;
@@ -1437,19 +2121,14 @@ impl SemanticAnalyzer {
                         continue;
                     }
                     
-                    // Skip module type symbols
-                    if let SymbolType::Variable(type_name) = &symbol.symbol_type {
-                        if type_name == "module" {
-                            continue;
-                        }
-                    }
-                    
                     // Create span with proper source file information
-                    let (line, column) = self.find_identifier_position(name);
-                    let start_pos = Position::new(line, column, 0);
-                    let end_pos = Position::new(line, column + name.len(), name.len());
-                    let span = Span::new(start_pos, end_pos)
-                        .with_source(self.current_file.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|| "source".to_string()));
+                    let span = symbol.span.clone().unwrap_or_else(|| {
+                        let (line, column) = self.find_identifier_position(name);
+                        let start_pos = Position::new(line, column, 0);
+                        let end_pos = Position::new(line, column + name.len(), name.len());
+                        Span::new(start_pos, end_pos)
+                            .with_source(self.current_file.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|| "source".to_string()))
+                    });
                     
                     let diagnostic = helpers::unused_variable(name, span);
                     self.diagnostics.add(diagnostic);
@@ -1462,6 +2141,7 @@ impl SemanticAnalyzer {
         // Use actual span information from the identifier if available
         if let Some(ref span) = ident.span {
             span.clone()
+                .with_source(self.current_file.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|| "source".to_string()))
         } else {
             // Find the actual position of the identifier in source
             let (line, column) = self.find_identifier_position(&ident.name);
@@ -1553,24 +2233,6 @@ impl SemanticAnalyzer {
         (1, 1) // Final fallback
     }
 
-    /// Check if two types are compatible for assignment
-    fn types_compatible(&self, from_type: &str, to_type: &str) -> bool {
-        match (from_type, to_type) {
-            // Exact matches
-            (a, b) if a == b => true,
-
-            // Any type is flexible
-            ("any", _) | (_, "any") => true,
-
-            // Numeric coercions
-            ("int", "float") | ("float", "int") => true,
-
-            // String concatenation flexibility (but not for explicit type declarations)
-            // For explicit declarations, we want strict typing
-            _ => false,
-        }
-    }
-
     /// Analyze impl block and register methods
     fn analyze_impl_block(&mut self, impl_block: &ImplBlock) {
         let type_name = &impl_block.target_type.name;
@@ -1581,122 +2243,306 @@ impl SemanticAnalyzer {
             // In a more complete implementation, we'd validate the type exists
         }
 
+        // Methods of a generic struct can name its type parameters
+        let old_type_parameters = self.type_parameters.clone();
+        if let Some(struct_symbol) = self.symbol_table.structs.get(type_name) {
+            self.type_parameters.extend(struct_symbol.type_params.iter().cloned());
+        }
+
         let mut methods = Vec::new();
 
         for method in &impl_block.methods {
-            // Set current_function context so return statements are valid (using dot notation)
-            let old_function = self.current_function.clone();
-            self.current_function = Some(format!("{}.{}", type_name, method.name.name));
-            
-            // Analyze method parameters and body
-            self.symbol_table.push_scope();
+            let method_symbol = self.analyze_method(type_name, method);
+            methods.push(method_symbol);
+        }
+
+        if let Some(ref trait_name) = impl_block.trait_name {
+            self.check_trait_impl(trait_name, impl_block, &mut methods);
+        }
+
+        self.type_parameters = old_type_parameters;
+
+        // Register all methods for this type (a type may have several impl blocks)
+        self.symbol_table.methods.entry(type_name.clone()).or_default().extend(methods);
+    }
+
+    /// Result type of an arithmetic operator on a struct, taken from the method that
+    /// overloads it when that method is already known
+    fn operator_result_type(&self, type_name: &str, operator: &BinaryOperator) -> Type {
+        let method_name = match operator {
+            BinaryOperator::Add => "add",
+            BinaryOperator::Subtract => "sub",
+            BinaryOperator::Multiply => "mul",
+            _ => return Type::Any,
+        };
+        self.symbol_table.methods.get(type_name)
+            .and_then(|methods| methods.iter().find(|m| m._name == method_name))
+            .map(|method| method.return_type.clone())
+            .filter(|return_type| *return_type != Type::Unknown)
+            .unwrap_or(Type::Any)
+    }
 
-            // If not static, add 'self' parameter to scope
-            if !method.is_static {
-                self.declare_variable("self", type_name, Position::new(1, 1, 0), false);
+    /// Analyze a method's parameters and body with `self` typed as `type_name`
+    fn analyze_method(&mut self, type_name: &str, method: &MethodDeclaration) -> MethodSymbol {
+        // Set current_function context so return statements are valid (using dot notation)
+        let old_function = self.current_function.clone();
+        self.current_function = Some(format!("{}.{}", type_name, method.name.name));
+        
+        // Analyze method parameters and body
+        self.symbol_table.push_scope();
+
+        // If not static, add 'self' parameter to scope
+        if !method.is_static {
+            self.declare_variable("self", Type::Custom(type_name.to_string()), Position::new(1, 1, 0), false);
+        }
+
+        // Add method parameters to scope
+        for param in &method.parameters {
+            if param.name.name != "self" {
+                // The annotation on a variadic parameter names its element type
+                let element_type = param.type_annotation.as_ref()
+                    .map_or(Type::Any, |annotation| self.annotation_type(annotation));
+                let param_type = if param.is_variadic {
+                    Type::Array(Box::new(element_type))
+                } else {
+                    element_type
+                };
+                if let Some(ref default_value) = param.default_value {
+                    self.analyze_expression(default_value);
+                }
+                self.declare_variable(
+                    &param.name.name,
+                    param_type,
+                    Position::new(1, 1, 0),
+                    true,
+                );
             }
+        }
 
-            // Add method parameters to scope
-            for param in &method.parameters {
-                if param.name.name != "self" {
-                    let type_name = Self::get_type_name_from_annotation(&param.type_annotation);
-                    self.declare_variable(
-                        &param.name.name,
-                        &type_name,
-                        Position::new(1, 1, 0),
-                        true,
+        // Analyze method body
+        self.analyze_statement(&Statement::BlockStatement(method.body.clone()));
+
+        self.symbol_table.pop_scope();
+        
+        // Restore previous function context
+        self.current_function = old_function;
+
+        MethodSymbol {
+            _name: method.name.name.clone(),
+            signature: CallSignature::from_parameters(&method.parameters),
+            return_type: method
+                .return_type
+                .as_ref()
+                .map_or(Type::Unknown, |annotation| self.annotation_type(annotation)),
+            is_static: method.is_static,
+            _defined_at: Position::new(1, 1, 0),
+        }
+    }
+
+    /// Check call arguments against a signature. Unknown, duplicate and missing named
+    /// arguments are reported here; a wrong positional count is returned as
+    /// `(expected, found)` for the caller to report. Spread arguments are only counted
+    /// at runtime.
+    fn check_call_arguments(
+        &mut self,
+        callee: &str,
+        signature: &CallSignature,
+        arguments: &[Expression],
+        span: Span,
+    ) -> Option<(usize, usize)> {
+        let mut positional = 0;
+        let mut named: Vec<&str> = Vec::new();
+
+        for arg in arguments {
+            match arg {
+                Expression::NamedArgument(named_arg) => {
+                    let name = named_arg.name.name.as_str();
+                    match signature.params.iter().position(|p| p == name) {
+                        None => {
+                            let diagnostic = helpers::unknown_named_argument(
+                                name,
+                                callee,
+                                span.clone(),
+                                &signature.params,
+                            );
+                            self.diagnostics.add(diagnostic);
+                        }
+                        Some(index) if index < positional || named.contains(&name) => {
+                            let diagnostic = helpers::duplicate_named_argument(name, span.clone());
+                            self.diagnostics.add(diagnostic);
+                        }
+                        Some(_) => {}
+                    }
+                    named.push(name);
+                }
+                _ if !named.is_empty() => {
+                    let diagnostic = helpers::syntax_error(
+                        "positional arguments must come before named arguments",
+                        span.clone(),
                     );
+                    self.diagnostics.add(diagnostic);
                 }
+                _ => positional += 1,
             }
+        }
 
-            // Analyze method body
-            self.analyze_statement(&Statement::BlockStatement(method.body.clone()));
-
-            // Register method
-            let method_symbol = MethodSymbol {
-                _name: method.name.name.clone(),
-                parameters: method
-                    .parameters
-                    .iter()
-                    .map(|p| Self::get_type_name_from_annotation(&p.type_annotation))
-                    .collect(),
-                return_type: method
-                    .return_type
-                    .as_ref()
-                    .map(|t| Self::get_type_name_from_type_annotation(t)),
-                is_static: method.is_static,
-                _defined_at: Position::new(1, 1, 0),
-            };
+        if arguments.iter().any(|arg| matches!(arg, Expression::SpreadExpression(_))) {
+            return None;
+        }
 
-            methods.push(method_symbol);
-            self.symbol_table.pop_scope();
-            
-            // Restore previous function context
-            self.current_function = old_function;
+        let param_count = signature.params.len();
+        if !signature.is_variadic && positional > param_count {
+            return Some((param_count, positional));
         }
+        // Without defaults or names, keep the plain argument count error
+        if named.is_empty() && signature.required == param_count && positional < param_count {
+            return Some((param_count, positional));
+        }
+
+        for param in signature.params.iter().take(signature.required).skip(positional) {
+            if !named.contains(&param.as_str()) {
+                let diagnostic = helpers::missing_argument(param.as_str(), callee, span.clone());
+                self.diagnostics.add(diagnostic);
+            }
+        }
+        None
+    }
 
-        // Register all methods for this type
-        self.symbol_table.methods.insert(type_name.clone(), methods);
+    /// Check method call arguments, reporting a wrong count as a method arity error
+    fn check_method_arguments(&mut self, method: &Identifier, signature: &CallSignature, arguments: &[Expression]) {
+        let span = self.create_span_from_identifier(method);
+        if let Some((expected, found)) = self.check_call_arguments(&method.name, signature, arguments, span) {
+            let diagnostic = Diagnostic::new(
+                crate::frontend::diagnostics::DiagnosticKind::ArgumentCountMismatch {
+                    expected,
+                    found,
+                },
+            )
+            .with_code("E0012");
+            self.diagnostics.add(diagnostic);
+        }
     }
 
     /// Analyze method call expression
-    fn analyze_method_call(&mut self, method_call: &MethodCallExpression) -> Option<String> {
+    fn analyze_method_call(&mut self, method_call: &MethodCallExpression) -> Type {
         // Analyze the object being called on
         let object_type = self.analyze_expression(&method_call.object);
 
         // Analyze method arguments
         for arg in &method_call.arguments {
-            self.analyze_expression(arg);
+            self.analyze_argument(arg);
         }
 
+        self.check_mutating_method(&method_call.object, &object_type, &method_call.method.name);
+
         // Check if the method exists for this type
-        if let Some(object_type_name) = &object_type {
-            if let Some(methods) = self.symbol_table.methods.get(object_type_name) {
-                let method_name = &method_call.method.name;
-
-                // Find the method
-                if let Some(method) = methods.iter().find(|m| m._name == *method_name) {
-                    // Validate argument count (excluding self parameter for non-static methods)
-                    let expected_args = if method.is_static {
-                        method.parameters.len()
-                    } else {
-                        method.parameters.len().saturating_sub(1) // Subtract 1 for self
-                    };
+        // Methods of a generic struct are registered under its bare name
+        let methods = struct_type_name(&object_type)
+            .and_then(|base_type_name| self.symbol_table.methods.get(base_type_name).map(|m| (base_type_name, m)));
+        if let Some((base_type_name, methods)) = methods {
+            let method_name = &method_call.method.name;
+
+            // Find the method
+            if let Some(method) = methods.iter().find(|m| m._name == *method_name) {
+                // Validate arguments (the signature excludes self for non-static methods)
+                let (signature, return_type) = (method.signature.clone(), method.return_type.clone());
+
+                self.check_method_arguments(&method_call.method, &signature, &method_call.arguments);
+
+                return return_type;
+            } else if method_name == "clone" && method_call.arguments.is_empty() {
+                return object_type;
+            } else {
+                // Method not found
+                let diagnostic = Diagnostic::new(
+                    crate::frontend::diagnostics::DiagnosticKind::UndefinedMethod {
+                        method: method_name.clone(),
+                        type_name: base_type_name.to_string(),
+                    },
+                )
+                .with_code("E0013");
+                self.diagnostics.add(diagnostic);
+            }
+        } else {
+            self.check_builtin_method(&object_type, &method_call.method);
+        }
 
-                    if method_call.arguments.len() != expected_args {
-                        let diagnostic = Diagnostic::new(
-                            crate::frontend::diagnostics::DiagnosticKind::ArgumentCountMismatch {
-                                expected: expected_args,
-                                found: method_call.arguments.len(),
-                            },
-                        )
-                        .with_code("E0012");
-                        self.diagnostics.add(diagnostic);
-                    }
+        Type::Unknown
+    }
 
-                    return method.return_type.clone();
-                } else {
-                    // Method not found
-                    let diagnostic = Diagnostic::new(
-                        crate::frontend::diagnostics::DiagnosticKind::UndefinedMethod {
-                            method: method_name.clone(),
-                            type_name: object_type_name.clone(),
-                        },
-                    )
-                    .with_code("E0013");
-                    self.diagnostics.add(diagnostic);
-                }
+    /// Report built-in methods such as `push` that would change the contents of a constant
+    fn check_mutating_method(&mut self, receiver: &Expression, receiver_type: &Type, method: &str) {
+        let is_mutating = match (receiver_type, builtin_type_kind(receiver_type)) {
+            (_, Some(kind)) => crate::stdlib::is_mutating_method(kind, method),
+            (Type::Any | Type::Unknown, None) => {
+                ["array", "map"].iter().any(|kind| crate::stdlib::is_mutating_method(kind, method))
             }
+            _ => false,
+        };
+        if is_mutating {
+            self.check_mutable_target(receiver, Some(method));
         }
+    }
 
-        None
+    /// Declared type of a struct field, reporting fields the struct does not have.
+    /// Fields of generic structs are typed with the value's type arguments.
+    fn struct_field_type(&mut self, object_type: &Type, field: &Identifier) -> Type {
+        let Some(base_type_name) = struct_type_name(object_type) else {
+            return Type::Unknown;
+        };
+        let Some(struct_symbol) = self.symbol_table.structs.get(base_type_name) else {
+            return Type::Unknown;
+        };
+        match struct_symbol.field_types.get(&field.name) {
+            Some(field_type) => {
+                let type_args = match object_type {
+                    Type::Generic(_, args) => args.as_slice(),
+                    _ => &[],
+                };
+                let bindings: HashMap<String, Type> = struct_symbol.type_params.iter().cloned()
+                    .zip(type_args.iter().cloned())
+                    .collect();
+                field_type.substitute(&bindings)
+            }
+            None => {
+                let diagnostic = helpers::missing_field(
+                    field.name.as_str(),
+                    base_type_name,
+                    self.create_span_for_pattern(&format!(".{}", field.name), ""),
+                );
+                self.diagnostics.add(diagnostic);
+                Type::Unknown
+            }
+        }
+    }
+
+    /// Report a method that values of a built-in type (str, array, map, Result, Option) lack
+    fn check_builtin_method(&mut self, receiver_type: &Type, method: &Identifier) {
+        let Some(kind) = builtin_type_kind(receiver_type) else {
+            return;
+        };
+        let Some(methods) = crate::stdlib::method_names(kind) else {
+            return;
+        };
+        if methods.contains(&method.name.as_str()) {
+            return;
+        }
+
+        let candidates: Vec<String> = methods.iter().map(|name| name.to_string()).collect();
+        let diagnostic = helpers::undefined_method_with_suggestions(
+            method.name.as_str(),
+            kind,
+            self.create_span_from_identifier(method),
+            &candidates,
+        );
+        self.diagnostics.add(diagnostic);
     }
 
     /// Analyze module call expression (e.g., utils.Function())
-    fn analyze_module_call(&mut self, module_call: &ModuleCallExpression) -> Option<String> {
+    fn analyze_module_call(&mut self, module_call: &ModuleCallExpression) -> Type {
         // Analyze arguments
         for arg in &module_call.arguments {
-            self.analyze_expression(arg);
+            self.analyze_argument(arg);
         }
 
         // Check if the module call is valid using visibility checker
@@ -1707,13 +2553,13 @@ impl SemanticAnalyzer {
             Ok(_symbol_info) => {
                 // Module call is valid, return unknown type for now
                 // TODO: Get actual return type from symbol info
-                Some("unknown".to_string())
+                Type::Unknown
             }
             Err(module_error) => {
                 // Module call is invalid, report error
                 let diagnostic = self.module_error_to_diagnostic(module_error);
                 self.diagnostics.add(diagnostic);
-                None
+                Type::Unknown
             }
         }
     }
@@ -1725,7 +2571,10 @@ impl SymbolTable {
             scopes: vec![HashMap::new()], // Global scope
             functions: HashMap::new(),
             structs: HashMap::new(),
+            enums: HashMap::new(),
             methods: HashMap::new(),
+            traits: HashMap::new(),
+            trait_impls: HashMap::new(),
         }
     }
 
@@ -1772,7 +2621,7 @@ impl SymbolTable {
         // Collect from all scopes
         for scope in &self.scopes {
             for (name, symbol) in scope {
-                if matches!(symbol.symbol_type, SymbolType::Variable(_)) {
+                if matches!(symbol.symbol_type, SymbolType::Variable(_) | SymbolType::Type | SymbolType::Module) {
                     names.push(name.clone());
                 }
             }
@@ -1795,6 +2644,30 @@ impl SymbolTable {
     }
 }
 
+/// Name of the struct (or other named type) a value has, without type arguments
+fn struct_type_name(ty: &Type) -> Option<&str> {
+    match ty {
+        Type::Custom(name) | Type::Generic(name, _) => Some(name),
+        _ => None,
+    }
+}
+
+/// Built-in type whose methods come from the stdlib method table, if `ty` is one
+fn builtin_type_kind(ty: &Type) -> Option<&'static str> {
+    match ty {
+        Type::String => Some("str"),
+        Type::Array(_) => Some("array"),
+        Type::Map(..) => Some("map"),
+        Type::Decimal => Some("decimal"),
+        Type::Option(_) => Some("Option"),
+        _ => match struct_type_name(ty) {
+            Some("Result") => Some("Result"),
+            Some("Option") => Some("Option"),
+            _ => None,
+        },
+    }
+}
+
 /// Calculate Levenshtein distance for similarity matching
 fn levenshtein_distance(s1: &str, s2: &str) -> usize {
     let len1 = s1.chars().count();
@@ -1841,6 +2714,7 @@ mod tests {
         let program = Program {
             statements: vec![Statement::FunctionDeclaration(FunctionDeclaration {
                 name: Identifier::new("main".to_string()),
+                type_parameters: vec![],
                 parameters: vec![],
                 return_type: None,
                 body: BlockStatement {
@@ -1867,6 +2741,7 @@ mod tests {
         let program = Program {
             statements: vec![Statement::FunctionDeclaration(FunctionDeclaration {
                 name: Identifier::new("main".to_string()),
+                type_parameters: vec![],
                 parameters: vec![],
                 return_type: None,
                 body: BlockStatement {
@@ -1889,4 +2764,389 @@ mod tests {
         // println should be recognized as a builtin
         assert!(!diagnostics.has_errors());
     }
+
+    #[test]
+    fn test_enum_variant_payload_arity() {
+        let mut analyzer = SemanticAnalyzer::new();
+
+        // Shape.Circle(1.0, 2.0) passes two values to a one-value variant
+        let program = Program {
+            statements: vec![
+                Statement::EnumDeclaration(EnumDeclaration {
+                    name: Identifier::new("Shape".to_string()),
+                    variants: vec![EnumVariant {
+                        name: Identifier::new("Circle".to_string()),
+                        fields: Some(vec![TypeAnnotation::Float]),
+                    }],
+                    is_public: false,
+                }),
+                Statement::ExpressionStatement(ExpressionStatement {
+                    expression: Expression::ModuleCallExpression(ModuleCallExpression::new(
+                        Identifier::new("Shape".to_string()),
+                        Identifier::new("Circle".to_string()),
+                        vec![
+                            Expression::FloatLiteral(FloatLiteral { value: 1.0 }),
+                            Expression::FloatLiteral(FloatLiteral { value: 2.0 }),
+                        ],
+                    )),
+                }),
+            ],
+        };
+
+        let diagnostics = analyzer.analyze(&program);
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_const_mutation_in_function_body() {
+        let mut analyzer = SemanticAnalyzer::new();
+
+        // fun bump() { LIMIT++ }  const LIMIT = 10
+        let program = Program {
+            statements: vec![
+                Statement::FunctionDeclaration(FunctionDeclaration {
+                    name: Identifier::new("bump".to_string()),
+                    type_parameters: vec![],
+                    parameters: vec![],
+                    return_type: None,
+                    body: BlockStatement {
+                        statements: vec![Statement::ExpressionStatement(ExpressionStatement {
+                            expression: Expression::UnaryExpression(UnaryExpression {
+                                operator: UnaryOperator::PostIncrement,
+                                operand: Box::new(Expression::Identifier(Identifier::new("LIMIT".to_string()))),
+                                span: None,
+                            }),
+                        })],
+                    },
+                    is_public: false,
+                }),
+                Statement::ConstantDeclaration(ConstantDeclaration {
+                    name: Identifier::new("LIMIT".to_string()),
+                    type_annotation: None,
+                    initializer: Expression::IntegerLiteral(IntegerLiteral::new(10)),
+                    is_public: false,
+                }),
+            ],
+        };
+
+        let diagnostics = analyzer.analyze(&program);
+        assert!(diagnostics.diagnostics.iter().any(|d| matches!(
+            &d.kind,
+            DiagnosticKind::ImmutableAssignment { name } if name == "LIMIT"
+        )));
+    }
+
+    #[test]
+    fn test_struct_operator_overloading() {
+        let source = r#"
+            struct Money { cents: int }
+            impl Money {
+                fun add(self, other: Money) -> Money {
+                    return Money { cents: self.cents + other.cents }
+                }
+            }
+            fun main() {
+                var a = Money { cents: 1 }
+                var total: Money = a + a
+                println(total)
+            }
+        "#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze(&program.unwrap());
+        assert!(!diagnostics.diagnostics.iter().any(|d| matches!(d.kind, DiagnosticKind::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_unknown_builtin_method_suggestion() {
+        let source = r#"
+            fun main() {
+                var name = "razen"
+                println(name.upper())
+                println(name.uper())
+            }
+        "#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze(&program.unwrap());
+        let unknown: Vec<_> = diagnostics.diagnostics.iter()
+            .filter(|d| matches!(d.kind, DiagnosticKind::UndefinedMethod { .. }))
+            .collect();
+        assert_eq!(unknown.len(), 1);
+        assert!(unknown[0].help.iter().any(|help| help.contains("upper")));
+    }
+
+    #[test]
+    fn test_struct_field_names_checked() {
+        let source = r#"
+            struct Point { x: int, y: int }
+            impl Point {
+                fun shift(self, dx: int) {
+                    self.x += dx
+                    self.z = dx
+                }
+            }
+            fun main() {
+                var p = Point { x: 1, y: 2 }
+                p.y = 5
+                println(p.w)
+            }
+        "#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze(&program.unwrap());
+        let missing: Vec<String> = diagnostics.diagnostics.iter()
+            .filter_map(|d| match &d.kind {
+                DiagnosticKind::MissingField { field, .. } => Some(field.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(missing, vec!["z".to_string(), "w".to_string()]);
+    }
+
+    #[test]
+    fn test_type_checker_reports_with_spans() {
+        let source = "fun label(n: int) -> str {\n    return n\n}\nfun main() {\n    var s = label(\"1\")\n    println(s)\n}\n";
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let lines: Vec<(String, usize)> = diagnostics.diagnostics.iter()
+            .filter_map(|d| {
+                let line = d.primary_span()?.start.line;
+                match &d.kind {
+                    DiagnosticKind::InvalidReturnType { found, .. } => Some((format!("return {}", found), line)),
+                    DiagnosticKind::TypeMismatch { found, .. } => Some((format!("argument {}", found), line)),
+                    _ => None,
+                }
+            })
+            .collect();
+        assert_eq!(lines, vec![("return int".to_string(), 2), ("argument str".to_string(), 5)]);
+    }
+
+    #[test]
+    fn test_comment_markers_in_strings_are_code() {
+        let source = "fun main() {\n    var url = \"http://x\" ; var n: int = \"bad\"\n    println(url + n)\n}\n";
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let mismatches: Vec<(usize, usize)> = diagnostics.diagnostics.iter()
+            .filter(|d| matches!(d.kind, DiagnosticKind::TypeMismatch { .. }))
+            .filter_map(|d| d.primary_span().map(|span| (span.start.line, span.start.column)))
+            .collect();
+        assert_eq!(mismatches, vec![(2, 32)]);
+    }
+
+    #[test]
+    fn test_trait_mismatch_points_at_impl_method() {
+        let source = r#"trait Store {
+    fun get(self, key: str) -> str
+}
+struct Mem { n: int }
+impl Store for Mem {
+    fun get(self, key: int) -> str {
+        return "x"
+    }
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let mismatch = diagnostics.diagnostics.iter()
+            .find(|d| d.code.as_deref() == Some("E0032"))
+            .expect("signature mismatch reported");
+        let labels: Vec<(usize, usize)> = mismatch.labels.iter()
+            .map(|label| (label.span.start.line, label.span.start.column))
+            .collect();
+        assert_eq!(labels, vec![(6, 9), (2, 9)]);
+    }
+
+    #[test]
+    fn test_diagnostics_point_at_the_reported_node() {
+        let source = r#"fun twice(x: int) -> int { return x * 2 }
+fun main() {
+    println(twice(1))
+    println(twice(2))
+    println(twice("three"))
+    var pair = ["a + b", 1 + true]
+    println(pair)
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        // Repeated calls and operators inside strings do not throw the positions off
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let errors: Vec<(usize, usize)> = diagnostics.diagnostics.iter()
+            .filter(|d| d.severity == crate::frontend::diagnostics::Severity::Error)
+            .filter_map(|d| d.primary_span().map(|span| (span.start.line, span.start.column)))
+            .collect();
+        assert_eq!(errors, vec![(5, 13), (6, 28)]);
+    }
+
+    #[test]
+    fn test_unary_operand_errors_point_at_operator() {
+        let source = r#"fun main() {
+    var name = "x"
+    var flag = !5
+    var neg = -name
+    var ok = !true
+    name++
+    println(f"{flag} {neg} {ok}")
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let errors: Vec<(usize, usize)> = diagnostics.diagnostics.iter()
+            .filter(|d| d.code.as_deref() == Some("E0020"))
+            .filter_map(|d| d.primary_span().map(|span| (span.start.line, span.start.column)))
+            .collect();
+        assert_eq!(errors, vec![(3, 16), (4, 15), (6, 9)]);
+    }
+
+    #[test]
+    fn test_inferred_types_narrow_and_point_at_initializer() {
+        let source = r#"fun find(xs: [int]) {
+    for x in xs {
+        if x > 0 {
+            return x
+        }
+    }
+    return null
+}
+fun main() {
+    var n = find([1])
+    var a = n + 1
+    if n != null {
+        println(n + 1)
+    }
+    var count = 0
+    count = "x"
+    println(a)
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        // (primary line, lines of the initializers the types were inferred from)
+        let errors: Vec<(usize, Vec<usize>)> = diagnostics.diagnostics.iter()
+            .filter(|d| matches!(d.code.as_deref(), Some("E0006" | "E0020")))
+            .filter_map(|d| {
+                let origins = d.labels.iter()
+                    .filter(|label| label.severity == crate::frontend::diagnostics::Severity::Note)
+                    .map(|label| label.span.start.line)
+                    .collect();
+                Some((d.primary_span()?.start.line, origins))
+            })
+            .collect();
+        assert_eq!(errors, vec![(11, vec![10]), (16, vec![15])]);
+    }
+
+    #[test]
+    fn test_return_types_inferred_across_declaration_order() {
+        let source = r#"fun main() {
+    var total: str = count(3)
+    var echoed: str = ping(1)
+    println(total, echoed)
+}
+fun count(n: int) {
+    if n == 0 {
+        return 0
+    }
+    return 1 + count(n - 1)
+}
+fun ping(n: int) {
+    return pong(n)
+}
+fun pong(n: int) {
+    return ping(n)
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        // count() is int even though main() calls it first and it calls itself; ping() and
+        // pong() only return each other, so they stay any
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let mismatches: Vec<(usize, String)> = diagnostics.diagnostics.iter()
+            .filter_map(|d| match &d.kind {
+                DiagnosticKind::TypeMismatch { found, .. } => Some((d.primary_span()?.start.line, found.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(mismatches, vec![(2, "int".to_string())]);
+    }
+
+    #[test]
+    fn test_unused_lambda_parameter_points_at_declaration() {
+        let source = r#"fun main() {
+    var b = 1
+    var f = |a, b| => a
+    println(f(b, 2))
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let unused: Vec<(usize, usize)> = diagnostics.diagnostics.iter()
+            .filter(|d| d.code.as_deref() == Some("W0001"))
+            .filter_map(|d| d.labels.first().map(|label| (label.span.start.line, label.span.start.column)))
+            .collect();
+        assert_eq!(unused, vec![(3, 17)]);
+    }
+
+    #[test]
+    fn test_tuple_diagnostics_point_at_source() {
+        let source = r#"fun main() {
+    var x = 1.5
+    var t = (x, "a")
+    var (a, b) = t
+    println(a, t.2)
+}
+"#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze_with_source(&program.unwrap(), source);
+        let location = |code: &str| diagnostics.diagnostics.iter()
+            .filter(|d| d.code.as_deref() == Some(code))
+            .filter_map(|d| d.labels.first().map(|label| (label.span.start.line, label.span.start.column)))
+            .collect::<Vec<_>>();
+        assert_eq!(location("W0001"), vec![(4, 13)]);
+        assert_eq!(location("E0022"), vec![(5, 17)]);
+    }
+
+    #[test]
+    fn test_clone_available_on_every_value() {
+        let source = r#"
+            struct Point { x: int, y: int }
+            impl Point {
+                fun norm(self) -> int {
+                    return self.x * self.x + self.y * self.y
+                }
+            }
+            fun main() {
+                var p = Point { x: 1, y: 2 }
+                var q: Point = p.clone()
+                var xs = [1, 2]
+                var ys = xs.clone()
+                println(q.norm())
+                println(ys)
+            }
+        "#;
+        let (program, _) = crate::frontend::parser::parse_source(source);
+        let mut analyzer = SemanticAnalyzer::new();
+
+        let diagnostics = analyzer.analyze(&program.unwrap());
+        assert!(!diagnostics.has_errors());
+    }
 }
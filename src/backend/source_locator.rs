@@ -0,0 +1,159 @@
+// src/backend/source_locator.rs
+//! Source positions for AST nodes: the spans the parser recorded, or else the text of a
+//! construct found in the source lines. Shared by semantic analysis and the type checker.
+
+use crate::frontend::diagnostics::{Position, Span};
+
+/// Finds where a construct appears in the source. Names and operators carry the span of
+/// their token; other nodes are searched for. Statements are checked in source order, so
+/// each search starts at the last position found and only wraps around when nothing
+/// follows it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SourceLocator {
+    lines: Vec<String>,
+    source_id: String,
+    line: usize, // 0-based line of the last match
+    column: Option<usize>, // 0-based column of the last match
+}
+
+impl SourceLocator {
+    pub(crate) fn new(lines: &[String], source_id: String) -> Self {
+        SourceLocator { lines: lines.to_vec(), source_id, line: 0, column: None }
+    }
+
+    /// Start searching from the top of the file again
+    pub(crate) fn rewind(&mut self) {
+        self.line = 0;
+        self.column = None;
+    }
+
+    /// The span the parser recorded for a node, moving the search there; None for nodes
+    /// built without one
+    pub(crate) fn at(&mut self, span: Option<&Span>) -> Option<Span> {
+        let span = span?;
+        self.line = span.start.line.saturating_sub(1);
+        self.column = Some(span.start.column.saturating_sub(1));
+        Some(span.clone().with_source(self.source_id.clone()))
+    }
+
+    /// Span of the next occurrence of `pattern` that `accept` agrees with, given the code
+    /// before and after it on its line
+    pub(crate) fn find_where(&mut self, pattern: &str, accept: impl Fn(&str, &str) -> bool) -> Span {
+        self.search(pattern, accept, 0)
+    }
+
+    /// Like `find_where`, but only past the previous match on its line, for constructs
+    /// that can repeat within a line
+    pub(crate) fn find_next_where(&mut self, pattern: &str, accept: impl Fn(&str, &str) -> bool) -> Span {
+        let skip = self.column.map_or(0, |column| column + 1);
+        self.search(pattern, accept, skip)
+    }
+
+    /// Search from the line of the last match, starting `skip` columns into that line
+    fn search(&mut self, pattern: &str, accept: impl Fn(&str, &str) -> bool, skip: usize) -> Span {
+        let line_count = self.lines.len();
+        let start = self.line.min(line_count);
+        for index in (start..line_count).chain(0..start) {
+            let from = if index == start { skip } else { 0 };
+            let accept = |before: &str, after: &str| before.len() >= from && accept(before, after);
+            if let Some(column) = find_in_line(&self.lines[index], pattern, &accept) {
+                self.line = index;
+                self.column = Some(column);
+                return self.span(index + 1, column + 1, pattern.len());
+            }
+        }
+        self.span(self.line + 1, 1, 0)
+    }
+
+    pub(crate) fn find(&mut self, pattern: &str) -> Span {
+        self.find_where(pattern, |_, _| true)
+    }
+
+    /// Span of a binary operator, skipping longer operators that contain it
+    pub(crate) fn find_operator(&mut self, symbol: &str) -> Span {
+        let is_operator_char = |c: char| "+-*/%=<>!&|^?".contains(c);
+        self.find_where(symbol, |before, after| {
+            !before.ends_with(is_operator_char) && !after.starts_with(is_operator_char)
+        })
+    }
+
+    /// Span of the initializer in `var name = value`, given the span of the name
+    pub(crate) fn initializer_span(&self, name_span: &Span) -> Option<Span> {
+        if name_span.start.column == name_span.end.column {
+            return None; // the declaration itself was not found
+        }
+        let line = self.lines.get(name_span.start.line - 1)?;
+        let after_name = name_span.end.column - 1;
+        let rest = line.get(after_name..)?;
+        let code = &rest[..code_end(rest, true)]; // up to a comment or the next statement
+        let equals = code.find('=')?;
+        let value = &code[equals + 1..];
+        let column = after_name + equals + 1 + (value.len() - value.trim_start().len());
+        let length = value.trim().len();
+        (length > 0).then(|| self.span(name_span.start.line, column + 1, length))
+    }
+
+    pub(crate) fn span(&self, line: usize, column: usize, length: usize) -> Span {
+        let start_pos = Position::new(line, column, 0);
+        let end_pos = Position::new(line, column + length, length);
+        Span::new(start_pos, end_pos).with_source(self.source_id.clone())
+    }
+}
+
+/// Column of the first match of `pattern` in the code part of a line, outside string and
+/// character literals. Patterns that start or end with a word character only match whole
+/// words.
+fn find_in_line(line: &str, pattern: &str, accept: &impl Fn(&str, &str) -> bool) -> Option<usize> {
+    let code = &line[..code_end(line, false)];
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    code.match_indices(pattern).map(|(column, _)| column).find(|&column| {
+        let before = &code[..column];
+        let after = &code[column + pattern.len()..];
+        let inside_word = (pattern.starts_with(is_word) && before.ends_with(is_word))
+            || (pattern.ends_with(is_word) && after.starts_with(is_word));
+        !inside_word && open_quote(before).is_none() && accept(before, after)
+    })
+}
+
+/// Where the code of a line ends: at a `//` comment or, with `at_semicolon`, at a `;`,
+/// neither counting inside string and character literals
+fn code_end(line: &str, at_semicolon: bool) -> usize {
+    let mut scan = QuoteScan::default();
+    for (index, c) in line.char_indices() {
+        if !scan.step(c) && (line[index..].starts_with("//") || (at_semicolon && c == ';')) {
+            return index;
+        }
+    }
+    line.len()
+}
+
+/// The quote of the literal still open at the end of `code`, if any
+fn open_quote(code: &str) -> Option<char> {
+    let mut scan = QuoteScan::default();
+    for c in code.chars() {
+        scan.step(c);
+    }
+    scan.quote
+}
+
+/// Tracks string and character literals while reading a line left to right
+#[derive(Default)]
+struct QuoteScan {
+    quote: Option<char>,
+    escaped: bool,
+}
+
+impl QuoteScan {
+    /// Read one character; true when it belongs to a literal, quotes included
+    fn step(&mut self, c: char) -> bool {
+        match self.quote {
+            Some(_) if self.escaped => self.escaped = false,
+            Some(_) if c == '\\' => self.escaped = true,
+            Some(open) if c == open => self.quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => self.quote = Some(c),
+            None => return false,
+        }
+        true
+    }
+}
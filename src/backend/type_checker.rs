@@ -1,145 +1,576 @@
 // src/backend/type_checker.rs
-//! Type checker for comprehensive type validation
+//! Static type checker: validates annotated variables, call arguments, return values,
+//! struct fields and operators against `Type`, reporting span-carrying diagnostics.
+//! Unannotated variables and functions take the type inferred from their initializer
+//! or return values, and conditions on a variable narrow its type inside the branch.
 
+use crate::backend::source_locator::SourceLocator;
 use crate::backend::types::Type;
+use crate::frontend::diagnostics::{helpers, Diagnostic, Diagnostics, Label, Span};
 use crate::frontend::parser::ast::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Declared parameter and return types of a function or method
+#[derive(Debug, Clone)]
+pub struct Signature {
+    params: Vec<(String, Type)>, // excluding `self` and a variadic rest parameter
+    return_type: Type,
+}
+
+/// Declared field types of a struct, in terms of its type parameters
+#[derive(Debug, Clone)]
+struct StructType {
+    type_params: Vec<String>,
+    fields: HashMap<String, Type>,
+}
+
+/// A variable in scope: the type it was declared or inferred with, and what a condition
+/// on it has narrowed that to
+#[derive(Debug, Clone)]
+struct Variable {
+    declared: Type,
+    narrowed: Option<Type>,
+    inferred_from: Option<Span>, // initializer the declared type was inferred from
+}
 
 /// Type context for tracking variable and function types
 #[derive(Debug, Clone)]
 pub struct TypeContext {
-    _variables: HashMap<String, Type>,
-    functions: HashMap<String, (Vec<Type>, Type)>, // (params, return_type)
-    scopes: Vec<HashMap<String, Type>>,
+    functions: HashMap<String, Signature>,
+    scopes: Vec<HashMap<String, Variable>>,
     type_aliases: HashMap<String, Type>, // type_name -> target_type
+    structs: HashMap<String, StructType>,
+    methods: HashMap<String, HashMap<String, Signature>>, // type_name -> method_name -> signature
+    trait_impls: HashMap<String, Vec<String>>,            // type_name -> implemented traits
+    named_types: HashSet<String>,                          // enums and traits
+}
+
+/// An unannotated function or method whose return type is being inferred
+struct PendingFunction<'a> {
+    impl_type: Option<&'a str>, // type of the impl block a method belongs to
+    name: &'a str,
+    parameters: &'a [Parameter],
+    type_params: Vec<String>,
+    body: &'a BlockStatement,
 }
 
 /// Main type checker
 pub struct TypeChecker {
     context: TypeContext,
-    errors: Vec<String>,
+    diagnostics: Diagnostics,
+    locator: SourceLocator,
+    return_type: Option<Type>, // declared return type of the function being checked
+    self_type: Option<Type>,   // type of `self` inside an impl block
+    returned: Option<Vec<Type>>, // types returned so far, while inferring a return type
+    called_pending: bool, // whether the expression being checked calls a function still being inferred
+}
+
+impl Signature {
+    fn new(params: Vec<(String, Type)>, return_type: Type) -> Self {
+        Signature { params, return_type }
+    }
 }
 
 impl TypeContext {
     pub fn new() -> Self {
         let mut context = TypeContext {
-            _variables: HashMap::new(),
             functions: HashMap::new(),
             scopes: vec![HashMap::new()],
             type_aliases: HashMap::new(),
+            structs: HashMap::new(),
+            methods: HashMap::new(),
+            trait_impls: HashMap::new(),
+            named_types: HashSet::new(),
         };
-        
+
         // Add builtin functions
         context.add_builtins();
         context
     }
-    
+
     fn add_builtins(&mut self) {
+        let param = |name: &str, ty: Type| (name.to_string(), ty);
+
         // I/O functions
-        self.functions.insert("print".to_string(), (vec![Type::Any], Type::Null));
-        self.functions.insert("println".to_string(), (vec![Type::Any], Type::Null));
-        self.functions.insert("printc".to_string(), (vec![Type::Any, Type::String], Type::Null)); // Colored print
-        self.functions.insert("printlnc".to_string(), (vec![Type::Any, Type::String], Type::Null)); // Colored println
-        self.functions.insert("input".to_string(), (vec![], Type::String));
-        self.functions.insert("read".to_string(), (vec![Type::String], Type::String));
-        self.functions.insert("write".to_string(), (vec![Type::String, Type::String], Type::Bool));
-        
-        // Dot notation type conversion methods
-        self.functions.insert("toint".to_string(), (vec![Type::Any], Type::Int));
-        self.functions.insert("tofloat".to_string(), (vec![Type::Any], Type::Float));
-        self.functions.insert("tostr".to_string(), (vec![Type::Any], Type::String));
-        self.functions.insert("tobool".to_string(), (vec![Type::Any], Type::Bool));
-    }
-    
+        self.declare_function("print".to_string(), vec![param("value", Type::Any)], Type::Null);
+        self.declare_function("println".to_string(), vec![param("value", Type::Any)], Type::Null);
+        self.declare_function("printc".to_string(), vec![param("text", Type::Any), param("color", Type::String)], Type::Null); // Colored print
+        self.declare_function("printlnc".to_string(), vec![param("text", Type::Any), param("color", Type::String)], Type::Null); // Colored println
+        self.declare_function("input".to_string(), vec![], Type::String);
+        self.declare_function("read".to_string(), vec![param("filename", Type::String)], Type::String);
+        self.declare_function("write".to_string(), vec![param("filename", Type::String), param("content", Type::String)], Type::Bool);
+        self.declare_function("len".to_string(), vec![param("value", Type::Any)], Type::Int);
+        self.declare_function("typeof".to_string(), vec![param("value", Type::Any)], Type::String);
+
+        // Type conversion functions
+        self.declare_function("toint".to_string(), vec![param("value", Type::Any)], Type::Int);
+        self.declare_function("tobigint".to_string(), vec![param("value", Type::Any)], Type::BigInt);
+        self.declare_function("decimal".to_string(), vec![param("value", Type::Any)], Type::Decimal);
+        self.declare_function("tofloat".to_string(), vec![param("value", Type::Any)], Type::Float);
+        self.declare_function("tostr".to_string(), vec![param("value", Type::Any)], Type::String);
+        self.declare_function("tobool".to_string(), vec![param("value", Type::Any)], Type::Bool);
+    }
+
     pub fn push_scope(&mut self) {
         self.scopes.push(HashMap::new());
     }
-    
+
     pub fn pop_scope(&mut self) {
         self.scopes.pop();
     }
-    
+
     pub fn declare_variable(&mut self, name: String, var_type: Type) {
+        self.declare_inferred(name, var_type, None);
+    }
+
+    fn declare_inferred(&mut self, name: String, var_type: Type, inferred_from: Option<Span>) {
         if let Some(current_scope) = self.scopes.last_mut() {
-            current_scope.insert(name, var_type);
+            current_scope.insert(name, Variable { declared: var_type, narrowed: None, inferred_from });
         }
     }
-    
-    pub fn declare_function(&mut self, name: String, params: Vec<Type>, return_type: Type) {
-        self.functions.insert(name, (params, return_type));
+
+    fn lookup(&self, name: &str) -> Option<&Variable> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
     }
-    
-    pub fn get_variable_type(&self, name: &str) -> Option<&Type> {
-        // Search from innermost to outermost scope
-        for scope in self.scopes.iter().rev() {
-            if let Some(var_type) = scope.get(name) {
-                return Some(var_type);
-            }
+
+    /// Narrow a variable for the rest of the current scope
+    fn narrow_variable(&mut self, name: &str, narrowed: Type) {
+        let Some(variable) = self.lookup(name).cloned() else {
+            return;
+        };
+        if let Some(current_scope) = self.scopes.last_mut() {
+            current_scope.insert(name.to_string(), Variable { narrowed: Some(narrowed), ..variable });
         }
-        None
-    }
-    
-    pub fn get_function_signature(&self, name: &str) -> Option<&(Vec<Type>, Type)> {
-        self.functions.get(name)
     }
-    
-    pub fn update_variable_type(&mut self, name: &str, new_type: Type) {
-        // Update in the most recent scope where the variable exists
-        for scope in self.scopes.iter_mut().rev() {
-            if scope.contains_key(name) {
-                scope.insert(name.to_string(), new_type);
-                return;
+
+    /// An assignment undoes what conditions established about a variable
+    fn clear_narrowing(&mut self, name: &str) {
+        for scope in &mut self.scopes {
+            if let Some(variable) = scope.get_mut(name) {
+                variable.narrowed = None;
             }
         }
     }
-    
+
+    pub fn declare_function(&mut self, name: String, params: Vec<(String, Type)>, return_type: Type) {
+        self.functions.insert(name, Signature::new(params, return_type));
+    }
+
+    /// Type of a variable at this point, taking narrowing into account
+    pub fn get_variable_type(&self, name: &str) -> Option<&Type> {
+        self.lookup(name).map(|variable| variable.narrowed.as_ref().unwrap_or(&variable.declared))
+    }
+
+    /// Type a variable was declared with, which is what assignments to it must fit
+    fn get_declared_type(&self, name: &str) -> Option<&Type> {
+        self.lookup(name).map(|variable| &variable.declared)
+    }
+
+    pub fn get_function_signature(&self, name: &str) -> Option<&Signature> {
+        self.functions.get(name)
+    }
+
     pub fn register_type_alias(&mut self, name: String, target_type: Type) {
         self.type_aliases.insert(name, target_type);
     }
-    
+
+    /// Expand aliases inside a type. Names that are not declared types become `Any`;
+    /// the semantic analyzer reports them.
     pub fn resolve_type_alias(&self, type_ref: &Type) -> Type {
         match type_ref {
             Type::Custom(name) => {
-                // Check if this is a type alias
                 if let Some(target_type) = self.type_aliases.get(name) {
                     // Recursively resolve in case the target is also an alias
                     self.resolve_type_alias(target_type)
-                } else {
-                    // Not an alias, return as-is
+                } else if self.structs.contains_key(name) || self.named_types.contains(name) {
                     type_ref.clone()
+                } else {
+                    Type::Any
                 }
             },
+            // A wrong number of type arguments is reported where the annotation is analyzed,
+            // so the annotation checks as Any rather than causing mismatches of its own
+            Type::Generic(name, args) if self.structs.get(name).is_some_and(|s| s.type_params.len() == args.len()) => {
+                Type::Generic(name.clone(), args.iter().map(|arg| self.resolve_type_alias(arg)).collect())
+            }
+            Type::Generic(..) => Type::Any,
+            Type::Array(inner) => Type::Array(Box::new(self.resolve_type_alias(inner))),
+            Type::Option(inner) => Type::Option(Box::new(self.resolve_type_alias(inner))),
+            Type::Map(key, value) => Type::Map(
+                Box::new(self.resolve_type_alias(key)),
+                Box::new(self.resolve_type_alias(value)),
+            ),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|e| self.resolve_type_alias(e)).collect()),
             _ => type_ref.clone(),
         }
     }
+
+    /// Whether `from` is a struct that implements the trait `to`
+    fn implements_trait(&self, from: &Type, to: &Type) -> bool {
+        match (from, to) {
+            (Type::Custom(type_name) | Type::Generic(type_name, _), Type::Custom(trait_name)) => self
+                .trait_impls
+                .get(type_name)
+                .is_some_and(|traits| traits.contains(trait_name)),
+            _ => false,
+        }
+    }
+
+    /// Whether a value of type `from` can be stored where `to` is expected
+    pub fn is_assignable(&self, from: &Type, to: &Type) -> bool {
+        from.can_assign_to(to) || self.implements_trait(from, to)
+    }
+
+    /// Declared type of a field, with the struct's type arguments filled in
+    fn field_type(&self, object_type: &Type, field: &str) -> Option<Type> {
+        let (name, args) = match object_type {
+            Type::Custom(name) => (name, &[][..]),
+            Type::Generic(name, args) => (name, &args[..]),
+            _ => return None,
+        };
+        let struct_type = self.structs.get(name)?;
+        let bindings: HashMap<String, Type> = struct_type.type_params.iter().cloned().zip(args.iter().cloned()).collect();
+        struct_type.fields.get(field).map(|ty| ty.substitute(&bindings))
+    }
+}
+
+/// Whether the code after a name is `op` (`=`, `+=`, ...) rather than a comparison
+fn is_assignment_operator(after: &str, op: &str) -> bool {
+    let after = after.trim_start();
+    after.starts_with(op) && !after[op.len()..].starts_with('=')
+}
+
+/// Whether the code before a name declares it (`var x`, `const x`)
+fn is_declaration(before: &str) -> bool {
+    let before = before.trim_end();
+    before.ends_with("var") || before.ends_with("const")
+}
+
+/// Result type of the builtins that build and take apart Option values
+fn option_builtin_type(name: &str, arg_types: &[Type]) -> Type {
+    match (name, arg_types) {
+        ("Some", [value]) => Type::Option(Box::new(value.clone())),
+        ("None", _) => Type::Option(Box::new(Type::Unknown)),
+        ("unwrap", [Type::Option(inner)]) => (**inner).clone(),
+        ("unwrap_or", [Type::Option(inner), default]) if **inner == *default => default.clone(),
+        ("is_some" | "is_none", _) => Type::Bool,
+        _ => Type::Unknown,
+    }
+}
+
+/// The type all elements of a literal share, or unknown when they differ
+fn common_type(types: &[Type]) -> Type {
+    match types.split_first() {
+        Some((first, rest)) if rest.iter().all(|t| t == first) => first.clone(),
+        _ => Type::Unknown,
+    }
+}
+
+/// The variable `call` passes to one of the given builtins: `is_some(x)` gives `x`
+fn checked_variable<'a>(call: &'a CallExpression, builtins: &[&str]) -> Option<&'a str> {
+    match (call.callee.as_ref(), call.arguments.as_slice()) {
+        (Expression::Identifier(callee), [Expression::Identifier(variable)]) if builtins.contains(&callee.name.as_str()) => {
+            Some(&variable.name)
+        }
+        _ => None,
+    }
+}
+
+/// Whether control never reaches the statement after this one
+fn always_exits(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::ReturnStatement(_) | Statement::ThrowStatement(_) |
+        Statement::BreakStatement(_) | Statement::ContinueStatement(_) => true,
+        Statement::BlockStatement(block) => block.statements.iter().any(always_exits),
+        Statement::IfStatement(if_stmt) => {
+            always_exits(&if_stmt.then_branch)
+                && if_stmt.elif_branches.iter().all(|elif| always_exits(&elif.body))
+                && if_stmt.else_branch.as_deref().is_some_and(always_exits)
+        }
+        _ => false,
+    }
+}
+
+fn unary_operator_symbol(op: &UnaryOperator) -> &'static str {
+    match op {
+        UnaryOperator::Not => "!",
+        UnaryOperator::Minus => "-",
+        UnaryOperator::Plus => "+",
+        UnaryOperator::BitwiseNot => "~",
+        UnaryOperator::PreIncrement | UnaryOperator::PostIncrement => "++",
+        UnaryOperator::PreDecrement | UnaryOperator::PostDecrement => "--",
+    }
+}
+
+fn binary_operator_symbol(op: &BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Power => "**",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Less => "<",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::LessEqual => "<=",
+        BinaryOperator::GreaterEqual => ">=",
+        BinaryOperator::And => "&&",
+        BinaryOperator::Or => "||",
+        BinaryOperator::BitwiseAnd => "&",
+        BinaryOperator::BitwiseOr => "|",
+        BinaryOperator::BitwiseXor => "^",
+        BinaryOperator::LeftShift => "<<",
+        BinaryOperator::RightShift => ">>",
+        BinaryOperator::Range => "..",
+        BinaryOperator::NullCoalesce => "??",
+    }
+}
+
+/// The binary operator a compound assignment applies, and how it is written
+fn compound_operator(op: &AssignmentOperator) -> Option<(BinaryOperator, &'static str)> {
+    match op {
+        AssignmentOperator::Assign => None,
+        AssignmentOperator::AddAssign => Some((BinaryOperator::Add, "+=")),
+        AssignmentOperator::SubtractAssign => Some((BinaryOperator::Subtract, "-=")),
+        AssignmentOperator::MultiplyAssign => Some((BinaryOperator::Multiply, "*=")),
+        AssignmentOperator::DivideAssign => Some((BinaryOperator::Divide, "/=")),
+        AssignmentOperator::ModuloAssign => Some((BinaryOperator::Modulo, "%=")),
+        AssignmentOperator::BitwiseAndAssign => Some((BinaryOperator::BitwiseAnd, "&=")),
+        AssignmentOperator::BitwiseOrAssign => Some((BinaryOperator::BitwiseOr, "|=")),
+        AssignmentOperator::BitwiseXorAssign => Some((BinaryOperator::BitwiseXor, "^=")),
+        AssignmentOperator::LeftShiftAssign => Some((BinaryOperator::LeftShift, "<<=")),
+        AssignmentOperator::RightShiftAssign => Some((BinaryOperator::RightShift, ">>=")),
+    }
 }
 
 impl TypeChecker {
     pub fn new() -> Self {
         TypeChecker {
             context: TypeContext::new(),
-            errors: Vec::new(),
+            diagnostics: Diagnostics::new(),
+            locator: SourceLocator::default(),
+            return_type: None,
+            self_type: None,
+            returned: None,
+            called_pending: false,
         }
     }
-    
-    pub fn check_program(&mut self, program: &Program) -> Vec<String> {
-        self.errors.clear();
-        
-        // First pass: collect all function declarations
+
+    /// Source text the diagnostics point into, and the file name they are reported under
+    pub fn set_source(&mut self, lines: &[String], source_id: String) {
+        self.locator = SourceLocator::new(lines, source_id);
+    }
+
+    pub fn check_program(&mut self, program: &Program) -> Diagnostics {
+        self.diagnostics = Diagnostics::new();
+        self.locator.rewind();
+
+        // First pass: collect type aliases, then the declarations that refer to them
+        for stmt in &program.statements {
+            match stmt {
+                Statement::TypeAliasDeclaration(alias) => {
+                    let target = Type::from_annotation(&alias.target_type);
+                    self.context.register_type_alias(alias.name.name.clone(), target);
+                }
+                Statement::StructDeclaration(struct_decl) => {
+                    // Registered up front so field types may name any struct
+                    self.context.structs.insert(
+                        struct_decl.name.name.clone(),
+                        StructType { type_params: Vec::new(), fields: HashMap::new() },
+                    );
+                }
+                Statement::EnumDeclaration(enum_decl) => {
+                    self.context.named_types.insert(enum_decl.name.name.clone());
+                }
+                Statement::TraitDeclaration(trait_decl) => {
+                    self.context.named_types.insert(trait_decl.name.name.clone());
+                }
+                _ => {}
+            }
+        }
         for stmt in &program.statements {
-            if let Statement::FunctionDeclaration(func_decl) = stmt {
-                self.collect_function_declaration(func_decl);
+            match stmt {
+                Statement::FunctionDeclaration(func_decl) => self.collect_function_declaration(func_decl),
+                Statement::StructDeclaration(struct_decl) => self.collect_struct_declaration(struct_decl),
+                Statement::ImplBlock(impl_block) => self.collect_impl_block(impl_block),
+                _ => {}
             }
         }
-        
+
+        self.infer_return_types(program);
+
         // Second pass: check all statements
+        self.locator.rewind();
         for stmt in &program.statements {
             self.check_statement(stmt);
         }
-        
-        self.errors.clone()
+
+        self.diagnostics.clone()
     }
-    
+
+    /// Unannotated functions and methods return what their return statements do. They start
+    /// out pending (Unknown) and are inferred in rounds until nothing changes, so a call
+    /// sees the return type of a function declared after it; whatever is still pending
+    /// after that, such as functions that only return each other's results, is `any`.
+    fn infer_return_types(&mut self, program: &Program) {
+        let mut pending: Vec<PendingFunction> = Vec::new();
+        for stmt in &program.statements {
+            match stmt {
+                Statement::FunctionDeclaration(func_decl) if func_decl.return_type.is_none() => {
+                    pending.push(PendingFunction {
+                        impl_type: None,
+                        name: &func_decl.name.name,
+                        parameters: &func_decl.parameters,
+                        type_params: func_decl.type_parameters.iter().map(|t| t.name.clone()).collect(),
+                        body: &func_decl.body,
+                    });
+                }
+                Statement::ImplBlock(impl_block) => {
+                    for method in impl_block.methods.iter().filter(|m| m.return_type.is_none()) {
+                        pending.push(PendingFunction {
+                            impl_type: Some(&impl_block.target_type.name),
+                            name: &method.name.name,
+                            parameters: &method.parameters,
+                            type_params: Vec::new(),
+                            body: &method.body,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        for function in &pending {
+            if let Some(signature) = self.inferred_signature(function) {
+                signature.return_type = Type::Unknown;
+            }
+        }
+
+        // Calls between n functions settle within n rounds; one more shows nothing changed
+        for _ in 0..pending.len() + 2 {
+            let mut changed = false;
+            for function in &pending {
+                let self_type = function.impl_type.map(|name| Type::Custom(name.to_string()));
+                let old_self_type = std::mem::replace(&mut self.self_type, self_type);
+                let return_type = self.infer_return_type(function.parameters, &function.type_params, function.body);
+                self.self_type = old_self_type;
+                if let Some(signature) = self.inferred_signature(function)
+                    && signature.return_type != return_type
+                {
+                    signature.return_type = return_type;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for function in &pending {
+            if let Some(signature) = self.inferred_signature(function)
+                && signature.return_type == Type::Unknown
+            {
+                signature.return_type = Type::Any;
+            }
+        }
+    }
+
+    /// Signature a pending function's inferred return type is stored in
+    fn inferred_signature(&mut self, function: &PendingFunction) -> Option<&mut Signature> {
+        match function.impl_type {
+            Some(type_name) => self.context.methods.get_mut(type_name)?.get_mut(function.name),
+            None => self.context.functions.get_mut(function.name),
+        }
+    }
+
+    /// Return type of a call to `signature`, noting a callee whose return type is pending
+    fn call_result(&mut self, signature: &Signature, bindings: &HashMap<String, Type>) -> Type {
+        if signature.return_type == Type::Unknown {
+            self.called_pending = true;
+        }
+        signature.return_type.substitute(bindings)
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.add(diagnostic);
+    }
+
+    /// Point out where the types of variables among `exprs` were inferred from
+    fn with_origins(&self, mut diagnostic: Diagnostic, exprs: &[&Expression]) -> Diagnostic {
+        for expr in exprs {
+            let mut expr = *expr;
+            while let Expression::GroupingExpression(grouping) = expr {
+                expr = &grouping.expression;
+            }
+            let Expression::Identifier(ident) = expr else {
+                continue;
+            };
+            // A narrowed type comes from a condition rather than the initializer
+            if let Some(variable) = self.context.lookup(&ident.name)
+                && variable.narrowed.is_none()
+                && let Some(ref origin) = variable.inferred_from
+            {
+                let label = Label::secondary(origin.clone())
+                    .with_message(format!("`{}` inferred as `{}` here", ident.name, variable.declared));
+                diagnostic = diagnostic.with_label(label);
+            }
+        }
+        diagnostic
+    }
+
+    /// Resolve an annotation written inside a declaration with the given type parameters
+    fn annotation_type(&self, annotation: &TypeAnnotation, type_params: &[String]) -> Type {
+        match (annotation, &self.self_type) {
+            (TypeAnnotation::Custom(ident), Some(self_type)) if ident.name == "Self" => self_type.clone(),
+            _ => self.context.resolve_type_alias(&Type::from_annotation_with_params(annotation, type_params)),
+        }
+    }
+
+    fn signature_of(&self, parameters: &[Parameter], return_type: &Option<TypeAnnotation>, type_params: &[String]) -> Signature {
+        let params = parameters.iter()
+            .filter(|p| !p.is_variadic && p.name.name != "self")
+            .map(|p| {
+                let param_type = p.type_annotation.as_ref()
+                    .map(|t| self.annotation_type(t, type_params))
+                    .unwrap_or(Type::Any); // Parameters without type annotations are flexible
+                (p.name.name.clone(), param_type)
+            })
+            .collect();
+        let return_type = return_type.as_ref()
+            .map(|t| self.annotation_type(t, type_params))
+            .unwrap_or(Type::Any);
+        Signature::new(params, return_type)
+    }
+
+    fn collect_function_declaration(&mut self, func_decl: &FunctionDeclaration) {
+        let type_params: Vec<String> = func_decl.type_parameters.iter().map(|t| t.name.clone()).collect();
+        let signature = self.signature_of(&func_decl.parameters, &func_decl.return_type, &type_params);
+        self.context.functions.insert(func_decl.name.name.clone(), signature);
+    }
+
+    fn collect_struct_declaration(&mut self, struct_decl: &StructDeclaration) {
+        let type_params: Vec<String> = struct_decl.type_parameters.iter().map(|t| t.name.clone()).collect();
+        let fields = struct_decl.fields.iter()
+            .map(|field| (field.name.name.clone(), self.annotation_type(&field.type_annotation, &type_params)))
+            .collect();
+        self.context.structs.insert(struct_decl.name.name.clone(), StructType { type_params, fields });
+    }
+
+    fn collect_impl_block(&mut self, impl_block: &ImplBlock) {
+        let type_name = impl_block.target_type.name.clone();
+        if let Some(ref trait_name) = impl_block.trait_name {
+            self.context.trait_impls.entry(type_name.clone()).or_default().push(trait_name.name.clone());
+        }
+
+        let old_self_type = self.self_type.replace(Type::Custom(type_name.clone()));
+        for method in &impl_block.methods {
+            let signature = self.signature_of(&method.parameters, &method.return_type, &[]);
+            self.context.methods.entry(type_name.clone()).or_default().insert(method.name.name.clone(), signature);
+        }
+        self.self_type = old_self_type;
+    }
+
     fn check_statement(&mut self, stmt: &Statement) {
         match stmt {
             Statement::VariableDeclaration(var_decl) => {
@@ -148,311 +579,1016 @@ impl TypeChecker {
             Statement::ConstantDeclaration(const_decl) => {
                 self.check_constant_declaration(const_decl);
             }
+            Statement::TypeAliasDeclaration(alias) => {
+                let target = Type::from_annotation(&alias.target_type);
+                self.context.register_type_alias(alias.name.name.clone(), target);
+            }
             Statement::FunctionDeclaration(func_decl) => {
+                // Nested functions are not seen by the collection pass
+                if !self.context.functions.contains_key(&func_decl.name.name) {
+                    self.collect_function_declaration(func_decl);
+                }
+                // Its returns are not those of the function it is nested in
+                let returned = self.returned.take();
                 self.check_function_declaration(func_decl);
+                self.returned = returned;
+            }
+            Statement::ImplBlock(impl_block) => {
+                let returned = self.returned.take();
+                self.check_impl_block(impl_block);
+                self.returned = returned;
             }
             Statement::ExpressionStatement(expr_stmt) => {
                 self.check_expression(&expr_stmt.expression);
             }
-            Statement::BlockStatement(block_stmt) => {
+            Statement::ReturnStatement(ret_stmt) => {
+                self.check_return_statement(ret_stmt);
+            }
+            Statement::IfStatement(if_stmt) => {
+                self.check_if_statement(if_stmt);
+            }
+            Statement::WhileStatement(while_stmt) => {
+                self.check_expression(&while_stmt.condition);
+                let narrowings = self.narrowings(&while_stmt.condition, true);
+                self.check_narrowed(&while_stmt.body, &narrowings);
+            }
+            Statement::ForStatement(for_stmt) => {
+                let element_type = match (&for_stmt.iterable, self.check_expression(&for_stmt.iterable)) {
+                    (Expression::RangeExpression(_), _) => Type::Int,
+                    (_, Type::Array(element)) => *element,
+                    (_, Type::String) => Type::String,
+                    // Maps are iterated as (key, value) pairs with string keys
+                    (_, Type::Map(_, value)) => Type::Tuple(vec![Type::String, *value]),
+                    _ => Type::Unknown,
+                };
                 self.context.push_scope();
-                for stmt in &block_stmt.statements {
-                    self.check_statement(stmt);
+                if for_stmt.destructure.is_empty() {
+                    self.context.declare_variable(for_stmt.variable.name.clone(), element_type);
+                } else {
+                    let parts = match element_type {
+                        Type::Tuple(parts) if parts.len() == for_stmt.destructure.len() => parts,
+                        _ => vec![Type::Unknown; for_stmt.destructure.len()],
+                    };
+                    for (name, part) in for_stmt.destructure.iter().zip(parts) {
+                        self.context.declare_variable(name.name.clone(), part);
+                    }
                 }
+                self.check_statement(&for_stmt.body);
                 self.context.pop_scope();
             }
+            Statement::MatchStatement(match_stmt) => {
+                self.check_match(&match_stmt.expression, &match_stmt.arms);
+            }
+            Statement::TryStatement(try_stmt) => {
+                self.check_block(&try_stmt.body);
+                if let Some(ref catch_clause) = try_stmt.catch_clause {
+                    self.context.push_scope();
+                    if let Some(ref parameter) = catch_clause.parameter {
+                        self.context.declare_variable(parameter.name.clone(), Type::Unknown);
+                    }
+                    self.check_block(&catch_clause.body);
+                    self.context.pop_scope();
+                }
+            }
+            Statement::BreakStatement(break_stmt) => {
+                if let Some(ref value) = break_stmt.value {
+                    self.check_expression(value);
+                }
+            }
+            Statement::ThrowStatement(throw_stmt) => {
+                self.check_expression(&throw_stmt.value);
+            }
+            Statement::DeferStatement(defer_stmt) => {
+                self.check_block(&defer_stmt.body);
+            }
+            Statement::BlockStatement(block_stmt) => {
+                self.check_block(block_stmt);
+            }
             _ => {
-                // Handle other statement types as needed
+                // Declarations without bodies were handled by the collection pass
             }
         }
     }
-    
-    fn check_variable_declaration(&mut self, var_decl: &VariableDeclaration) {
-        let var_name = &var_decl.name.name;
-        
-        // Get the declared type if any
-        let declared_type = var_decl.type_annotation.as_ref()
-            .map(|t| Type::from_annotation(t));
-        
-        // Get the inferred type from initializer if any
-        let inferred_type = var_decl.initializer.as_ref()
-            .map(|expr| self.check_expression(expr));
-        
-        match (declared_type, inferred_type) {
-            // Both declared type and initializer present
-            (Some(declared), Some(inferred)) => {
-                if !inferred.can_assign_to(&declared) {
-                    self.errors.push(format!(
-                        "Type mismatch: variable '{}' declared as '{}' but assigned value of type '{}'",
-                        var_name, declared, inferred
-                    ));
+
+    fn check_block(&mut self, block: &BlockStatement) {
+        self.context.push_scope();
+        for stmt in &block.statements {
+            self.check_statement(stmt);
+        }
+        self.context.pop_scope();
+    }
+
+    /// Check a branch body in its own scope, even when it is a single statement, with the
+    /// variables its condition narrowed
+    fn check_narrowed(&mut self, stmt: &Statement, narrowings: &[(String, Type)]) {
+        self.context.push_scope();
+        for (name, narrowed) in narrowings {
+            self.context.narrow_variable(name, narrowed.clone());
+        }
+        self.check_statement(stmt);
+        self.context.pop_scope();
+    }
+
+    fn check_if_statement(&mut self, if_stmt: &IfStatement) {
+        self.check_expression(&if_stmt.condition);
+        let narrowings = self.narrowings(&if_stmt.condition, true);
+        self.check_narrowed(&if_stmt.then_branch, &narrowings);
+
+        // Later branches only run when the earlier conditions were false
+        let mut failed = self.narrowings(&if_stmt.condition, false);
+        for elif in &if_stmt.elif_branches {
+            self.context.push_scope();
+            for (name, narrowed) in &failed {
+                self.context.narrow_variable(name, narrowed.clone());
+            }
+            self.check_expression(&elif.condition);
+            let narrowings = self.narrowings(&elif.condition, true);
+            failed.extend(self.narrowings(&elif.condition, false));
+            self.check_narrowed(&elif.body, &narrowings);
+            self.context.pop_scope();
+        }
+
+        match if_stmt.else_branch {
+            Some(ref else_branch) => self.check_narrowed(else_branch, &failed),
+            // `if x == null { return }`: the code after it only runs when every condition failed
+            None if always_exits(&if_stmt.then_branch)
+                && if_stmt.elif_branches.iter().all(|elif| always_exits(&elif.body)) =>
+            {
+                for (name, narrowed) in failed {
+                    self.context.narrow_variable(&name, narrowed);
                 }
-                self.context.declare_variable(var_name.clone(), declared);
             }
-            
-            // Only declared type, no initializer
-            (Some(declared), None) => {
-                self.context.declare_variable(var_name.clone(), declared);
+            None => {}
+        }
+    }
+
+    /// Types a condition establishes for variables when it evaluates to `holds`
+    fn narrowings(&self, condition: &Expression, holds: bool) -> Vec<(String, Type)> {
+        match condition {
+            Expression::GroupingExpression(grouping) => self.narrowings(&grouping.expression, holds),
+            Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::Not => {
+                self.narrowings(&unary.operand, !holds)
             }
-            
-            // Only initializer, no declared type (use inferred type)
-            (None, Some(inferred)) => {
-                    self.context.declare_variable(var_name.clone(), inferred);
+            // Both sides of a true `&&` hold, and neither side of a false `||` does
+            Expression::BinaryExpression(bin_expr)
+                if (bin_expr.operator == BinaryOperator::And && holds)
+                    || (bin_expr.operator == BinaryOperator::Or && !holds) =>
+            {
+                let mut narrowings = self.narrowings(&bin_expr.left, holds);
+                narrowings.extend(self.narrowings(&bin_expr.right, holds));
+                narrowings
             }
-            
-            // Neither declared type nor initializer
-            (None, None) => {
-                self.context.declare_variable(var_name.clone(), Type::Any);
+            Expression::BinaryExpression(bin_expr)
+                if matches!(bin_expr.operator, BinaryOperator::Equal | BinaryOperator::NotEqual) =>
+            {
+                let equal = (bin_expr.operator == BinaryOperator::Equal) == holds;
+                let narrowing = match (bin_expr.left.as_ref(), bin_expr.right.as_ref()) {
+                    // x != null
+                    (Expression::Identifier(ident), Expression::NullLiteral(_))
+                    | (Expression::NullLiteral(_), Expression::Identifier(ident)) if !equal => {
+                        match self.context.get_variable_type(&ident.name) {
+                            Some(Type::Nullable(inner)) => Some((ident.name.clone(), *inner.clone())),
+                            _ => None,
+                        }
+                    }
+                    // typeof(x) == "int"
+                    (Expression::CallExpression(call), Expression::StringLiteral(name))
+                    | (Expression::StringLiteral(name), Expression::CallExpression(call)) if equal => {
+                        self.typeof_narrowing(call, &name.value)
+                    }
+                    _ => None,
+                };
+                narrowing.into_iter().collect()
             }
+            // is_some(x) and is_none(x) are only true of Option values
+            Expression::CallExpression(call) if holds => {
+                let Some(name) = checked_variable(call, &["is_some", "is_none"]) else {
+                    return Vec::new();
+                };
+                let narrowed = match self.context.get_variable_type(name) {
+                    Some(Type::Any | Type::Unknown) => Type::Option(Box::new(Type::Unknown)),
+                    Some(Type::Nullable(inner)) if matches!(**inner, Type::Option(_)) => *inner.clone(),
+                    _ => return Vec::new(),
+                };
+                vec![(name.to_string(), narrowed)]
+            }
+            _ => Vec::new(),
         }
     }
-    
+
+    /// The type `typeof(x) == name` gives `x`, when that says more than its current type
+    fn typeof_narrowing(&self, call: &CallExpression, name: &str) -> Option<(String, Type)> {
+        let variable = checked_variable(call, &["typeof"])?;
+        let checked = match name {
+            "int" => Type::Int,
+            "bigint" => Type::BigInt,
+            "float" => Type::Float,
+            "decimal" => Type::Decimal,
+            "str" => Type::String,
+            "bool" => Type::Bool,
+            "null" => Type::Null,
+            "array" => Type::Array(Box::new(Type::Unknown)),
+            "map" => Type::Map(Box::new(Type::Unknown), Box::new(Type::Unknown)),
+            "Option" => Type::Option(Box::new(Type::Unknown)),
+            _ if self.context.structs.contains_key(name) || self.context.named_types.contains(name) => {
+                Type::Custom(name.to_string())
+            }
+            _ => return None,
+        };
+        let narrowed = match self.context.get_variable_type(variable)? {
+            Type::Any | Type::Unknown => checked,
+            // Keep what is known about the value when the check agrees with it
+            Type::Nullable(inner) if inner.can_assign_to(&checked) => *inner.clone(),
+            Type::Nullable(_) => checked,
+            _ => return None,
+        };
+        Some((variable.to_string(), narrowed))
+    }
+
+    fn check_variable_declaration(&mut self, var_decl: &VariableDeclaration) {
+        // var (q, r) = ...: the parts are not tracked individually
+        if !var_decl.destructure.is_empty() {
+            if let Some(ref initializer) = var_decl.initializer {
+                self.check_expression(initializer);
+            }
+            for name in &var_decl.destructure {
+                self.context.declare_variable(name.name.clone(), Type::Unknown);
+            }
+            return;
+        }
+
+        let var_name = &var_decl.name.name;
+        let span = self.locator.at(var_decl.name.span.as_ref())
+            .unwrap_or_else(|| self.locator.find_where(var_name, |before, _| before.trim_end().ends_with("var")));
+        let declared_type = var_decl.type_annotation.as_ref()
+            .map(|t| self.annotation_type(t, &[]));
+        let inferred_type = var_decl.initializer.as_ref()
+            .map(|expr| self.check_expression(expr));
+
+        self.declare_checked(var_name, declared_type, inferred_type, var_decl.initializer.as_ref(), span);
+    }
+
     fn check_constant_declaration(&mut self, const_decl: &ConstantDeclaration) {
         let const_name = &const_decl.name.name;
-        
-        // Get the declared type if any
+        let span = self.locator.at(const_decl.name.span.as_ref())
+            .unwrap_or_else(|| self.locator.find_where(const_name, |before, _| before.trim_end().ends_with("const")));
         let declared_type = const_decl.type_annotation.as_ref()
-            .map(|t| Type::from_annotation(t));
-        
-        // Get the inferred type from initializer (constants must have initializers)
+            .map(|t| self.annotation_type(t, &[]));
         let inferred_type = self.check_expression(&const_decl.initializer);
-        
-        match declared_type {
+
+        self.declare_checked(const_name, declared_type, Some(inferred_type), Some(&const_decl.initializer), span);
+    }
+
+    /// Declare a variable with its checked type, remembering the initializer when the
+    /// type was inferred from it
+    fn declare_checked(
+        &mut self,
+        name: &str,
+        declared: Option<Type>,
+        inferred: Option<Type>,
+        initializer: Option<&Expression>,
+        span: Span,
+    ) {
+        let is_inferred = declared.is_none();
+        let var_type = self.check_declared_type(declared, inferred, initializer, span.clone());
+        let inferred_from = match var_type {
+            Type::Any | Type::Unknown => None,
+            _ if is_inferred => self.locator.initializer_span(&span),
+            _ => None,
+        };
+        self.context.declare_inferred(name.to_string(), var_type, inferred_from);
+    }
+
+    /// Check an initializer against the annotation and return the type the name gets
+    fn check_declared_type(
+        &mut self,
+        declared: Option<Type>,
+        inferred: Option<Type>,
+        initializer: Option<&Expression>,
+        span: Span,
+    ) -> Type {
+        match (declared, inferred) {
             // Both declared type and initializer present
-            Some(declared) => {
-                if !inferred_type.can_assign_to(&declared) {
-                    self.errors.push(format!(
-                        "Type mismatch: constant '{}' declared as '{}' but assigned value of type '{}'",
-                        const_name, declared, inferred_type
-                    ));
+            (Some(declared), Some(inferred)) => {
+                if !self.context.is_assignable(&inferred, &declared) {
+                    let diagnostic = helpers::type_mismatch(declared.to_string(), inferred.to_string(), span);
+                    let diagnostic = self.with_origins(diagnostic, initializer.as_slice());
+                    self.report(diagnostic);
                 }
-                self.context.declare_variable(const_name.clone(), declared);
-            }
-            
-            // Only initializer, no declared type (use inferred type)
-            None => {
-                self.context.declare_variable(const_name.clone(), inferred_type);
+                declared
             }
+
+            // Only declared type, no initializer
+            (Some(declared), None) => declared,
+
+            // Only initializer: a null start says nothing about later values
+            (None, Some(Type::Null)) => Type::Any,
+            (None, Some(inferred)) => inferred,
+
+            // Neither declared type nor initializer
+            (None, None) => Type::Any,
         }
     }
-    
-    fn collect_function_declaration(&mut self, func_decl: &FunctionDeclaration) {
-        let param_types: Vec<Type> = func_decl.parameters.iter()
-            .map(|p| {
-                if let Some(ref type_ann) = p.type_annotation {
-                    Type::from_annotation(type_ann)
-                } else {
-                    Type::Any // Parameters without type annotations are flexible
-                }
-            })
-            .collect();
-        
-        let return_type = func_decl.return_type.as_ref()
-            .map(|t| Type::from_annotation(t))
-            .unwrap_or(Type::Null);
-        
-        
-        self.context.declare_function(
-            func_decl.name.name.clone(),
-            param_types,
-            return_type
-        );
-    }
-    
+
     fn check_function_declaration(&mut self, func_decl: &FunctionDeclaration) {
+        if self.locator.at(func_decl.name.span.as_ref()).is_none() {
+            self.locator.find_where(&func_decl.name.name, |before, _| before.trim_end().ends_with("fun"));
+        }
+        let type_params: Vec<String> = func_decl.type_parameters.iter().map(|t| t.name.clone()).collect();
+        self.check_function_body(&func_decl.parameters, &func_decl.return_type, &type_params, &func_decl.body);
+    }
+
+    fn check_impl_block(&mut self, impl_block: &ImplBlock) {
+        let old_self_type = self.self_type.replace(Type::Custom(impl_block.target_type.name.clone()));
+        for method in &impl_block.methods {
+            if self.locator.at(method.name.span.as_ref()).is_none() {
+                self.locator.find_where(&method.name.name, |before, _| before.trim_end().ends_with("fun"));
+            }
+            self.check_function_body(&method.parameters, &method.return_type, &[], &method.body);
+        }
+        self.self_type = old_self_type;
+    }
+
+    fn check_function_body(
+        &mut self,
+        parameters: &[Parameter],
+        return_type: &Option<TypeAnnotation>,
+        type_params: &[String],
+        body: &BlockStatement,
+    ) {
         // Create new scope for function
         self.context.push_scope();
-        
-        // Add parameters to scope
-        for param in &func_decl.parameters {
-            let param_type = if let Some(ref type_ann) = param.type_annotation {
-                Type::from_annotation(type_ann)
-            } else {
-                Type::Any // Parameters without type annotations are flexible
+
+        // Type parameters stand for any type inside the body
+        let erased: HashMap<String, Type> = type_params.iter().map(|t| (t.clone(), Type::Any)).collect();
+        for param in parameters {
+            let param_type = match param.type_annotation {
+                _ if param.is_variadic => Type::Array(Box::new(Type::Unknown)),
+                Some(ref type_ann) => self.annotation_type(type_ann, type_params).substitute(&erased),
+                None => Type::Any, // Parameters without type annotations are flexible
             };
+            if let Some(ref default_value) = param.default_value {
+                self.check_expression(default_value);
+            }
             self.context.declare_variable(param.name.name.clone(), param_type);
         }
-        
+
+        let declared_return = return_type.as_ref()
+            .map(|t| self.annotation_type(t, type_params).substitute(&erased));
+        let old_return_type = std::mem::replace(&mut self.return_type, declared_return);
+
         // Check function body
-        for stmt in &func_decl.body.statements {
+        for stmt in &body.statements {
             self.check_statement(stmt);
         }
-        
+
+        self.return_type = old_return_type;
         self.context.pop_scope();
     }
-    
+
+    /// Check a function body without reporting anything, to see what its returns give:
+    /// their common type, nullable when some return null, and `any` when they disagree.
+    /// Returns waiting on a pending function are left out; Unknown when only those remain.
+    fn infer_return_type(&mut self, parameters: &[Parameter], type_params: &[String], body: &BlockStatement) -> Type {
+        let diagnostics = std::mem::take(&mut self.diagnostics);
+        let old_returned = self.returned.replace(Vec::new());
+        self.check_function_body(parameters, &None, type_params, body);
+        let returned = std::mem::replace(&mut self.returned, old_returned).unwrap_or_default();
+        self.diagnostics = diagnostics;
+
+        let mut value_type: Option<Type> = None;
+        let mut nullable = false;
+        let mut waiting = false;
+        for return_type in returned {
+            let return_type = match return_type {
+                Type::Unknown => {
+                    waiting = true;
+                    continue;
+                }
+                Type::Null => {
+                    nullable = true;
+                    continue;
+                }
+                Type::Nullable(inner) => {
+                    nullable = true;
+                    *inner
+                }
+                Type::Any => return Type::Any,
+                other => other,
+            };
+            match value_type {
+                Some(ref previous) if *previous != return_type => return Type::Any,
+                _ => value_type = Some(return_type),
+            }
+        }
+        match value_type {
+            Some(value_type) if nullable => Type::Nullable(Box::new(value_type)),
+            Some(value_type) => value_type,
+            None if waiting => Type::Unknown,
+            None => Type::Any,
+        }
+    }
+
+    fn check_return_statement(&mut self, ret_stmt: &ReturnStatement) {
+        let span = self.locator.find("return");
+        let Some(ref value) = ret_stmt.value else {
+            if let Some(ref mut returned) = self.returned {
+                returned.push(Type::Null);
+            }
+            return;
+        };
+        let called_pending = std::mem::take(&mut self.called_pending);
+        let value_type = self.check_expression(value);
+        let waiting = std::mem::replace(&mut self.called_pending, called_pending)
+            && matches!(value_type, Type::Any | Type::Unknown);
+        if let Some(ref mut returned) = self.returned {
+            // Unknown marks a return that waits on a pending function; other unknowns are any
+            returned.push(match value_type {
+                Type::Any | Type::Unknown if waiting => Type::Unknown,
+                Type::Unknown => Type::Any,
+                ref other => other.clone(),
+            });
+        }
+        if let Some(declared) = self.return_type.clone()
+            && !self.context.is_assignable(&value_type, &declared)
+        {
+            let diagnostic = helpers::invalid_return_type(declared.to_string(), value_type.to_string(), span)
+                .with_note(format!("The function is declared to return `{}`", declared));
+            let diagnostic = self.with_origins(diagnostic, &[value]);
+            self.report(diagnostic);
+        }
+    }
+
+    fn check_match(&mut self, subject: &Expression, arms: &[MatchArm]) -> Type {
+        self.check_expression(subject);
+        let mut result: Option<Type> = None;
+        for arm in arms {
+            self.context.push_scope();
+            self.bind_pattern(&arm.pattern);
+            if let Some(ref guard) = arm.guard {
+                self.check_expression(guard);
+            }
+            let arm_type = match arm.body {
+                MatchArmBody::Expression(ref expr) => self.check_expression(expr),
+                MatchArmBody::Block(ref block) => {
+                    self.check_block(block);
+                    Type::Unknown
+                }
+            };
+            self.context.pop_scope();
+            result = match result {
+                None => Some(arm_type),
+                Some(previous) if previous == arm_type => Some(previous),
+                Some(_) => Some(Type::Unknown),
+            };
+        }
+        result.unwrap_or(Type::Unknown)
+    }
+
+    /// Declare the names a pattern binds; their types are not tracked
+    fn bind_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(ident) => self.context.declare_variable(ident.name.clone(), Type::Unknown),
+            Pattern::Variant { payload, .. } => payload.iter().for_each(|p| self.bind_pattern(p)),
+            Pattern::Struct { fields, .. } => fields.iter().for_each(|f| self.bind_pattern(&f.pattern)),
+            Pattern::Array { elements, .. } | Pattern::Tuple(elements) | Pattern::Or(elements) => {
+                elements.iter().for_each(|p| self.bind_pattern(p))
+            }
+            Pattern::Literal(_) | Pattern::Wildcard | Pattern::Range { .. } => {}
+        }
+    }
+
     fn check_expression(&mut self, expr: &Expression) -> Type {
         match expr {
+            Expression::IntegerLiteral(lit) if lit.is_big() => Type::BigInt,
             Expression::IntegerLiteral(_) => Type::Int,
             Expression::FloatLiteral(_) => Type::Float,
+            Expression::DecimalLiteral(_) => Type::Decimal,
             Expression::StringLiteral(_) => Type::String,
             Expression::CharacterLiteral(_) => Type::Char,
             Expression::BooleanLiteral(_) => Type::Bool,
             Expression::NullLiteral(_) => Type::Null,
-            
-            Expression::Identifier(ident) => {
-                if let Some(var_type) = self.context.get_variable_type(&ident.name) {
-                    var_type.clone()
-                } else {
-                    self.errors.push(format!("Undefined variable: '{}'", ident.name));
-                    Type::Unknown
+
+            Expression::InterpolatedString(interp_str) => {
+                for part in &interp_str.parts {
+                    if let InterpolationPart::Expression(expr) = part {
+                        self.check_expression(expr);
+                    }
                 }
+                Type::String
             }
-            
+
+            // Undefined names are reported by the semantic analyzer
+            Expression::Identifier(ident) => self.context.get_variable_type(&ident.name).cloned().unwrap_or(Type::Unknown),
+            Expression::SelfExpression(_) => self.self_type.clone().unwrap_or(Type::Unknown),
+
             Expression::BinaryExpression(bin_expr) => {
                 let left_type = self.check_expression(&bin_expr.left);
-                let right_type = self.check_expression(&bin_expr.right);
-                
-                self.check_binary_operation(&bin_expr.operator, &left_type, &right_type)
+                // The right side of `x != null && ...` only runs when the left side held
+                let right_type = match bin_expr.operator {
+                    BinaryOperator::And | BinaryOperator::Or => {
+                        let narrowings = self.narrowings(&bin_expr.left, bin_expr.operator == BinaryOperator::And);
+                        self.context.push_scope();
+                        for (name, narrowed) in narrowings {
+                            self.context.narrow_variable(&name, narrowed);
+                        }
+                        let right_type = self.check_expression(&bin_expr.right);
+                        self.context.pop_scope();
+                        right_type
+                    }
+                    _ => self.check_expression(&bin_expr.right),
+                };
+
+                let operands = [bin_expr.left.as_ref(), bin_expr.right.as_ref()];
+                self.check_binary_operation(&bin_expr.operator, bin_expr.span.as_ref(), &left_type, &right_type, operands)
             }
-            
+
+            Expression::UnaryExpression(unary_expr) => {
+                let operand_type = self.check_expression(&unary_expr.operand);
+                if let Some(result) = Type::unary_result(&unary_expr.operator, &operand_type) {
+                    return result;
+                }
+                let symbol = unary_operator_symbol(&unary_expr.operator);
+                let span = self.locator.at(unary_expr.span.as_ref()).unwrap_or_else(|| self.locator.find_operator(symbol));
+                let diagnostic = helpers::type_error(format!("cannot apply unary `{}` to `{}`", symbol, operand_type), span);
+                let diagnostic = self.with_origins(diagnostic, &[unary_expr.operand.as_ref()]);
+                self.report(diagnostic);
+                Type::Unknown
+            }
+
             Expression::CallExpression(call_expr) => {
                 self.check_call_expression(call_expr)
             }
-            
+
+            Expression::MethodCallExpression(method_call) => {
+                self.check_method_call(method_call)
+            }
+
+            Expression::MemberExpression(member_expr) => {
+                // Unit enum variant: Color.Red
+                if let Expression::Identifier(ident) = member_expr.object.as_ref()
+                    && self.context.get_variable_type(&ident.name).is_none()
+                    && self.context.named_types.contains(&ident.name)
+                {
+                    return Type::Custom(ident.name.clone());
+                }
+                let object_type = self.check_expression(&member_expr.object);
+                self.context.field_type(&object_type, &member_expr.property.name).unwrap_or(Type::Unknown)
+            }
+
             Expression::AssignmentExpression(assign_expr) => {
                 self.check_assignment_expression(assign_expr)
             }
-            
-            _ => Type::Unknown,
-        }
-    }
-    
-    fn check_binary_operation(&mut self, op: &BinaryOperator, left: &Type, right: &Type) -> Type {
-        match op {
-            BinaryOperator::Add => {
-                match (left, right) {
-                    // String concatenation
-                    (Type::String, _) | (_, Type::String) => Type::String,
-                    // Numeric operations
-                    (Type::Int, Type::Int) => Type::Int,
-                    (Type::Float, Type::Float) => Type::Float,
-                    (Type::Int, Type::Float) | (Type::Float, Type::Int) => Type::Float,
-                    // Flexible types
-                    (Type::Any, _) | (_, Type::Any) => Type::Any,
-                    _ => {
-                        self.errors.push(format!("Cannot add {} and {}", left, right));
-                        Type::Unknown
-                    }
+
+            Expression::IndexExpression(index_expr) => {
+                let object_type = self.check_expression(&index_expr.object);
+                self.check_expression(&index_expr.index);
+                match object_type {
+                    Type::Array(element) => *element,
+                    Type::Map(_, value) => *value,
+                    _ => Type::Unknown,
                 }
             }
-            
-            BinaryOperator::Subtract | BinaryOperator::Multiply | 
-            BinaryOperator::Divide | BinaryOperator::Modulo | BinaryOperator::Power => {
-                match (left, right) {
-                    (Type::Int, Type::Int) => Type::Int,
-                    (Type::Float, Type::Float) => Type::Float,
-                    (Type::Int, Type::Float) | (Type::Float, Type::Int) => Type::Float,
-                    (Type::Any, _) | (_, Type::Any) => Type::Any,
-                    _ => {
-                        self.errors.push(format!("Cannot perform arithmetic on {} and {}", left, right));
-                        Type::Unknown
+
+            Expression::SliceExpression(slice_expr) => {
+                let object_type = self.check_expression(&slice_expr.object);
+                for bound in [&slice_expr.start, &slice_expr.end].into_iter().flatten() {
+                    self.check_expression(bound);
+                }
+                // A slice has the type of what it was taken from
+                match object_type {
+                    Type::Array(_) | Type::String => object_type,
+                    _ => Type::Unknown,
+                }
+            }
+
+            Expression::ArrayLiteral(array_lit) => {
+                let element_types: Vec<Type> = array_lit.elements.iter()
+                    .map(|element| match element {
+                        Expression::SpreadExpression(_) => {
+                            self.check_expression(element);
+                            Type::Unknown
+                        }
+                        _ => self.check_expression(element),
+                    })
+                    .collect();
+                Type::Array(Box::new(common_type(&element_types)))
+            }
+
+            Expression::MapLiteral(map_lit) => {
+                let (key_types, value_types): (Vec<Type>, Vec<Type>) = map_lit.pairs.iter()
+                    .map(|pair| (self.check_expression(&pair.key), self.check_expression(&pair.value)))
+                    .unzip();
+                Type::Map(Box::new(common_type(&key_types)), Box::new(common_type(&value_types)))
+            }
+
+            Expression::TupleLiteral(tuple_lit) => Type::Tuple(
+                tuple_lit.elements.iter().map(|element| self.check_expression(element)).collect(),
+            ),
+
+            Expression::TupleIndexExpression(tuple_index) => match self.check_expression(&tuple_index.object) {
+                Type::Tuple(elements) => elements.get(tuple_index.index).cloned().unwrap_or(Type::Unknown),
+                _ => Type::Unknown,
+            },
+
+            Expression::StructInstantiation(struct_inst) => {
+                self.check_struct_instantiation(struct_inst)
+            }
+
+            Expression::QualifiedStructInstantiation(qualified) => {
+                for field in &qualified.fields {
+                    self.check_expression(&field.value);
+                }
+                Type::Unknown
+            }
+
+            Expression::RangeExpression(range) => {
+                self.check_expression(&range.start);
+                self.check_expression(&range.end);
+                Type::Unknown
+            }
+
+            Expression::ModuleCallExpression(module_call) => {
+                for arg in &module_call.arguments {
+                    self.check_expression(arg);
+                }
+                Type::Unknown
+            }
+
+            Expression::GroupingExpression(grouping) => self.check_expression(&grouping.expression),
+
+            Expression::LambdaExpression(lambda) => {
+                self.context.push_scope();
+                for param in &lambda.parameters {
+                    let param_type = param.type_annotation.as_ref()
+                        .map(|t| self.annotation_type(t, &[]))
+                        .unwrap_or(Type::Any);
+                    self.context.declare_variable(param.name.name.clone(), param_type);
+                }
+                // A `return` inside the lambda belongs to the lambda, which declares no type
+                let old_return_type = self.return_type.take();
+                let returned = self.returned.take();
+                match lambda.body {
+                    LambdaBody::Expression(ref body) => {
+                        self.check_expression(body);
                     }
+                    LambdaBody::Block(ref block) => self.check_block(block),
                 }
+                self.return_type = old_return_type;
+                self.returned = returned;
+                self.context.pop_scope();
+                Type::Unknown
             }
-            
-            // Comparison operations always return bool
-            BinaryOperator::Equal | BinaryOperator::NotEqual |
-            BinaryOperator::Less | BinaryOperator::Greater |
-            BinaryOperator::LessEqual | BinaryOperator::GreaterEqual => Type::Bool,
-            
-            // Logical operations
-            BinaryOperator::And | BinaryOperator::Or => {
-                if !matches!(left, Type::Bool | Type::Any) {
-                    self.errors.push(format!("Left operand of logical operation must be bool, got {}", left));
+
+            Expression::MatchExpression(match_expr) => self.check_match(&match_expr.expression, &match_expr.arms),
+
+            Expression::LoopExpression(loop_expr) => {
+                self.check_block(&loop_expr.body);
+                Type::Unknown
+            }
+
+            Expression::PropagateExpression(propagate) => {
+                self.check_expression(&propagate.expression);
+                Type::Unknown
+            }
+
+            Expression::OptionalChainExpression(chain) => {
+                self.check_expression(&chain.object);
+                for arg in chain.arguments.iter().flatten() {
+                    self.check_expression(arg);
                 }
-                if !matches!(right, Type::Bool | Type::Any) {
-                    self.errors.push(format!("Right operand of logical operation must be bool, got {}", right));
+                Type::Unknown
+            }
+
+            Expression::SpreadExpression(spread) => {
+                self.check_expression(&spread.expression);
+                Type::Unknown
+            }
+
+            Expression::NamedArgument(named) => self.check_expression(&named.value),
+        }
+    }
+
+    /// Result type of `left op right`; `operands` are the expressions the types came from
+    /// and `span` is where the parser found the operator
+    fn check_binary_operation(
+        &mut self,
+        op: &BinaryOperator,
+        span: Option<&Span>,
+        left: &Type,
+        right: &Type,
+        operands: [&Expression; 2],
+    ) -> Type {
+        let arithmetic = matches!(
+            op,
+            BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply |
+            BinaryOperator::Divide | BinaryOperator::Modulo | BinaryOperator::Power
+        );
+        // Structs overload operators with add/sub/mul methods
+        if arithmetic
+            && !matches!(right, Type::Any | Type::Unknown)
+            && let Type::Custom(name) | Type::Generic(name, _) = left
+            && self.context.structs.contains_key(name)
+        {
+            return self.operator_result_type(name, op);
+        }
+        if let Some(result) = Type::binary_result(op, left, right) {
+            return result;
+        }
+
+        match (left, right) {
+            (Type::Decimal, Type::Float) | (Type::Float, Type::Decimal) if arithmetic => {
+                let other = if *left == Type::Decimal { right } else { left };
+                let span = self.operator_span(span, op);
+                let diagnostic = helpers::type_mismatch("decimal".to_string(), other.to_string(), span)
+                    .with_help("Convert the value with decimal() so the arithmetic stays exact");
+                let diagnostic = self.with_origins(diagnostic, &operands);
+                self.report(diagnostic);
+            }
+            _ if matches!(op, BinaryOperator::And | BinaryOperator::Or) => {
+                for (operand, expr) in [left, right].into_iter().zip(operands) {
+                    if !matches!(operand, Type::Bool | Type::Any | Type::Unknown) {
+                        let span = self.operator_span(span, op);
+                        let diagnostic = helpers::type_mismatch("bool".to_string(), operand.to_string(), span);
+                        let diagnostic = self.with_origins(diagnostic, &[expr]);
+                        self.report(diagnostic);
+                    }
                 }
-                Type::Bool
+                return Type::Bool;
             }
-            
-            _ => Type::Unknown,
+            _ => self.report_operand_mismatch(op, span, left, right, operands),
+        }
+        Type::Unknown
+    }
+
+    fn report_operand_mismatch(
+        &mut self,
+        op: &BinaryOperator,
+        span: Option<&Span>,
+        left: &Type,
+        right: &Type,
+        operands: [&Expression; 2],
+    ) {
+        let symbol = binary_operator_symbol(op);
+        let span = self.operator_span(span, op);
+        let mut diagnostic = helpers::type_error(
+            format!("cannot apply `{}` to `{}` and `{}`", symbol, left, right),
+            span,
+        );
+        if matches!(left, Type::Nullable(_)) || matches!(right, Type::Nullable(_)) {
+            diagnostic = diagnostic.with_help("Compare the value with null first: inside `if x != null` it is no longer nullable");
         }
+        let diagnostic = self.with_origins(diagnostic, &operands);
+        self.report(diagnostic);
     }
-    
+
+    /// Span of a binary operator, searched for when the parser recorded none
+    fn operator_span(&mut self, span: Option<&Span>, op: &BinaryOperator) -> Span {
+        self.locator.at(span).unwrap_or_else(|| self.locator.find_operator(binary_operator_symbol(op)))
+    }
+
+    /// Result of an overloaded operator: the return type of the struct's operator method
+    fn operator_result_type(&mut self, type_name: &str, op: &BinaryOperator) -> Type {
+        let method = match op {
+            BinaryOperator::Add => "add",
+            BinaryOperator::Subtract => "sub",
+            BinaryOperator::Multiply => "mul",
+            _ => return Type::Unknown,
+        };
+        let signature = self.context.methods.get(type_name).and_then(|methods| methods.get(method)).cloned();
+        match signature {
+            Some(signature) => self.call_result(&signature, &HashMap::new()),
+            None => Type::Unknown,
+        }
+    }
+
     fn check_call_expression(&mut self, call_expr: &CallExpression) -> Type {
-        match call_expr.callee.as_ref() {
-            Expression::Identifier(func_name) => {
-                // Regular function call
-                let func_signature = self.context.get_function_signature(&func_name.name).cloned();
-                
-                if let Some((param_types, return_type)) = func_signature {
-                    // Check argument count (flexible for input function)
-                    if func_name.name != "input" && param_types.len() != call_expr.arguments.len() {
-                        self.errors.push(format!(
-                            "Function '{}' expects {} arguments, got {}",
-                            func_name.name, param_types.len(), call_expr.arguments.len()
-                        ));
-                    }
-                    
-                    // Check argument types
-                    for (i, arg) in call_expr.arguments.iter().enumerate() {
-                        let arg_type = self.check_expression(arg);
-                        if i < param_types.len() {
-                            let expected_type = &param_types[i];
-                            if !arg_type.can_assign_to(expected_type) {
-                                self.errors.push(format!(
-                                    "Argument {} of function '{}' expects type '{}', got '{}'",
-                                    i + 1, func_name.name, expected_type, arg_type
-                                ));
-                            }
-                        }
+        let Expression::Identifier(func_name) = call_expr.callee.as_ref() else {
+            // Other complex callee expressions
+            self.check_expression(&call_expr.callee);
+            for arg in &call_expr.arguments {
+                self.check_expression(arg);
+            }
+            return Type::Unknown;
+        };
+
+        // Variables holding function values (lambdas, callbacks) shadow functions
+        let is_variable = self.context.get_variable_type(&func_name.name).is_some();
+        let signature = match is_variable {
+            true => None,
+            false => self.context.get_function_signature(&func_name.name).cloned(),
+        };
+        let Some(signature) = signature else {
+            let arg_types: Vec<Type> = call_expr.arguments.iter().map(|arg| self.check_expression(arg)).collect();
+            if is_variable {
+                return Type::Unknown;
+            }
+            return option_builtin_type(&func_name.name, &arg_types);
+        };
+
+        let name = &func_name.name;
+        let mut bindings = HashMap::new();
+        self.check_arguments(func_name, name, &signature, &call_expr.arguments, &mut bindings);
+        self.call_result(&signature, &bindings)
+    }
+
+    fn check_method_call(&mut self, method_call: &MethodCallExpression) -> Type {
+        let method_name = &method_call.method.name;
+
+        // Type.method(...) on a struct or enum name rather than a value
+        let static_type = match method_call.object.as_ref() {
+            Expression::Identifier(ident) if self.context.get_variable_type(&ident.name).is_none() => {
+                if self.context.named_types.contains(&ident.name) {
+                    // Enum variant with a payload: Shape.Circle(5)
+                    for arg in &method_call.arguments {
+                        self.check_expression(arg);
                     }
-                    
-                    return_type
-                } else {
-                    self.errors.push(format!("Undefined function: '{}'", func_name.name));
-                    Type::Unknown
+                    return Type::Custom(ident.name.clone());
                 }
+                self.context.structs.contains_key(&ident.name).then(|| Type::Custom(ident.name.clone()))
             }
-            Expression::MemberExpression(member_expr) => {
-                // Method call like input().toint()
-                let _object_type = self.check_expression(&member_expr.object);
-                let method_name = &member_expr.property.name;
-                
-                // Check if it's a known method and return its type
-                match method_name.as_str() {
-                    "toint" => Type::Int,
-                    "tofloat" => Type::Float,
-                    "tostr" => Type::String,
-                    "tobool" => Type::Bool,
-                    _ => {
-                        self.errors.push(format!("Unknown method: '{}'", method_name));
-                        Type::Unknown
-                    }
+            _ => None,
+        };
+        let object_type = match static_type {
+            Some(ty) => ty,
+            None => self.check_expression(&method_call.object),
+        };
+
+        let signature = match object_type {
+            Type::Custom(ref type_name) | Type::Generic(ref type_name, _) => self.context.methods
+                .get(type_name)
+                .and_then(|methods| methods.get(method_name))
+                .cloned(),
+            _ => None,
+        };
+        if let Some(signature) = signature {
+            let mut bindings = HashMap::new();
+            let pattern = format!(".{}", method_name);
+            self.check_arguments(&method_call.method, &pattern, &signature, &method_call.arguments, &mut bindings);
+            return self.call_result(&signature, &bindings);
+        }
+
+        for arg in &method_call.arguments {
+            self.check_expression(arg);
+        }
+        // Conversion methods available on every value
+        match method_name.as_str() {
+            "toint" => Type::Int,
+            "tobigint" => Type::BigInt,
+            "tofloat" => Type::Float,
+            "tostr" => Type::String,
+            "tobool" => Type::Bool,
+            _ => Type::Unknown,
+        }
+    }
+
+    /// Check each argument against the parameter it binds to, recording what the type
+    /// parameters of a generic callee stand for. `pattern` locates the call in the source
+    /// when the parser recorded no span for the callee's name.
+    fn check_arguments(
+        &mut self,
+        callee: &Identifier,
+        pattern: &str,
+        signature: &Signature,
+        arguments: &[Expression],
+        bindings: &mut HashMap<String, Type>,
+    ) {
+        let span = self.locator.at(callee.span.as_ref())
+            .unwrap_or_else(|| self.locator.find_where(pattern, |_, after| after.trim_start().starts_with('(')));
+        let callee = &callee.name;
+        let mut position = 0;
+        let mut spread_seen = false;
+
+        for arg in arguments {
+            let param_index = match arg {
+                Expression::NamedArgument(named) => {
+                    signature.params.iter().position(|(name, _)| *name == named.name.name)
+                }
+                Expression::SpreadExpression(_) => {
+                    spread_seen = true;
+                    None
+                }
+                _ if spread_seen => None,
+                _ => {
+                    position += 1;
+                    Some(position - 1)
                 }
+            };
+
+            let arg_type = self.check_expression(arg);
+            let Some((param_name, param_type)) = param_index.and_then(|i| signature.params.get(i)) else {
+                continue;
+            };
+            if self.context.implements_trait(&arg_type, param_type) {
+                continue;
             }
-            _ => {
-                // Other complex callee expressions
-                self.check_expression(&call_expr.callee);
-                Type::Unknown
+            if let Err((expected, found)) = param_type.unify(&arg_type, bindings) {
+                let diagnostic = helpers::type_mismatch(expected.to_string(), found.to_string(), span.clone())
+                    .with_note(format!("Parameter `{}` of `{}` is declared as `{}`", param_name, callee, param_type));
+                let value = match arg {
+                    Expression::NamedArgument(named) => &named.value,
+                    _ => arg,
+                };
+                let diagnostic = self.with_origins(diagnostic, &[value]);
+                self.report(diagnostic);
+            }
+        }
+    }
+
+    fn check_struct_instantiation(&mut self, struct_inst: &StructInstantiation) -> Type {
+        let struct_name = &struct_inst.name.name;
+        let Some(struct_type) = self.context.structs.get(struct_name).cloned() else {
+            for field in &struct_inst.fields {
+                self.check_expression(&field.value);
+            }
+            return Type::Unknown;
+        };
+
+        // Generic struct: the field values decide the type arguments
+        let mut bindings = HashMap::new();
+        for field in &struct_inst.fields {
+            let value_type = self.check_expression(&field.value);
+            // Unknown fields are reported by the semantic analyzer
+            let Some(field_type) = struct_type.fields.get(&field.name.name) else {
+                continue;
+            };
+            if self.context.implements_trait(&value_type, field_type) {
+                continue;
+            }
+            if let Err((expected, found)) = field_type.unify(&value_type, &mut bindings) {
+                let span = self.locator.at(field.name.span.as_ref())
+                    .unwrap_or_else(|| self.locator.find_where(&field.name.name, |_, after| after.trim_start().starts_with(':')));
+                let diagnostic = helpers::type_mismatch(expected.to_string(), found.to_string(), span)
+                    .with_note(format!("Field `{}` of `{}` is declared as `{}`", field.name.name, struct_name, field_type));
+                let diagnostic = self.with_origins(diagnostic, &[&field.value]);
+                self.report(diagnostic);
             }
         }
+
+        if struct_type.type_params.is_empty() {
+            Type::Custom(struct_name.clone())
+        } else {
+            let type_args = struct_type.type_params.iter()
+                .map(|param| bindings.get(param).cloned().unwrap_or(Type::Unknown))
+                .collect();
+            Type::Generic(struct_name.clone(), type_args)
+        }
     }
-    
+
     fn check_assignment_expression(&mut self, assign_expr: &AssignmentExpression) -> Type {
+        let compound = compound_operator(&assign_expr.operator);
+        let op_text = compound.as_ref().map_or("=", |(_, text)| *text);
+
+        // The declared type of the target, and where to point when the value does not fit
+        let (target_type, span) = match assign_expr.left.as_ref() {
+            Expression::Identifier(ident) => {
+                let span = self.locator.at(ident.span.as_ref()).unwrap_or_else(|| {
+                    self.locator.find_where(&ident.name, |before, after| {
+                        !is_declaration(before) && is_assignment_operator(after, op_text)
+                    })
+                });
+                (self.context.get_declared_type(&ident.name).cloned(), span)
+            }
+            Expression::MemberExpression(member_expr) if !member_expr.computed => {
+                let object_type = self.check_expression(&member_expr.object);
+                let span = self.locator.at(member_expr.property.span.as_ref()).unwrap_or_else(|| {
+                    self.locator.find_where(&format!(".{}", member_expr.property.name), |_, after| {
+                        is_assignment_operator(after, op_text)
+                    })
+                });
+                (self.context.field_type(&object_type, &member_expr.property.name), span)
+            }
+            target => {
+                self.check_expression(target);
+                (None, self.locator.find(op_text))
+            }
+        };
+        let value_type = self.check_expression(&assign_expr.right);
+
+        let Some(target_type) = target_type else {
+            return value_type;
+        };
+        let operands = [assign_expr.left.as_ref(), assign_expr.right.as_ref()];
+        let result_type = match compound {
+            Some((op, _)) => {
+                // The current value takes part, which a condition may have narrowed
+                let current_type = match assign_expr.left.as_ref() {
+                    Expression::Identifier(ident) => self.context.get_variable_type(&ident.name).cloned(),
+                    _ => None,
+                };
+                let current_type = current_type.unwrap_or_else(|| target_type.clone());
+                self.check_binary_operation(&op, None, &current_type, &value_type, operands)
+            }
+            None => value_type,
+        };
+        if !self.context.is_assignable(&result_type, &target_type) {
+            let diagnostic = helpers::type_mismatch(target_type.to_string(), result_type.to_string(), span);
+            let diagnostic = self.with_origins(diagnostic, &operands);
+            self.report(diagnostic);
+        }
         if let Expression::Identifier(ident) = assign_expr.left.as_ref() {
-            let value_type = self.check_expression(&assign_expr.right);
-            
-            if let Some(var_type) = self.context.get_variable_type(&ident.name) {
-                // For flexible variables (Type::Any), update the type
-                if matches!(var_type, Type::Any) {
-                    self.context.update_variable_type(&ident.name, value_type.clone());
-                } else if !value_type.can_assign_to(var_type) {
-                    self.errors.push(format!(
-                        "Cannot assign value of type '{}' to variable '{}' of type '{}'",
-                        value_type, ident.name, var_type
-                    ));
-                }
-            } else {
-                self.errors.push(format!("Undefined variable: '{}'", ident.name));
-            }
-            
-            value_type
-        } else {
-            self.check_expression(&assign_expr.left);
-            self.check_expression(&assign_expr.right)
+            self.context.clear_narrowing(&ident.name);
         }
+        result_type
     }
 }
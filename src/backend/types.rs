@@ -2,6 +2,7 @@
 //! Core type system for the Razen programming language
 
 use crate::frontend::parser::ast::*;
+use std::collections::HashMap;
 use std::fmt;
 
 /// Represents all possible types in the Razen language
@@ -9,7 +10,9 @@ use std::fmt;
 pub enum Type {
     // Primitive types
     Int,
+    BigInt,
     Float,
+    Decimal,
     String,
     Bool,
     Char,
@@ -21,8 +24,14 @@ pub enum Type {
     
     // Composite types
     Array(Box<Type>),
+    Map(Box<Type>, Box<Type>),      // (key, value)
+    Option(Box<Type>),              // Some(value) or None
+    Nullable(Box<Type>),            // A value or null, inferred from what a function returns
+    Tuple(Vec<Type>),
     Function(Vec<Type>, Box<Type>), // (parameters, return_type)
     Custom(String),
+    Generic(String, Vec<Type>), // Instantiated generic struct: Pair<int, str>
+    TypeParam(String),          // Type parameter of a generic declaration: T
 }
 
 impl Type {
@@ -31,6 +40,8 @@ impl Type {
         match (self, target) {
             // Exact matches
             (Type::Int, Type::Int) |
+            (Type::BigInt, Type::BigInt) |
+            (Type::Decimal, Type::Decimal) |
             (Type::Float, Type::Float) |
             (Type::String, Type::String) |
             (Type::Bool, Type::Bool) |
@@ -45,6 +56,36 @@ impl Type {
             
             // Numeric coercions
             (Type::Int, Type::Float) | (Type::Float, Type::Int) => true,
+            // Ints widen to bigints and decimals, never the other way round
+            (Type::Int, Type::BigInt) | (Type::Int, Type::Decimal) => true,
+            
+            (Type::Array(from), Type::Array(to)) => from.can_assign_to(to),
+            (Type::Map(from_key, from_value), Type::Map(to_key, to_value)) => {
+                from_key.can_assign_to(to_key) && from_value.can_assign_to(to_value)
+            }
+            (Type::Option(from), Type::Option(to)) => from.can_assign_to(to),
+            
+            // A nullable slot takes null or a value, but a nullable value only fits another
+            // nullable slot until it is checked against null
+            (Type::Null, Type::Nullable(_)) => true,
+            (Type::Nullable(from), Type::Nullable(to)) => from.can_assign_to(to),
+            (_, Type::Nullable(to)) => self.can_assign_to(to),
+            (Type::Tuple(from), Type::Tuple(to)) => {
+                from.len() == to.len() && from.iter().zip(to).all(|(a, b)| a.can_assign_to(b))
+            }
+            (Type::Custom(from), Type::Custom(to)) => from == to,
+            
+            // A bare struct name accepts any instantiation of it
+            (Type::Generic(from, _), Type::Custom(to)) |
+            (Type::Custom(from), Type::Generic(to, _)) => from == to,
+            (Type::Generic(from, from_args), Type::Generic(to, to_args)) => {
+                from == to
+                    && from_args.len() == to_args.len()
+                    && from_args.iter().zip(to_args).all(|(a, b)| a.can_assign_to(b))
+            }
+            
+            // Type parameters are only checked once they are instantiated
+            (Type::TypeParam(_), _) | (_, Type::TypeParam(_)) => true,
             
             // Everything else is incompatible
             _ => false,
@@ -59,24 +100,232 @@ impl Type {
     
     /// Convert TypeAnnotation to Type
     pub fn from_annotation(annotation: &TypeAnnotation) -> Type {
+        Type::from_annotation_with_params(annotation, &[])
+    }
+    
+    /// Convert TypeAnnotation to Type, treating the given names as type parameters
+    pub fn from_annotation_with_params(annotation: &TypeAnnotation, type_params: &[String]) -> Type {
         match annotation {
             TypeAnnotation::Int => Type::Int,
+            TypeAnnotation::BigInt => Type::BigInt,
             TypeAnnotation::Float => Type::Float,
+            TypeAnnotation::Decimal => Type::Decimal,
             TypeAnnotation::String => Type::String,
             TypeAnnotation::Bool => Type::Bool,
             TypeAnnotation::Char => Type::Char,
             TypeAnnotation::Any => Type::Any,
-            TypeAnnotation::Array(inner) => Type::Array(Box::new(Type::from_annotation(inner))),
+            TypeAnnotation::Array(inner) => {
+                Type::Array(Box::new(Type::from_annotation_with_params(inner, type_params)))
+            }
+            TypeAnnotation::Map(key, value) => Type::Map(
+                Box::new(Type::from_annotation_with_params(key, type_params)),
+                Box::new(Type::from_annotation_with_params(value, type_params)),
+            ),
+            TypeAnnotation::Tuple(elements) => Type::Tuple(
+                elements.iter().map(|element| Type::from_annotation_with_params(element, type_params)).collect(),
+            ),
+            TypeAnnotation::Custom(ident) if type_params.contains(&ident.name) => {
+                Type::TypeParam(ident.name.clone())
+            }
             TypeAnnotation::Custom(ident) => Type::Custom(ident.name.clone()),
-            _ => Type::Any,
+            TypeAnnotation::Generic(ident, args) if ident.name == "Option" && args.len() == 1 => {
+                Type::Option(Box::new(Type::from_annotation_with_params(&args[0], type_params)))
+            }
+            TypeAnnotation::Generic(ident, args) => Type::Generic(
+                ident.name.clone(),
+                args.iter().map(|arg| Type::from_annotation_with_params(arg, type_params)).collect(),
+            ),
+        }
+    }
+    
+    /// Result of a built-in binary operator on operands of these types, or `None` when
+    /// they do not fit. An operand known only at runtime gives an unknown result; operators
+    /// that structs overload with methods are resolved by the caller.
+    pub fn binary_result(op: &BinaryOperator, left: &Type, right: &Type) -> Option<Type> {
+        let flexible = |ty: &Type| matches!(ty, Type::Any | Type::Unknown);
+        match op {
+            BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply |
+            BinaryOperator::Divide | BinaryOperator::Modulo | BinaryOperator::Power => match (left, right) {
+                _ if flexible(left) || flexible(right) => Some(Type::Unknown),
+                // String concatenation
+                (Type::String, _) | (_, Type::String) => Some(Type::String),
+                // Array concatenation
+                (Type::Array(left_element), Type::Array(right_element)) if *op == BinaryOperator::Add => {
+                    if left_element == right_element {
+                        Some(left.clone())
+                    } else {
+                        Some(Type::Array(Box::new(Type::Unknown)))
+                    }
+                }
+                (Type::Int, Type::Int) => Some(Type::Int),
+                // Ints are promoted to bigint
+                (Type::BigInt, Type::Int | Type::BigInt) | (Type::Int, Type::BigInt) => Some(Type::BigInt),
+                // Decimals mix exactly with integers but never with floats
+                (Type::Decimal, Type::Int | Type::BigInt | Type::Decimal)
+                | (Type::Int | Type::BigInt, Type::Decimal) => Some(Type::Decimal),
+                (Type::Float, Type::Int | Type::BigInt | Type::Float)
+                | (Type::Int | Type::BigInt, Type::Float) => Some(Type::Float),
+                _ => None,
+            },
+
+            BinaryOperator::Equal | BinaryOperator::NotEqual |
+            BinaryOperator::Less | BinaryOperator::Greater |
+            BinaryOperator::LessEqual | BinaryOperator::GreaterEqual => Some(Type::Bool),
+
+            BinaryOperator::And | BinaryOperator::Or => {
+                [left, right].iter().all(|ty| **ty == Type::Bool || flexible(ty)).then_some(Type::Bool)
+            }
+
+            // Bitwise operations work on integers; a shift keeps the type of the value shifted
+            BinaryOperator::BitwiseAnd | BinaryOperator::BitwiseOr | BinaryOperator::BitwiseXor |
+            BinaryOperator::LeftShift | BinaryOperator::RightShift => {
+                let is_integer = |ty: &Type| matches!(ty, Type::Int | Type::BigInt) || flexible(ty);
+                if !is_integer(left) || !is_integer(right) {
+                    return None;
+                }
+                let is_shift = matches!(op, BinaryOperator::LeftShift | BinaryOperator::RightShift);
+                Some(match (left, right) {
+                    (Type::BigInt, _) => Type::BigInt,
+                    (_, Type::BigInt) if !is_shift => Type::BigInt,
+                    (Type::Int, Type::Int) | (Type::Int, Type::BigInt) => Type::Int,
+                    _ => Type::Unknown,
+                })
+            }
+
+            // The fallback decides the type when the left side may be null
+            BinaryOperator::NullCoalesce => Some(match (left, right) {
+                (Type::Null, _) => right.clone(),
+                // `??` also unwraps a Some
+                (Type::Nullable(inner) | Type::Option(inner), _) if **inner == *right => right.clone(),
+                _ if left == right => left.clone(),
+                _ => Type::Unknown,
+            }),
+
+            BinaryOperator::Range => Some(Type::Unknown),
+        }
+    }
+
+    /// Result of a unary operator on an operand of this type, or `None` when it does not fit:
+    /// `!` takes a bool, `-` and `+` a number, and `~`, `++` and `--` an integer
+    pub fn unary_result(op: &UnaryOperator, operand: &Type) -> Option<Type> {
+        let fits = matches!(operand, Type::Any | Type::Unknown) || match op {
+            UnaryOperator::Not => *operand == Type::Bool,
+            UnaryOperator::Minus | UnaryOperator::Plus => {
+                matches!(operand, Type::Int | Type::BigInt | Type::Float | Type::Decimal)
+            }
+            UnaryOperator::BitwiseNot
+            | UnaryOperator::PreIncrement | UnaryOperator::PostIncrement
+            | UnaryOperator::PreDecrement | UnaryOperator::PostDecrement => {
+                matches!(operand, Type::Int | Type::BigInt)
+            }
+        };
+        match op {
+            UnaryOperator::Not => fits.then_some(Type::Bool),
+            _ => fits.then(|| operand.clone()),
+        }
+    }
+    
+    /// Whether this type still mentions an uninstantiated type parameter
+    pub fn has_type_params(&self) -> bool {
+        match self {
+            Type::TypeParam(_) => true,
+            Type::Array(inner) | Type::Option(inner) | Type::Nullable(inner) => inner.has_type_params(),
+            Type::Map(key, value) => key.has_type_params() || value.has_type_params(),
+            Type::Tuple(elements) => elements.iter().any(Type::has_type_params),
+            Type::Generic(_, args) => args.iter().any(Type::has_type_params),
+            Type::Function(params, ret) => {
+                params.iter().any(Type::has_type_params) || ret.has_type_params()
+            }
+            _ => false,
+        }
+    }
+    
+    /// Replace type parameters with their bound types; unbound parameters become Unknown
+    pub fn substitute(&self, bindings: &HashMap<String, Type>) -> Type {
+        match self {
+            Type::TypeParam(name) => bindings.get(name).cloned().unwrap_or(Type::Unknown),
+            Type::Array(inner) => Type::Array(Box::new(inner.substitute(bindings))),
+            Type::Option(inner) => Type::Option(Box::new(inner.substitute(bindings))),
+            Type::Nullable(inner) => Type::Nullable(Box::new(inner.substitute(bindings))),
+            Type::Map(key, value) => {
+                Type::Map(Box::new(key.substitute(bindings)), Box::new(value.substitute(bindings)))
+            }
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|e| e.substitute(bindings)).collect()),
+            Type::Generic(name, args) => {
+                Type::Generic(name.clone(), args.iter().map(|arg| arg.substitute(bindings)).collect())
+            }
+            Type::Function(params, ret) => Type::Function(
+                params.iter().map(|param| param.substitute(bindings)).collect(),
+                Box::new(ret.substitute(bindings)),
+            ),
+            other => other.clone(),
+        }
+    }
+    
+    /// Match a declared parameter type against the type of an argument, recording what each
+    /// type parameter stands for. On conflict returns the (expected, found) pair to report.
+    pub fn unify(&self, actual: &Type, bindings: &mut HashMap<String, Type>) -> Result<(), (Type, Type)> {
+        match (self, actual) {
+            // Nothing is learned from arguments whose type is not known statically
+            (_, Type::Unknown) | (_, Type::Any) => Ok(()),
+            (Type::TypeParam(name), _) => match bindings.get(name) {
+                Some(bound) if *bound == Type::Unknown => {
+                    bindings.insert(name.clone(), actual.clone());
+                    Ok(())
+                }
+                Some(bound) => {
+                    if actual.can_assign_to(bound) {
+                        Ok(())
+                    } else {
+                        Err((bound.clone(), actual.clone()))
+                    }
+                }
+                None => {
+                    bindings.insert(name.clone(), actual.clone());
+                    Ok(())
+                }
+            },
+            (Type::Array(expected), Type::Array(found))
+            | (Type::Option(expected), Type::Option(found))
+            | (Type::Nullable(expected), Type::Nullable(found)) => expected.unify(found, bindings),
+            (Type::Map(expected_key, expected_value), Type::Map(found_key, found_value)) => {
+                expected_key.unify(found_key, bindings)?;
+                expected_value.unify(found_value, bindings)
+            }
+            (Type::Tuple(expected_elements), Type::Tuple(found_elements))
+                if expected_elements.len() == found_elements.len() =>
+            {
+                for (expected, found) in expected_elements.iter().zip(found_elements) {
+                    expected.unify(found, bindings)?;
+                }
+                Ok(())
+            }
+            (Type::Generic(expected_name, expected_args), Type::Generic(found_name, found_args))
+                if expected_name == found_name && expected_args.len() == found_args.len() =>
+            {
+                for (expected, found) in expected_args.iter().zip(found_args) {
+                    expected.unify(found, bindings)?;
+                }
+                Ok(())
+            }
+            (expected, found) => {
+                let expected = expected.substitute(bindings);
+                if found.can_assign_to(&expected) {
+                    Ok(())
+                } else {
+                    Err((expected, found.clone()))
+                }
+            }
         }
     }
     
     /// Infer type from a literal expression
     pub fn from_literal(expr: &Expression) -> Type {
         match expr {
+            Expression::IntegerLiteral(lit) if lit.is_big() => Type::BigInt,
             Expression::IntegerLiteral(_) => Type::Int,
             Expression::FloatLiteral(_) => Type::Float,
+            Expression::DecimalLiteral(_) => Type::Decimal,
             Expression::StringLiteral(_) => Type::String,
             Expression::CharacterLiteral(_) => Type::Char,
             Expression::BooleanLiteral(_) => Type::Bool,
@@ -90,7 +339,9 @@ impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Type::Int => write!(f, "int"),
+            Type::BigInt => write!(f, "bigint"),
             Type::Float => write!(f, "float"),
+            Type::Decimal => write!(f, "decimal"),
             Type::String => write!(f, "str"),
             Type::Bool => write!(f, "bool"),
             Type::Char => write!(f, "char"),
@@ -98,6 +349,17 @@ impl fmt::Display for Type {
             Type::Any => write!(f, "any"),
             Type::Unknown => write!(f, "unknown"),
             Type::Array(inner) => write!(f, "[{}]", inner),
+            Type::Map(key, value) => write!(f, "map<{}, {}>", key, value),
+            Type::Option(inner) => write!(f, "Option<{}>", inner),
+            Type::Nullable(inner) => write!(f, "{}?", inner),
+            Type::Tuple(elements) => {
+                write!(f, "(")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 { write!(f, ", ")?; }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, ")")
+            }
             Type::Function(params, ret) => {
                 write!(f, "fun(")?;
                 for (i, param) in params.iter().enumerate() {
@@ -107,6 +369,15 @@ impl fmt::Display for Type {
                 write!(f, ") -> {}", ret)
             }
             Type::Custom(name) => write!(f, "{}", name),
+            Type::Generic(name, args) => {
+                write!(f, "{}<", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 { write!(f, ", ")?; }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ">")
+            }
+            Type::TypeParam(name) => write!(f, "{}", name),
         }
     }
 }
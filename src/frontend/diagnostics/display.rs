@@ -274,12 +274,17 @@ impl DiagnosticRenderer {
         let dim_gray = if self.config.use_colors { "\x1b[90m" } else { "" };
         let reset = if self.config.use_colors { "\x1b[0m" } else { "" };
 
-        // File location header - clean Razen style
+        // File location header - clean Razen style, at the primary label when there is one
+        let location = labels.iter()
+            .find(|l| l.severity == Severity::Error)
+            .or(labels.first())
+            .map(|l| (l.span.start.line, l.span.start.column))
+            .unwrap_or((min_line, 1));
         output.push_str(&format!(
             "  {}:{}:{}\n",
             source_file.name,
-            min_line,
-            labels.first().map(|l| l.span.start.column).unwrap_or(1)
+            location.0,
+            location.1
         ));
 
         output.push_str("\n");
@@ -133,10 +133,19 @@ pub enum DiagnosticKind {
     InvalidReturnType { expected: String, found: String },
     MissingReturn { function_name: String },
     InvalidFunctionCall { reason: String },
+    UnknownNamedArgument { name: String, function: String },
+    DuplicateNamedArgument { name: String },
+    MissingArgument { name: String, function: String },
     
     // Method-related errors
     UndefinedMethod { method: String, type_name: String },
     
+    // Trait-related errors
+    UndefinedTrait { name: String },
+    MissingTraitMethod { trait_name: String, method: String, type_name: String },
+    TraitMethodMismatch { trait_name: String, method: String, reason: String },
+    MethodNotInTrait { method: String, trait_name: String },
+    
     // Variable-related errors
     UninitializedVariable { name: String },
     ImmutableAssignment { name: String },
@@ -146,6 +155,8 @@ pub enum DiagnosticKind {
     // Control flow errors
     BreakOutsideLoop,
     ContinueOutsideLoop,
+    UndeclaredLabel { name: String },
+    BreakValueOutsideLoop,
     InvalidCondition { found: String },
     
     // Module system errors
@@ -165,11 +176,15 @@ pub enum DiagnosticKind {
     
     // Additional helpful errors
     DivisionByZero,
-    IndexOutOfBounds { index: i64, length: usize },
+    IndexOutOfBounds { index: i64, length: usize, container: String },
+    ReversedSlice { start: i64, end: i64 },
     EmptyArray,
     InvalidArrayAccess { reason: String },
     MissingField { field: String, type_name: String },
     ExtraField { field: String, type_name: String },
+    UndefinedVariant { variant: String, enum_name: String },
+    VariantPayloadMismatch { enum_name: String, variant: String, expected: usize, found: usize },
+    CapitalizedBinding { name: String },
     
     // Type system errors
     InvalidTypeAnnotation { type_name: String, reason: String },
@@ -255,12 +270,35 @@ impl DiagnosticKind {
             DiagnosticKind::InvalidFunctionCall { reason } => {
                 format!("invalid function call: {}", reason)
             },
+            DiagnosticKind::UnknownNamedArgument { name, function } => {
+                format!("function `{}` has no parameter named `{}`", function, name)
+            },
+            DiagnosticKind::DuplicateNamedArgument { name } => {
+                format!("argument `{}` is specified more than once", name)
+            },
+            DiagnosticKind::MissingArgument { name, function } => {
+                format!("missing argument `{}` in call to `{}`", name, function)
+            },
             
             // Method-related errors
             DiagnosticKind::UndefinedMethod { method, type_name } => {
                 format!("no method named `{}` found for type `{}`", method, type_name)
             },
             
+            // Trait-related errors
+            DiagnosticKind::UndefinedTrait { name } => {
+                format!("cannot find trait `{}` in this scope", name)
+            },
+            DiagnosticKind::MissingTraitMethod { trait_name, method, type_name } => {
+                format!("not all trait methods implemented: `{}` is missing `{}` from trait `{}`", type_name, method, trait_name)
+            },
+            DiagnosticKind::TraitMethodMismatch { trait_name, method, reason } => {
+                format!("method `{}` has an incompatible signature for trait `{}`: {}", method, trait_name, reason)
+            },
+            DiagnosticKind::MethodNotInTrait { method, trait_name } => {
+                format!("method `{}` is not a member of trait `{}`", method, trait_name)
+            },
+            
             // Variable-related errors
             DiagnosticKind::UninitializedVariable { name } => {
                 format!("use of possibly-uninitialized variable `{}`", name)
@@ -278,6 +316,10 @@ impl DiagnosticKind {
             // Control flow errors
             DiagnosticKind::BreakOutsideLoop => "`break` outside of loop".to_string(),
             DiagnosticKind::ContinueOutsideLoop => "`continue` outside of loop".to_string(),
+            DiagnosticKind::UndeclaredLabel { name } => {
+                format!("use of undeclared loop label `{}`", name)
+            },
+            DiagnosticKind::BreakValueOutsideLoop => "`break` with a value outside of `loop`".to_string(),
             DiagnosticKind::InvalidCondition { found } => {
                 format!("expected boolean condition, found `{}`", found)
             },
@@ -317,8 +359,11 @@ impl DiagnosticKind {
             
             // Additional helpful errors
             DiagnosticKind::DivisionByZero => "attempt to divide by zero".to_string(),
-            DiagnosticKind::IndexOutOfBounds { index, length } => {
-                format!("index {} out of bounds for array of length {}", index, length)
+            DiagnosticKind::IndexOutOfBounds { index, length, container } => {
+                format!("index {} out of bounds for {} of length {}", index, container, length)
+            },
+            DiagnosticKind::ReversedSlice { start, end } => {
+                format!("slice range {}..{} ends before it starts", start, end)
             },
             DiagnosticKind::EmptyArray => "operation on empty array".to_string(),
             DiagnosticKind::InvalidArrayAccess { reason } => {
@@ -327,9 +372,18 @@ impl DiagnosticKind {
             DiagnosticKind::MissingField { field, type_name } => {
                 format!("struct `{}` does not have a field named `{}`", type_name, field)
             },
+            DiagnosticKind::UndefinedVariant { variant, enum_name } => {
+                format!("no variant named `{}` in enum `{}`", variant, enum_name)
+            },
+            DiagnosticKind::VariantPayloadMismatch { enum_name, variant, expected, found } => {
+                format!("variant `{}.{}` takes {} value(s) but {} were given", enum_name, variant, expected, found)
+            },
             DiagnosticKind::ExtraField { field, type_name } => {
                 format!("struct `{}` does not have a field named `{}`", type_name, field)
             },
+            DiagnosticKind::CapitalizedBinding { name } => {
+                format!("pattern `{}` would bind a new variable that matches any value", name)
+            },
             
             // Type system errors
             DiagnosticKind::InvalidTypeAnnotation { type_name, reason } => {
@@ -375,20 +429,33 @@ impl DiagnosticKind {
             | DiagnosticKind::InvalidReturnType { .. }
             | DiagnosticKind::MissingReturn { .. }
             | DiagnosticKind::InvalidFunctionCall { .. }
+            | DiagnosticKind::UnknownNamedArgument { .. }
+            | DiagnosticKind::DuplicateNamedArgument { .. }
+            | DiagnosticKind::MissingArgument { .. }
             | DiagnosticKind::UndefinedMethod { .. }
+            | DiagnosticKind::UndefinedTrait { .. }
+            | DiagnosticKind::MissingTraitMethod { .. }
+            | DiagnosticKind::TraitMethodMismatch { .. }
+            | DiagnosticKind::MethodNotInTrait { .. }
             | DiagnosticKind::UninitializedVariable { .. }
             | DiagnosticKind::ImmutableAssignment { .. }
             | DiagnosticKind::BreakOutsideLoop
             | DiagnosticKind::ContinueOutsideLoop
+            | DiagnosticKind::UndeclaredLabel { .. }
+            | DiagnosticKind::BreakValueOutsideLoop
             | DiagnosticKind::InvalidCondition { .. }
             | DiagnosticKind::ModuleNotFound { .. }
             | DiagnosticKind::CircularImport { .. }
             | DiagnosticKind::InvalidImport { .. }
             | DiagnosticKind::DivisionByZero
             | DiagnosticKind::IndexOutOfBounds { .. }
+            | DiagnosticKind::ReversedSlice { .. }
             | DiagnosticKind::InvalidArrayAccess { .. }
             | DiagnosticKind::MissingField { .. }
             | DiagnosticKind::ExtraField { .. }
+            | DiagnosticKind::UndefinedVariant { .. }
+            | DiagnosticKind::VariantPayloadMismatch { .. }
+            | DiagnosticKind::CapitalizedBinding { .. }
             | DiagnosticKind::InvalidTypeAnnotation { .. }
             | DiagnosticKind::TypeNotFound { .. }
             | DiagnosticKind::InvalidLValue { .. }
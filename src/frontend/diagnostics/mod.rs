@@ -357,8 +357,8 @@ pub mod helpers {
     }
 
     /// Create an index out of bounds error
-    pub fn index_out_of_bounds(index: i64, length: usize, span: Span) -> Diagnostic {
-        Diagnostic::new(DiagnosticKind::IndexOutOfBounds { index, length })
+    pub fn index_out_of_bounds<S: Into<String>>(index: i64, length: usize, container: S, span: Span) -> Diagnostic {
+        Diagnostic::new(DiagnosticKind::IndexOutOfBounds { index, length, container: container.into() })
             .with_label(Label::primary(span))
             .with_code("E0022")
             .with_help(format!("Valid indices are 0 to {}", length.saturating_sub(1)))
@@ -379,6 +379,161 @@ pub mod helpers {
             .with_help(format!("Check the definition of struct `{}` for available fields", type_str))
     }
 
+    /// Create an undefined enum variant error listing the declared variants
+    pub fn undefined_variant<S: Into<String>>(variant: S, enum_name: S, span: Span, variants: &[String]) -> Diagnostic {
+        let enum_str = enum_name.into();
+        let mut diagnostic = Diagnostic::new(DiagnosticKind::UndefinedVariant {
+            variant: variant.into(),
+            enum_name: enum_str.clone(),
+        })
+            .with_label(Label::primary(span))
+            .with_code("E0024");
+
+        if !variants.is_empty() {
+            diagnostic = diagnostic.with_help(format!("Enum `{}` has variants: {}", enum_str, variants.join(", ")));
+        }
+
+        diagnostic
+    }
+
+    /// Create an error for a capitalized name in a pattern, which looks like a variant
+    /// but would bind everything; `variant` is the qualified variant it most likely means
+    pub fn capitalized_binding<S: Into<String>>(name: S, span: Span, variant: Option<String>) -> Diagnostic {
+        let name_str = name.into();
+        let help = match variant {
+            Some(variant) => format!("Write `{}` to match the enum variant", variant),
+            None => format!("Qualify a variant as `Enum.{}`, or use a lowercase name to bind the value", name_str),
+        };
+        Diagnostic::new(DiagnosticKind::CapitalizedBinding { name: name_str })
+            .with_label(Label::primary(span))
+            .with_code("E0037")
+            .with_help(help)
+    }
+
+    /// Create an undefined method error for a built-in type, suggesting the closest method
+    pub fn undefined_method_with_suggestions<S: Into<String>>(method: S, type_name: S, span: Span, methods: &[String]) -> Diagnostic {
+        let method_str = method.into();
+        let mut diagnostic = Diagnostic::new(DiagnosticKind::UndefinedMethod {
+            method: method_str.clone(),
+            type_name: type_name.into(),
+        })
+            .with_label(Label::primary(span))
+            .with_code("E0013");
+
+        if let Some(suggestion) = find_best_match(&method_str, methods) {
+            diagnostic = diagnostic.with_help(format!("Did you mean `{}`?", suggestion));
+        }
+
+        diagnostic
+    }
+
+    /// Create an enum variant payload arity error
+    pub fn variant_payload_mismatch<S: Into<String>>(enum_name: S, variant: S, expected: usize, found: usize, span: Span) -> Diagnostic {
+        let enum_str = enum_name.into();
+        let variant_str = variant.into();
+        let help = if expected == 0 {
+            format!("Use `{}.{}` without arguments", enum_str, variant_str)
+        } else {
+            format!("Construct it as `{}.{}(...)` with {} value(s)", enum_str, variant_str, expected)
+        };
+
+        Diagnostic::new(DiagnosticKind::VariantPayloadMismatch {
+            enum_name: enum_str,
+            variant: variant_str,
+            expected,
+            found,
+        })
+            .with_label(Label::primary(span))
+            .with_code("E0025")
+            .with_help(help)
+    }
+
+    /// Create an unknown named argument error listing the function's parameters
+    pub fn unknown_named_argument<S: Into<String>>(name: S, function: S, span: Span, parameters: &[String]) -> Diagnostic {
+        let function_str = function.into();
+        let mut diagnostic = Diagnostic::new(DiagnosticKind::UnknownNamedArgument {
+            name: name.into(),
+            function: function_str.clone(),
+        })
+            .with_label(Label::primary(span))
+            .with_code("E0026");
+
+        if !parameters.is_empty() {
+            diagnostic = diagnostic.with_help(format!("`{}` has parameters: {}", function_str, parameters.join(", ")));
+        }
+
+        diagnostic
+    }
+
+    /// Create an error for an argument given both positionally and by name, or named twice
+    pub fn duplicate_named_argument<S: Into<String>>(name: S, span: Span) -> Diagnostic {
+        Diagnostic::new(DiagnosticKind::DuplicateNamedArgument { name: name.into() })
+            .with_label(Label::primary(span))
+            .with_code("E0027")
+            .with_help("Each parameter can receive only one value")
+    }
+
+    /// Create a missing required argument error
+    pub fn missing_argument<S: Into<String>>(name: S, function: S, span: Span) -> Diagnostic {
+        let name_str = name.into();
+        Diagnostic::new(DiagnosticKind::MissingArgument {
+            name: name_str.clone(),
+            function: function.into(),
+        })
+            .with_label(Label::primary(span))
+            .with_code("E0028")
+            .with_help(format!("Pass `{}` positionally or as `{}: value`, or give the parameter a default", name_str, name_str))
+    }
+
+    /// Create an undefined trait error
+    pub fn undefined_trait<S: Into<String>>(name: S, span: Span) -> Diagnostic {
+        let name_str = name.into();
+        Diagnostic::new(DiagnosticKind::UndefinedTrait { name: name_str.clone() })
+            .with_label(Label::primary(span))
+            .with_code("E0030")
+            .with_help(format!("Declare it with `trait {} {{ ... }}` before implementing it", name_str))
+    }
+
+    /// Create a missing trait method error
+    pub fn missing_trait_method<S: Into<String>>(trait_name: S, method: S, type_name: S, span: Span) -> Diagnostic {
+        let method_str = method.into();
+        Diagnostic::new(DiagnosticKind::MissingTraitMethod {
+            trait_name: trait_name.into(),
+            method: method_str.clone(),
+            type_name: type_name.into(),
+        })
+            .with_label(Label::primary(span).with_message(format!("missing `{}` in implementation", method_str)))
+            .with_code("E0031")
+            .with_help(format!("Add `fun {}(...)` to the impl block, or give it a default body in the trait", method_str))
+    }
+
+    /// Create a trait method signature mismatch error at the implementing method, with
+    /// the trait's declaration of it as a secondary label
+    pub fn trait_method_mismatch<S: Into<String>>(trait_name: S, method: S, reason: S, span: Span, trait_span: Span) -> Diagnostic {
+        let trait_str = trait_name.into();
+        Diagnostic::new(DiagnosticKind::TraitMethodMismatch {
+            trait_name: trait_str.clone(),
+            method: method.into(),
+            reason: reason.into(),
+        })
+            .with_label(Label::primary(span))
+            .with_label(Label::secondary(trait_span).with_message(format!("signature declared in trait `{}`", trait_str)))
+            .with_code("E0032")
+            .with_note("Trait implementations must match the trait's parameters and return type")
+    }
+
+    /// Create an error for an impl method the trait does not declare
+    pub fn method_not_in_trait<S: Into<String>>(method: S, trait_name: S, span: Span) -> Diagnostic {
+        let trait_str = trait_name.into();
+        Diagnostic::new(DiagnosticKind::MethodNotInTrait {
+            method: method.into(),
+            trait_name: trait_str.clone(),
+        })
+            .with_label(Label::primary(span).with_message(format!("not a member of trait `{}`", trait_str)))
+            .with_code("E0033")
+            .with_help("Move the method to an inherent `impl` block for the type")
+    }
+
     /// Create an immutable assignment error with context
     pub fn immutable_assignment<S: Into<String>>(name: S, span: Span) -> Diagnostic {
         let name_str = name.into();
@@ -394,7 +549,7 @@ pub mod helpers {
         Diagnostic::new(DiagnosticKind::BreakOutsideLoop)
             .with_label(Label::primary(span))
             .with_code("E0009")
-            .with_help("Use `break` only inside `for` or `while` statements")
+            .with_help("Use `break` only inside `for`, `while` or `loop`")
     }
 
     /// Create a continue outside loop error
@@ -402,7 +557,24 @@ pub mod helpers {
         Diagnostic::new(DiagnosticKind::ContinueOutsideLoop)
             .with_label(Label::primary(span))
             .with_code("E0010")
-            .with_help("Use `continue` only inside `for` or `while` statements")
+            .with_help("Use `continue` only inside `for`, `while` or `loop`")
+    }
+
+    /// Create an undeclared loop label error
+    pub fn undeclared_label<S: Into<String>>(name: S, span: Span) -> Diagnostic {
+        let name_str = name.into();
+        Diagnostic::new(DiagnosticKind::UndeclaredLabel { name: name_str.clone() })
+            .with_label(Label::primary(span))
+            .with_code("E0034")
+            .with_help(format!("Label an enclosing loop with `{}: for ...`, `{}: while ...` or `{}: loop {{ ... }}`", name_str, name_str, name_str))
+    }
+
+    /// Create an error for a break value that no `loop` expression receives
+    pub fn break_value_outside_loop(span: Span) -> Diagnostic {
+        Diagnostic::new(DiagnosticKind::BreakValueOutsideLoop)
+            .with_label(Label::primary(span))
+            .with_code("E0035")
+            .with_help("Only `loop { ... }` produces a value; use a plain `break` in `for` and `while`")
     }
     
     /// Create an invalid condition error
@@ -459,6 +631,16 @@ pub mod helpers {
         diagnostic
     }
     
+    /// Create an invalid type annotation error (e.g. wrong number of type arguments)
+    pub fn invalid_type_annotation<S: Into<String>>(type_name: S, reason: S, span: Span) -> Diagnostic {
+        Diagnostic::new(DiagnosticKind::InvalidTypeAnnotation {
+            type_name: type_name.into(),
+            reason: reason.into(),
+        })
+            .with_label(Label::primary(span))
+            .with_code("E0029")
+    }
+    
     /// Create an uninitialized variable error
     pub fn uninitialized_variable<S: Into<String>>(name: S, span: Span) -> Diagnostic {
         let name_str = name.into();
@@ -468,6 +650,18 @@ pub mod helpers {
             .with_help(format!("Initialize `{}` before using it: `var {} = value`", name_str, name_str))
     }
     
+    /// Create a mismatched return type error
+    pub fn invalid_return_type<S: Into<String>>(expected: S, found: S, span: Span) -> Diagnostic {
+        let expected_str = expected.into();
+        let found_str = found.into();
+        Diagnostic::new(DiagnosticKind::InvalidReturnType {
+            expected: expected_str.clone(),
+            found: found_str.clone(),
+        })
+            .with_label(Label::primary(span).with_message(format!("expected `{}`, found `{}`", expected_str, found_str)))
+            .with_code("E0036")
+    }
+
     /// Create a missing return statement error
     pub fn missing_return<S: Into<String>>(function_name: S, span: Span) -> Diagnostic {
         let func_str = function_name.into();
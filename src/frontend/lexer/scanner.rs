@@ -26,6 +26,7 @@ impl Scanner {
         keywords.insert("struct".to_string(), TokenKind::Struct);
         keywords.insert("enum".to_string(), TokenKind::Enum);
         keywords.insert("impl".to_string(), TokenKind::Impl);
+        keywords.insert("trait".to_string(), TokenKind::Trait);
 
         // Control Flow
         keywords.insert("if".to_string(), TokenKind::If);
@@ -41,6 +42,8 @@ impl Scanner {
         keywords.insert("try".to_string(), TokenKind::Try);
         keywords.insert("catch".to_string(), TokenKind::Catch);
         keywords.insert("throw".to_string(), TokenKind::Throw);
+        keywords.insert("defer".to_string(), TokenKind::Defer);
+        keywords.insert("loop".to_string(), TokenKind::Loop);
 
         // Module System
         keywords.insert("mod".to_string(), TokenKind::Mod);
@@ -85,7 +88,7 @@ impl Scanner {
             self.scan_token();
         }
 
-        self.tokens.push(Token::new(TokenKind::Eof, "".to_string(), self.line));
+        self.tokens.push(Token::new(TokenKind::Eof, "".to_string(), self.line, self.column_of(self.current)));
         &self.tokens
     }
 
@@ -318,15 +321,33 @@ impl Scanner {
         }
 
         let text: String = self.source[self.start..self.current].iter().collect();
+
+        // A `d` suffix makes a decimal literal: 12.50d keeps its two places exactly
+        if self.peek() == 'd' && !(self.peek_next().is_alphanumeric() || self.peek_next() == '_') {
+            self.advance(); // consume 'd'
+            let value = if text.contains(['e', 'E']) {
+                rust_decimal::Decimal::from_scientific(&text)
+            } else {
+                rust_decimal::Decimal::from_str_exact(&text)
+            };
+            match value {
+                Ok(value) => self.add_token(TokenKind::Decimal(value)),
+                Err(_) => self.add_token(TokenKind::Illegal),
+            }
+            return;
+        }
+
         if is_float {
             match text.parse::<f64>() {
                 Ok(value) => self.add_token(TokenKind::Float(value)),
                 Err(_) => self.add_token(TokenKind::Illegal),
             }
         } else {
-            match text.parse::<i64>() {
-                Ok(value) => self.add_token(TokenKind::Integer(value)),
-                Err(_) => self.add_token(TokenKind::Illegal),
+            // Literals too large for int become bigints
+            match (text.parse::<i64>(), text.parse::<num_bigint::BigInt>()) {
+                (Ok(value), _) => self.add_token(TokenKind::Integer(value)),
+                (Err(_), Ok(value)) => self.add_token(TokenKind::BigInteger(value)),
+                _ => self.add_token(TokenKind::Illegal),
             }
         }
     }
@@ -477,6 +498,12 @@ impl Scanner {
 
     fn add_token(&mut self, kind: TokenKind) {
         let text: String = self.source[self.start..self.current].iter().collect();
-        self.tokens.push(Token::new(kind, text, self.line));
+        self.tokens.push(Token::new(kind, text, self.line, self.column_of(self.start)));
+    }
+
+    /// Column of the character at `index` within its line
+    fn column_of(&self, index: usize) -> usize {
+        let line_start = self.source[..index].iter().rposition(|&c| c == '\n').map_or(0, |newline| newline + 1);
+        index - line_start + 1
     }
 }
@@ -6,10 +6,10 @@ pub enum TokenKind {
     Const, Var, Fun, Type,
 
     // Data Structures
-    Struct, Enum, Impl,
+    Struct, Enum, Impl, Trait,
 
     // Control Flow
-    If, Else, Elif, While, For, In, Return, Break, Continue, Match, Try, Catch, Throw,
+    If, Else, Elif, While, For, Loop, In, Return, Break, Continue, Match, Try, Catch, Throw, Defer,
 
     // Module System
     Mod, Use, Pub, From, As,
@@ -18,7 +18,7 @@ pub enum TokenKind {
     Int, Str, Bool, Char, Array, Map, Any, FloatType, // Added FloatType
 
     // Literals
-    Identifier, String(String), FString(String), Character(char), Integer(i64), Float(f64), True, False, Null, Self_,
+    Identifier, String(String), FString(String), Character(char), Integer(i64), BigInteger(num_bigint::BigInt), Float(f64), Decimal(rust_decimal::Decimal), True, False, Null, Self_,
 
     // Arithmetic Operators
     Plus, Minus, Star, Slash, Percent, StarStar,
@@ -64,10 +64,11 @@ pub struct Token {
     pub kind: TokenKind,
     pub lexeme: String,
     pub line: usize,
+    pub column: usize, // 1-based, in characters; 0 when the position is unknown
 }
 
 impl Token {
-    pub fn new(kind: TokenKind, lexeme: String, line: usize) -> Self {
-        Token { kind, lexeme, line }
+    pub fn new(kind: TokenKind, lexeme: String, line: usize, column: usize) -> Self {
+        Token { kind, lexeme, line, column }
     }
 }
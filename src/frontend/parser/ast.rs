@@ -1,5 +1,8 @@
 // src/frontend/parser/ast.rs
 
+use num_bigint::BigInt;
+use rust_decimal::Decimal;
+
 /// The main AST node representing a complete Razen program
 #[derive(Debug, Clone, PartialEq)]
 pub struct Program {
@@ -24,6 +27,7 @@ pub enum Statement {
     // Data structures
     StructDeclaration(StructDeclaration),
     EnumDeclaration(EnumDeclaration),
+    TraitDeclaration(TraitDeclaration),
     ImplBlock(ImplBlock),
     
     // Control flow
@@ -38,6 +42,7 @@ pub enum Statement {
     BreakStatement(BreakStatement),
     ContinueStatement(ContinueStatement),
     ThrowStatement(ThrowStatement),
+    DeferStatement(DeferStatement),
     
     // Expression statement
     ExpressionStatement(ExpressionStatement),
@@ -52,6 +57,7 @@ pub enum Expression {
     // Literals
     IntegerLiteral(IntegerLiteral),
     FloatLiteral(FloatLiteral),
+    DecimalLiteral(DecimalLiteral),
     StringLiteral(StringLiteral),
     CharacterLiteral(CharacterLiteral),
     BooleanLiteral(BooleanLiteral),
@@ -83,10 +89,17 @@ pub enum Expression {
     
     // Array access
     IndexExpression(IndexExpression),
+
+    // Slicing (e.g., xs[1..3], s[2..], xs[..n])
+    SliceExpression(SliceExpression),
     
     // Array literal
     ArrayLiteral(ArrayLiteral),
     
+    // Tuple literal and positional access (e.g., (a, b) and pair.0)
+    TupleLiteral(TupleLiteral),
+    TupleIndexExpression(TupleIndexExpression),
+    
     // Map literal
     MapLiteral(MapLiteral),
     
@@ -107,6 +120,27 @@ pub enum Expression {
     
     // Grouping (parentheses)
     GroupingExpression(GroupingExpression),
+    
+    // Lambda expression (e.g., |a, b| => a + b)
+    LambdaExpression(LambdaExpression),
+    
+    // Match used as a value (e.g., var x = match n { 1 => "one", _ => "many" })
+    MatchExpression(MatchExpression),
+    
+    // Infinite loop producing the value of its `break` (e.g., var x = loop { break 42 })
+    LoopExpression(LoopExpression),
+    
+    // Error propagation (e.g., read_config()?)
+    PropagateExpression(PropagateExpression),
+    
+    // Optional chaining (e.g., user?.name or user?.greet())
+    OptionalChainExpression(OptionalChainExpression),
+    
+    // Spread in call arguments or array literals (e.g., f(...args), [...a, ...b])
+    SpreadExpression(SpreadExpression),
+    
+    // Named call argument (e.g., connect(host: "x", port: 9000))
+    NamedArgument(NamedArgument),
 }
 
 // Module System
@@ -132,6 +166,7 @@ pub struct ModuleReference {
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariableDeclaration {
     pub name: Identifier,
+    pub destructure: Vec<Identifier>, // var (q, r) = ...; empty for a single name
     pub type_annotation: Option<TypeAnnotation>,
     pub initializer: Option<Expression>,
     pub is_public: bool,
@@ -156,6 +191,7 @@ pub struct TypeAliasDeclaration {
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDeclaration {
     pub name: Identifier,
+    pub type_parameters: Vec<Identifier>, // fun first<T>(...)
     pub parameters: Vec<Parameter>,
     pub return_type: Option<TypeAnnotation>,
     pub body: BlockStatement,
@@ -166,12 +202,15 @@ pub struct FunctionDeclaration {
 pub struct Parameter {
     pub name: Identifier,
     pub type_annotation: Option<TypeAnnotation>,
+    pub is_variadic: bool, // ...name collects remaining arguments into an array
+    pub default_value: Option<Expression>, // used when the caller omits the argument
 }
 
 // Data Structures
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructDeclaration {
     pub name: Identifier,
+    pub type_parameters: Vec<Identifier>, // struct Pair<A, B> { ... }
     pub fields: Vec<StructField>,
     pub is_public: bool,
 }
@@ -198,9 +237,26 @@ pub struct EnumVariant {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ImplBlock {
     pub target_type: Identifier,
+    pub trait_name: Option<Identifier>, // impl Trait for Type { ... }
     pub methods: Vec<MethodDeclaration>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitDeclaration {
+    pub name: Identifier,
+    pub methods: Vec<TraitMethod>,
+    pub is_public: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitMethod {
+    pub name: Identifier,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<TypeAnnotation>,
+    pub default_body: Option<BlockStatement>, // None for required methods
+    pub is_static: bool,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct MethodDeclaration {
     pub name: Identifier,
@@ -227,13 +283,16 @@ pub struct ElifBranch {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct WhileStatement {
+    pub label: Option<Identifier>, // outer: while ...
     pub condition: Expression,
     pub body: Box<Statement>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ForStatement {
+    pub label: Option<Identifier>, // outer: for ...
     pub variable: Identifier,
+    pub destructure: Vec<Identifier>, // for (k, v) in ...; empty for a single name
     pub iterable: Expression,
     pub body: Box<Statement>,
 }
@@ -247,14 +306,53 @@ pub struct MatchStatement {
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatchArm {
     pub pattern: Pattern,
-    pub body: Expression,
+    pub guard: Option<Expression>, // Optional `if` guard
+    pub body: MatchArmBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchArmBody {
+    Expression(Expression), // pattern => value
+    Block(BlockStatement),  // pattern => { statements }, value of a trailing expression
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Pattern {
     Literal(Expression),
-    Identifier(Identifier),
+    Identifier(Identifier), // Binds the matched value
     Wildcard,
+    // Variant with optional payload: Some(x), None, Err(e), Shape.Circle(r)
+    Variant {
+        enum_name: Option<Identifier>,
+        variant: Identifier,
+        payload: Vec<Pattern>,
+    },
+    // Struct field pattern: Point { x, y: 0 }
+    Struct {
+        name: Identifier,
+        fields: Vec<FieldPattern>,
+    },
+    // Array pattern: [first, second, ..]
+    Array {
+        elements: Vec<Pattern>,
+        has_rest: bool,
+    },
+    // Tuple pattern: (a, b)
+    Tuple(Vec<Pattern>),
+    // Range pattern: 1..=9
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        inclusive: bool,
+    },
+    // Or pattern: 1 | 2 | 3
+    Or(Vec<Pattern>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPattern {
+    pub name: Identifier,
+    pub pattern: Pattern,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -276,16 +374,27 @@ pub struct ReturnStatement {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct BreakStatement;
+pub struct BreakStatement {
+    pub label: Option<Identifier>,  // break outer
+    pub value: Option<Expression>,  // break value, only inside `loop`
+}
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct ContinueStatement;
+pub struct ContinueStatement {
+    pub label: Option<Identifier>,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ThrowStatement {
     pub value: Expression,
 }
 
+/// `defer { ... }` or `defer expr` - runs when the enclosing function exits
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeferStatement {
+    pub body: BlockStatement,
+}
+
 // Other Statements
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExpressionStatement {
@@ -300,7 +409,7 @@ pub struct BlockStatement {
 // Expressions
 #[derive(Debug, Clone, PartialEq)]
 pub struct IntegerLiteral {
-    pub value: i64,
+    pub value: BigInt, // Literals beyond the int range evaluate to bigints
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -308,6 +417,11 @@ pub struct FloatLiteral {
     pub value: f64,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecimalLiteral {
+    pub value: Decimal, // 12.50d, with the scale as written
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct StringLiteral {
     pub value: String,
@@ -337,6 +451,7 @@ pub struct BinaryExpression {
     pub left: Box<Expression>,
     pub operator: BinaryOperator,
     pub right: Box<Expression>,
+    pub span: Option<crate::frontend::diagnostics::Span>, // the operator
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -355,12 +470,14 @@ pub enum BinaryOperator {
     
     // Special
     Range, // for 1..10
+    NullCoalesce, // for a ?? b
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct UnaryExpression {
     pub operator: UnaryOperator,
     pub operand: Box<Expression>,
+    pub span: Option<crate::frontend::diagnostics::Span>, // the operator
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -410,6 +527,14 @@ pub struct IndexExpression {
     pub index: Box<Expression>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceExpression {
+    pub object: Box<Expression>,
+    pub start: Option<Box<Expression>>, // defaults to the beginning
+    pub end: Option<Box<Expression>>,   // defaults to the end
+    pub inclusive: bool,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ArrayLiteral {
     pub elements: Vec<Expression>,
@@ -474,17 +599,79 @@ pub struct GroupingExpression {
     pub expression: Box<Expression>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct TupleLiteral {
+    pub elements: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TupleIndexExpression {
+    pub object: Box<Expression>,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExpression {
+    pub expression: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopExpression {
+    pub label: Option<Identifier>,
+    pub body: BlockStatement,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropagateExpression {
+    pub expression: Box<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionalChainExpression {
+    pub object: Box<Expression>,
+    pub property: Identifier,
+    pub arguments: Option<Vec<Expression>>, // Some for method calls: a?.method()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadExpression {
+    pub expression: Box<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArgument {
+    pub name: Identifier,
+    pub value: Box<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LambdaExpression {
+    pub parameters: Vec<Parameter>,
+    pub body: LambdaBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LambdaBody {
+    Expression(Box<Expression>), // |x| => x * 2
+    Block(BlockStatement),       // |x| => { return x * 2 }
+}
+
 // Type System
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeAnnotation {
     Int,
+    BigInt,
     Float,
+    Decimal,
     String,
     Bool,
     Char,
     Array(Box<TypeAnnotation>),
     Map(Box<TypeAnnotation>, Box<TypeAnnotation>),
     Custom(Identifier),
+    Generic(Identifier, Vec<TypeAnnotation>), // Pair<int, str>
+    Tuple(Vec<TypeAnnotation>),               // (int, str)
     Any,
 }
 
@@ -506,8 +693,19 @@ impl Identifier {
 }
 
 impl IntegerLiteral {
-    pub fn new(value: i64) -> Self {
-        IntegerLiteral { value }
+    pub fn new(value: impl Into<BigInt>) -> Self {
+        IntegerLiteral { value: value.into() }
+    }
+
+    /// Whether the literal is outside the int range and evaluates to a bigint
+    pub fn is_big(&self) -> bool {
+        i64::try_from(&self.value).is_err()
+    }
+}
+
+impl DecimalLiteral {
+    pub fn new(value: Decimal) -> Self {
+        DecimalLiteral { value }
     }
 }
 
@@ -543,7 +741,11 @@ impl BlockStatement {
 
 impl ImplBlock {
     pub fn new(target_type: Identifier, methods: Vec<MethodDeclaration>) -> Self {
-        ImplBlock { target_type, methods }
+        ImplBlock { target_type, trait_name: None, methods }
+    }
+
+    pub fn for_trait(trait_name: Identifier, target_type: Identifier, methods: Vec<MethodDeclaration>) -> Self {
+        ImplBlock { target_type, trait_name: Some(trait_name), methods }
     }
 }
 
@@ -2,6 +2,7 @@
 
 use crate::frontend::lexer::token::{Token, TokenKind};
 use crate::frontend::parser::ast::*;
+use crate::frontend::parser::statement::StatementParser;
 use crate::frontend::diagnostics::{Diagnostic, Position, Span, helpers};
 
 /// Parser error type - now wraps diagnostic
@@ -40,6 +41,17 @@ impl ParseError {
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Convert a token to a span, unless it was lexed outside the source (inside an f-string)
+pub(crate) fn token_span(token: &Token) -> Option<Span> {
+    if token.column == 0 {
+        return None;
+    }
+    let length = token.lexeme.chars().count();
+    let start = Position::new(token.line, token.column, 0);
+    let end = Position::new(token.line, token.column + length, length);
+    Some(Span::new(start, end))
+}
+
 /// Expression parser for Razen language
 pub struct ExpressionParser<'a> {
     tokens: &'a [Token],
@@ -64,7 +76,7 @@ impl<'a> ExpressionParser<'a> {
 
     /// Parse assignment expressions (lowest precedence)
     fn parse_assignment(&mut self) -> ParseResult<Expression> {
-        let expr = self.parse_logical_or()?;
+        let expr = self.parse_null_coalescing()?;
 
         if self.match_tokens(&[
             TokenKind::Equal,
@@ -107,16 +119,36 @@ impl<'a> ExpressionParser<'a> {
         Ok(expr)
     }
 
+    /// Parse null-coalescing expressions: a ?? b
+    fn parse_null_coalescing(&mut self) -> ParseResult<Expression> {
+        let mut expr = self.parse_logical_or()?;
+
+        while self.match_tokens(&[TokenKind::QuestionQuestion]) {
+            let span = self.previous_span();
+            let right = self.parse_logical_or()?;
+            expr = Expression::BinaryExpression(BinaryExpression {
+                left: Box::new(expr),
+                operator: BinaryOperator::NullCoalesce,
+                right: Box::new(right),
+                span,
+            });
+        }
+
+        Ok(expr)
+    }
+
     /// Parse logical OR expressions
     fn parse_logical_or(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_logical_and()?;
 
         while self.match_tokens(&[TokenKind::PipePipe]) {
+            let span = self.previous_span();
             let right = self.parse_logical_and()?;
             expr = Expression::BinaryExpression(BinaryExpression {
                 left: Box::new(expr),
                 operator: BinaryOperator::Or,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -128,11 +160,13 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_bitwise_or()?;
 
         while self.match_tokens(&[TokenKind::AmpersandAmpersand]) {
+            let span = self.previous_span();
             let right = self.parse_bitwise_or()?;
             expr = Expression::BinaryExpression(BinaryExpression {
                 left: Box::new(expr),
                 operator: BinaryOperator::And,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -144,11 +178,13 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_bitwise_xor()?;
 
         while self.match_tokens(&[TokenKind::Pipe]) {
+            let span = self.previous_span();
             let right = self.parse_bitwise_xor()?;
             expr = Expression::BinaryExpression(BinaryExpression {
                 left: Box::new(expr),
                 operator: BinaryOperator::BitwiseOr,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -160,11 +196,13 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_bitwise_and()?;
 
         while self.match_tokens(&[TokenKind::Caret]) {
+            let span = self.previous_span();
             let right = self.parse_bitwise_and()?;
             expr = Expression::BinaryExpression(BinaryExpression {
                 left: Box::new(expr),
                 operator: BinaryOperator::BitwiseXor,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -176,11 +214,13 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_equality()?;
 
         while self.match_tokens(&[TokenKind::Ampersand]) {
+            let span = self.previous_span();
             let right = self.parse_equality()?;
             expr = Expression::BinaryExpression(BinaryExpression {
                 left: Box::new(expr),
                 operator: BinaryOperator::BitwiseAnd,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -192,6 +232,7 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_comparison()?;
 
         while self.match_tokens(&[TokenKind::EqualEqual, TokenKind::BangEqual]) {
+            let span = self.previous_span();
             let operator = self.previous().kind.clone();
             let right = self.parse_comparison()?;
 
@@ -205,6 +246,7 @@ impl<'a> ExpressionParser<'a> {
                 left: Box::new(expr),
                 operator: binary_op,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -221,6 +263,7 @@ impl<'a> ExpressionParser<'a> {
             TokenKind::Less,
             TokenKind::LessEqual,
         ]) {
+            let span = self.previous_span();
             let operator = self.previous().kind.clone();
             let right = self.parse_shift()?;
 
@@ -236,6 +279,7 @@ impl<'a> ExpressionParser<'a> {
                 left: Box::new(expr),
                 operator: binary_op,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -247,6 +291,7 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_range()?;
 
         while self.match_tokens(&[TokenKind::LessLess, TokenKind::GreaterGreater]) {
+            let span = self.previous_span();
             let operator = self.previous().kind.clone();
             let right = self.parse_range()?;
 
@@ -260,6 +305,7 @@ impl<'a> ExpressionParser<'a> {
                 left: Box::new(expr),
                 operator: binary_op,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -289,11 +335,61 @@ impl<'a> ExpressionParser<'a> {
         Ok(expr)
     }
 
+    /// Whether the brackets just opened hold a slice range rather than a single index
+    fn is_slice_index(&self) -> bool {
+        let mut depth = 0usize;
+        for token in &self.tokens[self.current..] {
+            match token.kind {
+                TokenKind::LeftBracket | TokenKind::LeftParen | TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBracket if depth == 0 => return false,
+                TokenKind::RightBracket | TokenKind::RightParen | TokenKind::RightBrace => {
+                    depth = depth.saturating_sub(1)
+                }
+                TokenKind::DotDot | TokenKind::DotDotEqual if depth == 0 => return true,
+                TokenKind::Eof => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Parse the inside of `object[start..end]` after the '['; either bound may be omitted
+    fn parse_slice(&mut self, object: Expression) -> ParseResult<Expression> {
+        let start = if self.check(&TokenKind::DotDot) || self.check(&TokenKind::DotDotEqual) {
+            None
+        } else {
+            Some(Box::new(self.parse_term()?))
+        };
+
+        if !self.match_tokens(&[TokenKind::DotDot, TokenKind::DotDotEqual]) {
+            return Err(ParseError::new("Expected '..' or '..=' in slice".to_string(), self.peek().line));
+        }
+        let inclusive = self.previous().kind == TokenKind::DotDotEqual;
+
+        let end = if self.check(&TokenKind::RightBracket) {
+            None
+        } else {
+            Some(Box::new(self.parse_term()?))
+        };
+        if inclusive && end.is_none() {
+            return Err(ParseError::new("Inclusive slice '..=' needs an end index".to_string(), self.peek().line));
+        }
+        self.consume(TokenKind::RightBracket, "Expected ']' after slice")?;
+
+        Ok(Expression::SliceExpression(SliceExpression {
+            object: Box::new(object),
+            start,
+            end,
+            inclusive,
+        }))
+    }
+
     /// Parse term expressions (addition and subtraction)
     fn parse_term(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_factor()?;
 
         while self.match_tokens(&[TokenKind::Minus, TokenKind::Plus]) {
+            let span = self.previous_span();
             let operator = self.previous().kind.clone();
             let right = self.parse_factor()?;
 
@@ -307,6 +403,7 @@ impl<'a> ExpressionParser<'a> {
                 left: Box::new(expr),
                 operator: binary_op,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -318,6 +415,7 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_power()?;
 
         while self.match_tokens(&[TokenKind::Slash, TokenKind::Star, TokenKind::Percent]) {
+            let span = self.previous_span();
             let operator = self.previous().kind.clone();
             let right = self.parse_power()?;
 
@@ -332,6 +430,7 @@ impl<'a> ExpressionParser<'a> {
                 left: Box::new(expr),
                 operator: binary_op,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -343,11 +442,13 @@ impl<'a> ExpressionParser<'a> {
         let mut expr = self.parse_unary()?;
 
         while self.match_tokens(&[TokenKind::StarStar]) {
+            let span = self.previous_span();
             let right = self.parse_unary()?;
             expr = Expression::BinaryExpression(BinaryExpression {
                 left: Box::new(expr),
                 operator: BinaryOperator::Power,
                 right: Box::new(right),
+                span,
             });
         }
 
@@ -365,6 +466,7 @@ impl<'a> ExpressionParser<'a> {
             TokenKind::MinusMinus,
         ]) {
             let operator = self.previous().kind.clone();
+            let span = self.previous_span();
             let right = self.parse_unary()?;
 
             let unary_op = match operator {
@@ -380,6 +482,7 @@ impl<'a> ExpressionParser<'a> {
             return Ok(Expression::UnaryExpression(UnaryExpression {
                 operator: unary_op,
                 operand: Box::new(right),
+                span,
             }));
         }
 
@@ -397,7 +500,7 @@ impl<'a> ExpressionParser<'a> {
                 
                 if !self.check(&TokenKind::RightParen) {
                     loop {
-                        arguments.push(self.parse_expression()?);
+                        arguments.push(self.parse_argument()?);
                         if !self.match_tokens(&[TokenKind::Comma]) {
                             break;
                         }
@@ -411,8 +514,25 @@ impl<'a> ExpressionParser<'a> {
                     arguments,
                 });
             } else if self.match_tokens(&[TokenKind::Dot]) {
+                // Tuple element access: pair.0 (and pair.0.1, which lexes as a float)
+                if matches!(self.peek().kind, TokenKind::Integer(_) | TokenKind::Float(_)) {
+                    let token = self.advance().clone();
+                    for part in token.lexeme.split('.') {
+                        let index = part.parse::<usize>().map_err(|_| ParseError::new(
+                            format!("Invalid tuple index '{}'", token.lexeme),
+                            token.line,
+                        ))?;
+                        expr = Expression::TupleIndexExpression(TupleIndexExpression {
+                            object: Box::new(expr),
+                            index,
+                        });
+                    }
+                    continue;
+                }
+
                 // Member access, method call, or module call
-                let name = self.consume_identifier("Expected property name after '.'")?;
+                let name = self.consume_member_name("Expected property name after '.'")?;
+                let name_span = self.previous_span();
                 
                 // Check if this is a function call (followed by parentheses)
                 if self.check(&TokenKind::LeftParen) {
@@ -422,7 +542,7 @@ impl<'a> ExpressionParser<'a> {
                     let mut arguments = Vec::new();
                     if !self.check(&TokenKind::RightParen) {
                         loop {
-                            arguments.push(self.parse_expression()?);
+                            arguments.push(self.parse_argument()?);
                             if !self.match_tokens(&[TokenKind::Comma]) {
                                 break;
                             }
@@ -438,7 +558,7 @@ impl<'a> ExpressionParser<'a> {
                         // This is definitely a method call on a variable/expression
                         expr = Expression::MethodCallExpression(MethodCallExpression::new(
                             Box::new(expr),
-                            Identifier::new(name),
+                            Identifier { name, span: name_span },
                             arguments,
                         ));
                     } else if let Expression::Identifier(module_id) = &expr {
@@ -447,14 +567,14 @@ impl<'a> ExpressionParser<'a> {
                         // TODO: Add proper module resolution check here
                         expr = Expression::ModuleCallExpression(ModuleCallExpression::new(
                             module_id.clone(),
-                            Identifier::new(name),
+                            Identifier { name, span: name_span },
                             arguments,
                         ));
                     } else {
                         // This is a method call on an object/expression
                         expr = Expression::MethodCallExpression(MethodCallExpression::new(
                             Box::new(expr),
-                            Identifier::new(name),
+                            Identifier { name, span: name_span },
                             arguments,
                         ));
                     }
@@ -462,7 +582,7 @@ impl<'a> ExpressionParser<'a> {
                     // Regular member access
                     expr = Expression::MemberExpression(MemberExpression {
                         object: Box::new(expr),
-                        property: Identifier::new(name),
+                        property: Identifier { name, span: name_span },
                         computed: false,
                     });
                     
@@ -478,11 +598,12 @@ impl<'a> ExpressionParser<'a> {
                         if !self.check(&TokenKind::RightBrace) {
                             loop {
                                 let field_name = self.consume_identifier("Expected field name")?;
+                                let field_span = self.previous_span();
                                 self.consume(TokenKind::Colon, "Expected ':' after field name")?;
                                 let field_value = self.parse_expression()?;
                                 
                                 fields.push(StructFieldInit {
-                                    name: Identifier::new(field_name),
+                                    name: Identifier { name: field_name, span: field_span },
                                     value: field_value,
                                 });
                                 
@@ -503,6 +624,11 @@ impl<'a> ExpressionParser<'a> {
                     }
                 }
             } else if self.match_tokens(&[TokenKind::LeftBracket]) {
+                if self.is_slice_index() {
+                    expr = self.parse_slice(expr)?;
+                    continue;
+                }
+
                 // Array indexing
                 let index = self.parse_expression()?;
                 self.consume(TokenKind::RightBracket, "Expected ']' after array index")?;
@@ -523,6 +649,41 @@ impl<'a> ExpressionParser<'a> {
                 expr = Expression::UnaryExpression(UnaryExpression {
                     operator: unary_op,
                     operand: Box::new(expr),
+                    span: self.previous_span(),
+                });
+            } else if self.check(&TokenKind::Question)
+                && self.tokens.get(self.current + 1).is_some_and(|t| t.kind == TokenKind::Dot)
+            {
+                // Optional chaining: value?.field or value?.method()
+                self.advance(); // consume '?'
+                self.advance(); // consume '.'
+                let name = self.consume_member_name("Expected property name after '?.'")?;
+
+                let arguments = if self.match_tokens(&[TokenKind::LeftParen]) {
+                    let mut arguments = Vec::new();
+                    if !self.check(&TokenKind::RightParen) {
+                        loop {
+                            arguments.push(self.parse_argument()?);
+                            if !self.match_tokens(&[TokenKind::Comma]) {
+                                break;
+                            }
+                        }
+                    }
+                    self.consume(TokenKind::RightParen, "Expected ')' after method arguments")?;
+                    Some(arguments)
+                } else {
+                    None
+                };
+
+                expr = Expression::OptionalChainExpression(OptionalChainExpression {
+                    object: Box::new(expr),
+                    property: Identifier::new(name),
+                    arguments,
+                });
+            } else if self.match_tokens(&[TokenKind::Question]) {
+                // Error propagation: value?
+                expr = Expression::PropagateExpression(PropagateExpression {
+                    expression: Box::new(expr),
                 });
             } else {
                 break;
@@ -552,12 +713,24 @@ impl<'a> ExpressionParser<'a> {
             }
         }
 
+        if let Some(token) = self.match_token_kind(&TokenKind::BigInteger(Default::default()))
+            && let TokenKind::BigInteger(value) = token.kind
+        {
+            return Ok(Expression::IntegerLiteral(IntegerLiteral::new(value)));
+        }
+
         if let Some(token) = self.match_token_kind(&TokenKind::Float(0.0)) {
             if let TokenKind::Float(value) = &token.kind {
                 return Ok(Expression::FloatLiteral(FloatLiteral::new(*value)));
             }
         }
 
+        if let Some(token) = self.match_token_kind(&TokenKind::Decimal(Default::default()))
+            && let TokenKind::Decimal(value) = &token.kind
+        {
+            return Ok(Expression::DecimalLiteral(DecimalLiteral::new(*value)));
+        }
+
         if let Some(token) = self.match_token_kind(&TokenKind::String("".to_string())) {
             if let TokenKind::String(value) = &token.kind {
                 return Ok(Expression::StringLiteral(StringLiteral::new(value.clone())));
@@ -578,8 +751,27 @@ impl<'a> ExpressionParser<'a> {
             }
         }
 
+        // Labeled loop expression: outer: loop { ... }
+        if self.check(&TokenKind::Identifier)
+            && matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::Colon))
+            && matches!(self.tokens.get(self.current + 2).map(|t| &t.kind), Some(TokenKind::Loop))
+        {
+            let label = Identifier::new(self.advance().lexeme.clone());
+            self.advance(); // consume ':'
+            return self.parse_loop(Some(label));
+        }
+
+        // `map` is also a type keyword; followed by '(' it calls the map() adapter
+        if self.check(&TokenKind::Map)
+            && matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::LeftParen))
+        {
+            self.advance();
+            return Ok(Expression::Identifier(Identifier { name: "map".to_string(), span: self.previous_span() }));
+        }
+
         if self.match_tokens(&[TokenKind::Identifier]) {
             let name = self.previous().lexeme.clone();
+            let name_span = self.previous_span();
             
             if self.debug {
                 println!("[DEBUG] Found identifier '{}' in primary parsing", name);
@@ -598,11 +790,12 @@ impl<'a> ExpressionParser<'a> {
                 if !self.check(&TokenKind::RightBrace) {
                     loop {
                         let field_name = self.consume_identifier("Expected field name")?;
+                        let field_span = self.previous_span();
                         self.consume(TokenKind::Colon, "Expected ':' after field name")?;
                         let field_value = self.parse_expression()?;
                         
                         fields.push(StructFieldInit {
-                            name: Identifier::new(field_name),
+                            name: Identifier { name: field_name, span: field_span },
                             value: field_value,
                         });
                         
@@ -615,7 +808,7 @@ impl<'a> ExpressionParser<'a> {
                 self.consume(TokenKind::RightBrace, "Expected '}' after struct fields")?;
                 
                 return Ok(Expression::StructInstantiation(StructInstantiation {
-                    name: Identifier::new(name),
+                    name: Identifier { name, span: name_span },
                     fields,
                 }));
             }
@@ -624,7 +817,7 @@ impl<'a> ExpressionParser<'a> {
             if self.debug {
                 println!("[DEBUG] Returning identifier '{}' as simple identifier", name);
             }
-            return Ok(Expression::Identifier(Identifier::new(name)));
+            return Ok(Expression::Identifier(Identifier { name, span: name_span }));
         }
 
         // Handle self expression
@@ -636,8 +829,42 @@ impl<'a> ExpressionParser<'a> {
 
         // Note: Built-in functions are now handled as regular identifiers above
 
+        // Handle lambda expressions: |a, b| => a + b or || => value
+        if self.check(&TokenKind::Pipe) || self.check(&TokenKind::PipePipe) {
+            return self.parse_lambda();
+        }
+
+        // Handle match used as a value: var x = match n { ... }
+        if self.check(&TokenKind::Match) {
+            return Ok(Expression::MatchExpression(self.parse_match()?));
+        }
+
+        // Handle loop used as a value: var x = loop { break 42 }
+        if self.check(&TokenKind::Loop) {
+            return self.parse_loop(None);
+        }
+
         if self.match_tokens(&[TokenKind::LeftParen]) {
+            // Unit tuple: ()
+            if self.match_tokens(&[TokenKind::RightParen]) {
+                return Ok(Expression::TupleLiteral(TupleLiteral { elements: Vec::new() }));
+            }
+
             let expr = self.parse_expression()?;
+
+            // A comma makes it a tuple: (a, b) or (a,)
+            if self.match_tokens(&[TokenKind::Comma]) {
+                let mut elements = vec![expr];
+                while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+                    elements.push(self.parse_expression()?);
+                    if !self.match_tokens(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(TokenKind::RightParen, "Expected ')' after tuple elements")?;
+                return Ok(Expression::TupleLiteral(TupleLiteral { elements }));
+            }
+
             self.consume(TokenKind::RightParen, "Expected ')' after expression")?;
             return Ok(Expression::GroupingExpression(GroupingExpression {
                 expression: Box::new(expr),
@@ -650,7 +877,7 @@ impl<'a> ExpressionParser<'a> {
             
             if !self.check(&TokenKind::RightBracket) {
                 loop {
-                    elements.push(self.parse_expression()?);
+                    elements.push(self.parse_argument()?);
                     if !self.match_tokens(&[TokenKind::Comma]) {
                         break;
                     }
@@ -689,6 +916,316 @@ impl<'a> ExpressionParser<'a> {
         ))
     }
 
+    /// Parse a call argument or array element, allowing a leading `...` spread
+    /// or a `name: value` named argument
+    fn parse_argument(&mut self) -> ParseResult<Expression> {
+        if self.match_tokens(&[TokenKind::DotDotDot]) {
+            let expression = self.parse_expression()?;
+            return Ok(Expression::SpreadExpression(SpreadExpression {
+                expression: Box::new(expression),
+            }));
+        }
+
+        if self.check(&TokenKind::Identifier)
+            && self.tokens.get(self.current + 1).is_some_and(|t| t.kind == TokenKind::Colon)
+        {
+            let name = self.consume_identifier("Expected argument name")?;
+            let name_span = self.previous_span();
+            self.advance(); // consume ':'
+            let value = self.parse_expression()?;
+            return Ok(Expression::NamedArgument(NamedArgument {
+                name: Identifier { name, span: name_span },
+                value: Box::new(value),
+            }));
+        }
+
+        self.parse_expression()
+    }
+
+    /// Parse lambda expression: |params| => expression or |params| => { block }
+    fn parse_lambda(&mut self) -> ParseResult<Expression> {
+        let mut parameters = Vec::new();
+
+        // '||' is lexed as a single token and means an empty parameter list
+        if !self.match_tokens(&[TokenKind::PipePipe]) {
+            self.consume(TokenKind::Pipe, "Expected '|' before lambda parameters")?;
+
+            if !self.check(&TokenKind::Pipe) {
+                loop {
+                    let is_variadic = self.match_tokens(&[TokenKind::DotDotDot]);
+                    let param_name = self.consume_identifier("Expected parameter name")?;
+                    let param_span = self.previous_span();
+
+                    let type_annotation = if self.match_tokens(&[TokenKind::Colon]) {
+                        Some(self.parse_type_annotation()?)
+                    } else {
+                        None
+                    };
+
+                    parameters.push(Parameter {
+                        name: Identifier { name: param_name, span: param_span },
+                        type_annotation,
+                        is_variadic,
+                        default_value: None,
+                    });
+
+                    if !self.match_tokens(&[TokenKind::Comma]) {
+                        break;
+                    }
+                    if is_variadic {
+                        return Err(ParseError::new(
+                            "Variadic parameter must be the last parameter".to_string(),
+                            self.peek().line,
+                        ));
+                    }
+                }
+            }
+
+            self.consume(TokenKind::Pipe, "Expected '|' after lambda parameters")?;
+        }
+
+        self.consume(TokenKind::FatArrow, "Expected '=>' after lambda parameters")?;
+
+        // A brace after '=>' starts a block body unless it looks like a map literal
+        let body = if self.check(&TokenKind::LeftBrace) && !self.is_likely_map_literal() {
+            LambdaBody::Block(self.parse_block()?)
+        } else {
+            LambdaBody::Expression(Box::new(self.parse_expression()?))
+        };
+
+        Ok(Expression::LambdaExpression(LambdaExpression { parameters, body }))
+    }
+
+    /// Parse match expression: match value { pattern [if guard] => body, ... }
+    pub(crate) fn parse_match(&mut self) -> ParseResult<MatchExpression> {
+        self.consume(TokenKind::Match, "Expected 'match'")?;
+        let expression = self.parse_expression()?;
+
+        self.consume(TokenKind::LeftBrace, "Expected '{' after match expression")?;
+
+        let mut arms = Vec::new();
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let pattern = self.parse_pattern()?;
+
+            let guard = if self.match_tokens(&[TokenKind::If]) {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+
+            self.consume(TokenKind::FatArrow, "Expected '=>' after match pattern")?;
+
+            let body = if self.check(&TokenKind::LeftBrace) && !self.is_likely_map_literal() {
+                MatchArmBody::Block(self.parse_block()?)
+            } else {
+                MatchArmBody::Expression(self.parse_expression()?)
+            };
+
+            arms.push(MatchArm { pattern, guard, body });
+
+            // Optional comma
+            self.match_tokens(&[TokenKind::Comma]);
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after match arms")?;
+
+        Ok(MatchExpression {
+            expression: Box::new(expression),
+            arms,
+        })
+    }
+
+    /// Parse a pattern, including or-patterns: 1 | 2 | 3
+    pub(crate) fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        let first = self.parse_single_pattern()?;
+        if !self.check(&TokenKind::Pipe) {
+            return Ok(first);
+        }
+
+        let mut alternatives = vec![first];
+        while self.match_tokens(&[TokenKind::Pipe]) {
+            alternatives.push(self.parse_single_pattern()?);
+        }
+        Ok(Pattern::Or(alternatives))
+    }
+
+    /// Parse a pattern without top-level alternatives
+    fn parse_single_pattern(&mut self) -> ParseResult<Pattern> {
+        // Array pattern: [first, second, ..]
+        if self.match_tokens(&[TokenKind::LeftBracket]) {
+            let mut elements = Vec::new();
+            let mut has_rest = false;
+
+            if !self.check(&TokenKind::RightBracket) {
+                loop {
+                    if self.match_tokens(&[TokenKind::DotDot]) {
+                        has_rest = true;
+                    } else if has_rest {
+                        return Err(ParseError::new(
+                            "'..' must be the last element of an array pattern".to_string(),
+                            self.peek().line,
+                        ));
+                    } else {
+                        elements.push(self.parse_pattern()?);
+                    }
+                    if !self.match_tokens(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(TokenKind::RightBracket, "Expected ']' after array pattern")?;
+            return Ok(Pattern::Array { elements, has_rest });
+        }
+
+        // Tuple pattern: (a, b), or a parenthesized pattern: (p)
+        if self.match_tokens(&[TokenKind::LeftParen]) {
+            let mut elements = Vec::new();
+            let mut saw_comma = false;
+
+            if !self.check(&TokenKind::RightParen) {
+                loop {
+                    elements.push(self.parse_pattern()?);
+                    if !self.match_tokens(&[TokenKind::Comma]) {
+                        break;
+                    }
+                    saw_comma = true;
+                    if self.check(&TokenKind::RightParen) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(TokenKind::RightParen, "Expected ')' after tuple pattern")?;
+
+            if elements.len() == 1 && !saw_comma {
+                return Ok(elements.remove(0));
+            }
+            return Ok(Pattern::Tuple(elements));
+        }
+
+        if self.match_tokens(&[TokenKind::Identifier]) {
+            let name = self.previous().lexeme.clone();
+            let name_span = self.previous_span();
+
+            if name == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+
+            // Qualified variant: Shape.Circle(r) or Color.Red
+            if self.match_tokens(&[TokenKind::Dot]) {
+                let variant = self.consume_identifier("Expected variant name after '.'")?;
+                let payload = self.parse_variant_payload()?;
+                return Ok(Pattern::Variant {
+                    enum_name: Some(Identifier::new(name)),
+                    variant: Identifier::new(variant),
+                    payload,
+                });
+            }
+
+            // Unqualified variant with payload: Some(x), Ok(v), Err(e)
+            if self.check(&TokenKind::LeftParen) || name == "None" {
+                let payload = self.parse_variant_payload()?;
+                return Ok(Pattern::Variant {
+                    enum_name: None,
+                    variant: Identifier::new(name),
+                    payload,
+                });
+            }
+
+            // Struct pattern: Point { x, y: 0, .. }
+            if self.match_tokens(&[TokenKind::LeftBrace]) {
+                let mut fields = Vec::new();
+
+                while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+                    if !self.match_tokens(&[TokenKind::DotDot]) {
+                        let field_name = self.consume_identifier("Expected field name in struct pattern")?;
+                        let field_span = self.previous_span();
+                        let pattern = if self.match_tokens(&[TokenKind::Colon]) {
+                            self.parse_pattern()?
+                        } else {
+                            // Shorthand `x` binds the field to a variable of the same name
+                            Pattern::Identifier(Identifier { name: field_name.clone(), span: field_span.clone() })
+                        };
+                        fields.push(FieldPattern {
+                            name: Identifier { name: field_name, span: field_span },
+                            pattern,
+                        });
+                    }
+                    if !self.match_tokens(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+
+                self.consume(TokenKind::RightBrace, "Expected '}' after struct pattern")?;
+                return Ok(Pattern::Struct {
+                    name: Identifier::new(name),
+                    fields,
+                });
+            }
+
+            return Ok(Pattern::Identifier(Identifier { name, span: name_span }));
+        }
+
+        // Literal or range pattern: 42, "text", -1, 1..=9
+        let start = self.parse_unary()?;
+        if self.match_tokens(&[TokenKind::DotDot, TokenKind::DotDotEqual]) {
+            let inclusive = self.previous().kind == TokenKind::DotDotEqual;
+            let end = self.parse_unary()?;
+            return Ok(Pattern::Range { start: Box::new(start), end: Box::new(end), inclusive });
+        }
+
+        Ok(Pattern::Literal(start))
+    }
+
+    /// Parse an optional parenthesized list of payload patterns
+    fn parse_variant_payload(&mut self) -> ParseResult<Vec<Pattern>> {
+        let mut payload = Vec::new();
+        if self.match_tokens(&[TokenKind::LeftParen]) {
+            if !self.check(&TokenKind::RightParen) {
+                loop {
+                    payload.push(self.parse_pattern()?);
+                    if !self.match_tokens(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenKind::RightParen, "Expected ')' after variant payload")?;
+        }
+        Ok(payload)
+    }
+
+    /// Parse loop expression: loop { body }
+    fn parse_loop(&mut self, label: Option<Identifier>) -> ParseResult<Expression> {
+        self.consume(TokenKind::Loop, "Expected 'loop'")?;
+        let body = self.parse_block()?;
+        Ok(Expression::LoopExpression(LoopExpression { label, body }))
+    }
+
+    /// Parse a `{ ... }` block by delegating to the statement parser
+    fn parse_block(&mut self) -> ParseResult<BlockStatement> {
+        let mut stmt_parser = StatementParser::new(&self.tokens[self.current..]);
+        stmt_parser.set_debug(self.debug);
+        let block = stmt_parser.parse_block_statement()?;
+        self.current += stmt_parser.current_position();
+
+        match block {
+            Statement::BlockStatement(block) => Ok(block),
+            _ => Err(ParseError::new(
+                "Expected block statement".to_string(),
+                self.peek().line,
+            )),
+        }
+    }
+
+    /// Parse a type annotation by delegating to the statement parser
+    fn parse_type_annotation(&mut self) -> ParseResult<TypeAnnotation> {
+        let mut stmt_parser = StatementParser::new(&self.tokens[self.current..]);
+        let type_annotation = stmt_parser.parse_type_annotation()?;
+        self.current += stmt_parser.current_position();
+        Ok(type_annotation)
+    }
+
     // Helper methods
     fn match_tokens(&mut self, types: &[TokenKind]) -> bool {
         for token_type in types {
@@ -754,12 +1291,17 @@ impl<'a> ExpressionParser<'a> {
         }
     }
 
-    /// Convert a token to a span
-    #[allow(dead_code)]
-    fn token_to_span(&self, token: &Token) -> Span {
-        let start = Position::new(token.line, 1, 0); // We don't have column info yet
-        let end = Position::new(token.line, token.lexeme.len() + 1, token.lexeme.len());
-        Span::new(start, end)
+    /// Consume a name after '.', where the `map` keyword is a valid method name
+    fn consume_member_name(&mut self, message: &str) -> ParseResult<String> {
+        if self.match_tokens(&[TokenKind::Map]) {
+            return Ok(self.previous().lexeme.clone());
+        }
+        self.consume_identifier(message)
+    }
+
+    /// Span of the token just consumed
+    fn previous_span(&self) -> Option<Span> {
+        token_span(self.previous())
     }
     
     /// Parse f-string with interpolation support
@@ -837,7 +1379,11 @@ impl<'a> ExpressionParser<'a> {
         // Use proper lexer and parser for f-string expressions
         use crate::frontend::lexer::Lexer;
         let lexer = Lexer::new();
-        let tokens = lexer.lex(trimmed);
+        let mut tokens = lexer.lex(trimmed);
+        // Their positions are relative to the braces, not to the source
+        for token in &mut tokens {
+            token.column = 0;
+        }
         
         if tokens.is_empty() {
             return Err(ParseError::new("Empty expression in f-string".to_string(), 0));
@@ -873,6 +1419,15 @@ impl<'a> ExpressionParser<'a> {
         // Default to false - assume it's a block statement
         false
     }
+
+    /// Check if a LeftBrace starts a map literal ({key: value}) rather than a block
+    fn is_likely_map_literal(&self) -> bool {
+        if let (Some(key), Some(after_key)) = (self.tokens.get(self.current + 1), self.tokens.get(self.current + 2)) {
+            return matches!(key.kind, TokenKind::Identifier | TokenKind::String(_))
+                && matches!(after_key.kind, TokenKind::Colon);
+        }
+        false
+    }
 }
 
 #[cfg(test)]
@@ -888,11 +1443,40 @@ mod tests {
         
         let result = parser.parse_expression().unwrap();
         match result {
-            Expression::IntegerLiteral(lit) => assert_eq!(lit.value, 42),
+            Expression::IntegerLiteral(lit) => assert_eq!(lit.value, 42.into()),
+            _ => panic!("Expected integer literal"),
+        }
+    }
+
+    #[test]
+    fn test_parse_big_integer_literal() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("123456789012345678901234567890");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::IntegerLiteral(lit) => {
+                assert!(lit.is_big());
+                assert_eq!(lit.value.to_string(), "123456789012345678901234567890");
+            }
             _ => panic!("Expected integer literal"),
         }
     }
 
+    #[test]
+    fn test_parse_decimal_literal_keeps_scale() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("12.50d");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::DecimalLiteral(lit) => assert_eq!(lit.value.to_string(), "12.50"),
+            _ => panic!("Expected decimal literal"),
+        }
+    }
+
     #[test]
     fn test_parse_binary_expression() {
         let lexer = Lexer::new();
@@ -922,4 +1506,163 @@ mod tests {
             _ => panic!("Expected call expression"),
         }
     }
+
+    #[test]
+    fn test_parse_lambda_expression() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("|a, b: int| => a + b");
+        let mut parser = ExpressionParser::new(&tokens);
+        
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::LambdaExpression(lambda) => {
+                assert_eq!(lambda.parameters.len(), 2);
+                assert_eq!(lambda.parameters[1].type_annotation, Some(TypeAnnotation::Int));
+                assert!(matches!(lambda.body, LambdaBody::Expression(_)));
+            },
+            _ => panic!("Expected lambda expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_match_expression_patterns() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("match r { Ok(v) if v > 0 => v, Err(e) | Shape.Empty => 0, 1..=9 => 1, [x, ..] => x, _ => -1 }");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::MatchExpression(match_expr) => {
+                assert_eq!(match_expr.arms.len(), 5);
+                assert!(matches!(&match_expr.arms[0].pattern, Pattern::Variant { payload, .. } if payload.len() == 1));
+                assert!(match_expr.arms[0].guard.is_some());
+                assert!(matches!(&match_expr.arms[1].pattern, Pattern::Or(alternatives) if alternatives.len() == 2));
+                assert!(matches!(match_expr.arms[2].pattern, Pattern::Range { inclusive: true, .. }));
+                assert!(matches!(match_expr.arms[3].pattern, Pattern::Array { has_rest: true, .. }));
+                assert!(matches!(match_expr.arms[4].pattern, Pattern::Wildcard));
+            },
+            _ => panic!("Expected match expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_propagate_expression() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("load(path)? + 1");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::BinaryExpression(binary) => {
+                assert!(matches!(*binary.left, Expression::PropagateExpression(_)));
+            },
+            _ => panic!("Expected binary expression with a propagated operand"),
+        }
+    }
+
+    #[test]
+    fn test_parse_null_safe_operators() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("user?.name ?? \"anonymous\"");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::BinaryExpression(binary) => {
+                assert_eq!(binary.operator, BinaryOperator::NullCoalesce);
+                assert!(matches!(*binary.left, Expression::OptionalChainExpression(ref chain) if chain.arguments.is_none()));
+            },
+            _ => panic!("Expected null-coalescing expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_spread_arguments() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("log(\"INFO\", ...parts, [...a, 1])");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::CallExpression(call) => {
+                assert_eq!(call.arguments.len(), 3);
+                assert!(matches!(call.arguments[1], Expression::SpreadExpression(_)));
+                match &call.arguments[2] {
+                    Expression::ArrayLiteral(array) => {
+                        assert!(matches!(array.elements[0], Expression::SpreadExpression(_)));
+                    },
+                    _ => panic!("Expected array literal argument"),
+                }
+            },
+            _ => panic!("Expected call expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_tuples() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("((1, \"a\"), (2,), ()).0.1");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::TupleIndexExpression(outer) => {
+                assert_eq!(outer.index, 1);
+                match *outer.object {
+                    Expression::TupleIndexExpression(inner) => {
+                        assert_eq!(inner.index, 0);
+                        match *inner.object {
+                            Expression::TupleLiteral(tuple) => {
+                                assert_eq!(tuple.elements.len(), 3);
+                                assert!(matches!(tuple.elements[1], Expression::TupleLiteral(ref t) if t.elements.len() == 1));
+                                assert!(matches!(tuple.elements[2], Expression::TupleLiteral(ref t) if t.elements.is_empty()));
+                            },
+                            _ => panic!("Expected tuple literal"),
+                        }
+                    },
+                    _ => panic!("Expected nested tuple index"),
+                }
+            },
+            _ => panic!("Expected tuple index expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_map_adapter_call() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("map(0..10, |x| => x * 2)");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        match result {
+            Expression::CallExpression(call) => {
+                assert!(matches!(*call.callee, Expression::Identifier(ref id) if id.name == "map"));
+                assert_eq!(call.arguments.len(), 2);
+                assert!(matches!(call.arguments[0], Expression::RangeExpression(_)));
+            },
+            _ => panic!("Expected call expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_slices() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("xs[1..=n - 1][..2][-1]");
+        let mut parser = ExpressionParser::new(&tokens);
+
+        let result = parser.parse_expression().unwrap();
+        let Expression::IndexExpression(index) = result else {
+            panic!("Expected index expression");
+        };
+        assert!(matches!(*index.index, Expression::UnaryExpression(_)));
+        let Expression::SliceExpression(outer) = *index.object else {
+            panic!("Expected slice expression");
+        };
+        assert!(outer.start.is_none() && outer.end.is_some() && !outer.inclusive);
+        let Expression::SliceExpression(inner) = *outer.object else {
+            panic!("Expected nested slice expression");
+        };
+        assert!(inner.inclusive);
+        assert!(matches!(inner.end.as_deref(), Some(Expression::BinaryExpression(_))));
+    }
 }
\ No newline at end of file
@@ -95,15 +95,18 @@ impl Parser {
                 | crate::frontend::lexer::token::TokenKind::Fun
                 | crate::frontend::lexer::token::TokenKind::Struct
                 | crate::frontend::lexer::token::TokenKind::Enum
+                | crate::frontend::lexer::token::TokenKind::Trait
                 | crate::frontend::lexer::token::TokenKind::If
                 | crate::frontend::lexer::token::TokenKind::While
                 | crate::frontend::lexer::token::TokenKind::For
+                | crate::frontend::lexer::token::TokenKind::Loop
                 | crate::frontend::lexer::token::TokenKind::Match
                 | crate::frontend::lexer::token::TokenKind::Try
                 | crate::frontend::lexer::token::TokenKind::Return
                 | crate::frontend::lexer::token::TokenKind::Break
                 | crate::frontend::lexer::token::TokenKind::Continue
-                | crate::frontend::lexer::token::TokenKind::Throw => return,
+                | crate::frontend::lexer::token::TokenKind::Throw
+                | crate::frontend::lexer::token::TokenKind::Defer => return,
                 _ => {}
             }
 
@@ -2,18 +2,20 @@
 
 use crate::frontend::lexer::token::{Token, TokenKind};
 use crate::frontend::parser::ast::*;
-use crate::frontend::parser::expression::{ExpressionParser, ParseError, ParseResult};
+use crate::frontend::parser::expression::{ExpressionParser, ParseError, ParseResult, token_span};
+use crate::frontend::diagnostics::Span;
 
 /// Statement parser for Razen language
 pub struct StatementParser<'a> {
     tokens: &'a [Token],
     current: usize,
     debug: bool,
+    split_type_close: bool, // second half of a `>>` that closed two type argument lists
 }
 
 impl<'a> StatementParser<'a> {
     pub fn new(tokens: &'a [Token]) -> Self {
-        StatementParser { tokens, current: 0, debug: false }
+        StatementParser { tokens, current: 0, debug: false, split_type_close: false }
     }
     
     /// Set debug mode for detailed parsing output
@@ -52,14 +54,18 @@ impl<'a> StatementParser<'a> {
             self.parse_struct_declaration(is_public)
         } else if self.check(&TokenKind::Enum) {
             self.parse_enum_declaration(is_public)
+        } else if self.check(&TokenKind::Trait) {
+            self.parse_trait_declaration(is_public)
         } else if self.check(&TokenKind::Impl) {
             self.parse_impl_block()
         } else if self.check(&TokenKind::If) {
             self.parse_if_statement()
         } else if self.check(&TokenKind::While) {
-            self.parse_while_statement()
+            self.parse_while_statement(None)
         } else if self.check(&TokenKind::For) {
-            self.parse_for_statement()
+            self.parse_for_statement(None)
+        } else if self.is_labeled_loop() {
+            self.parse_labeled_loop()
         } else if self.check(&TokenKind::Match) {
             self.parse_match_statement()
         } else if self.check(&TokenKind::Try) {
@@ -72,6 +78,8 @@ impl<'a> StatementParser<'a> {
             self.parse_continue_statement()
         } else if self.check(&TokenKind::Throw) {
             self.parse_throw_statement()
+        } else if self.check(&TokenKind::Defer) {
+            self.parse_defer_statement()
         } else if self.check(&TokenKind::LeftBrace) {
             self.parse_block_statement()
         } else {
@@ -152,6 +160,7 @@ impl<'a> StatementParser<'a> {
     fn parse_constant_declaration(&mut self, is_public: bool) -> ParseResult<Statement> {
         self.consume(TokenKind::Const, "Expected 'const'")?;
         let name = self.consume_identifier("Expected constant name")?;
+        let name_span = self.previous_span();
 
         let mut type_annotation = None;
         if self.match_tokens(&[TokenKind::Colon]) {
@@ -162,7 +171,7 @@ impl<'a> StatementParser<'a> {
         let initializer = self.parse_expression()?;
 
         Ok(Statement::ConstantDeclaration(ConstantDeclaration {
-            name: Identifier::new(name),
+            name: Identifier { name, span: name_span },
             type_annotation,
             initializer,
             is_public,
@@ -172,7 +181,12 @@ impl<'a> StatementParser<'a> {
     /// Parse variable declaration: var name: type = value
     fn parse_variable_declaration(&mut self, is_public: bool) -> ParseResult<Statement> {
         self.consume(TokenKind::Var, "Expected 'var'")?;
-        let name = self.consume_identifier("Expected variable name")?;
+        let destructure = self.parse_destructure_names()?;
+        let (name, name_span) = if destructure.is_empty() {
+            (self.consume_identifier("Expected variable name")?, self.previous_span())
+        } else {
+            (Self::destructure_text(&destructure), None)
+        };
 
         let mut type_annotation = None;
         if self.match_tokens(&[TokenKind::Colon]) {
@@ -184,8 +198,16 @@ impl<'a> StatementParser<'a> {
             initializer = Some(self.parse_expression()?);
         }
 
+        if !destructure.is_empty() && initializer.is_none() {
+            return Err(ParseError::new(
+                "Destructuring declaration requires an initializer".to_string(),
+                self.peek().line,
+            ));
+        }
+
         Ok(Statement::VariableDeclaration(VariableDeclaration {
-            name: Identifier::new(name),
+            name: Identifier { name, span: name_span },
+            destructure,
             type_annotation,
             initializer,
             is_public,
@@ -196,13 +218,17 @@ impl<'a> StatementParser<'a> {
     fn parse_function_declaration(&mut self, is_public: bool) -> ParseResult<Statement> {
         self.consume(TokenKind::Fun, "Expected 'fun'")?;
         let name = self.consume_identifier("Expected function name")?;
+        let name_span = self.previous_span();
+        let type_parameters = self.parse_type_parameters()?;
 
         self.consume(TokenKind::LeftParen, "Expected '(' after function name")?;
 
         let mut parameters = Vec::new();
         if !self.check(&TokenKind::RightParen) {
             loop {
+                let is_variadic = self.match_tokens(&[TokenKind::DotDotDot]);
                 let param_name = self.consume_identifier("Expected parameter name")?;
+                let param_span = self.previous_span();
 
                 // Make type annotation optional (like old implementation)
                 let param_type = if self.match_tokens(&[TokenKind::Colon]) {
@@ -212,14 +238,24 @@ impl<'a> StatementParser<'a> {
                     None
                 };
 
+                let default_value = self.parse_parameter_default(&param_name, is_variadic, &parameters)?;
+
                 parameters.push(Parameter {
-                    name: Identifier::new(param_name),
+                    name: Identifier { name: param_name, span: param_span },
                     type_annotation: param_type,
+                    is_variadic,
+                    default_value,
                 });
 
                 if !self.match_tokens(&[TokenKind::Comma]) {
                     break;
                 }
+                if is_variadic {
+                    return Err(ParseError::new(
+                        "Variadic parameter must be the last parameter".to_string(),
+                        self.peek().line,
+                    ));
+                }
             }
         }
 
@@ -240,7 +276,8 @@ impl<'a> StatementParser<'a> {
         };
 
         Ok(Statement::FunctionDeclaration(FunctionDeclaration {
-            name: Identifier::new(name),
+            name: Identifier { name, span: name_span },
+            type_parameters,
             parameters,
             return_type,
             body,
@@ -252,6 +289,7 @@ impl<'a> StatementParser<'a> {
     fn parse_struct_declaration(&mut self, is_public: bool) -> ParseResult<Statement> {
         self.consume(TokenKind::Struct, "Expected 'struct'")?;
         let name = self.consume_identifier("Expected struct name")?;
+        let type_parameters = self.parse_type_parameters()?;
 
         self.consume(TokenKind::LeftBrace, "Expected '{' after struct name")?;
 
@@ -274,6 +312,7 @@ impl<'a> StatementParser<'a> {
 
         Ok(Statement::StructDeclaration(StructDeclaration {
             name: Identifier::new(name),
+            type_parameters,
             fields,
             is_public,
         }))
@@ -327,10 +366,20 @@ impl<'a> StatementParser<'a> {
         }))
     }
 
-    /// Parse impl block: impl TypeName { methods }
+    /// Parse impl block: impl TypeName { methods } or impl TraitName for TypeName { methods }
     fn parse_impl_block(&mut self) -> ParseResult<Statement> {
         self.consume(TokenKind::Impl, "Expected 'impl'")?;
-        let target_type = self.consume_identifier("Expected type name after 'impl'")?;
+        let first_name = self.consume_identifier("Expected type name after 'impl'")?;
+        let trait_name = if self.match_tokens(&[TokenKind::For]) {
+            Some(first_name.clone())
+        } else {
+            None
+        };
+        let target_type = if trait_name.is_some() {
+            self.consume_identifier("Expected type name after 'for'")?
+        } else {
+            first_name
+        };
 
         self.consume(TokenKind::LeftBrace, "Expected '{' after impl type")?;
 
@@ -342,16 +391,92 @@ impl<'a> StatementParser<'a> {
 
         self.consume(TokenKind::RightBrace, "Expected '}' after impl methods")?;
 
-        Ok(Statement::ImplBlock(ImplBlock::new(
-            Identifier::new(target_type),
+        Ok(Statement::ImplBlock(match trait_name {
+            Some(trait_name) => ImplBlock::for_trait(Identifier::new(trait_name), Identifier::new(target_type), methods),
+            None => ImplBlock::new(Identifier::new(target_type), methods),
+        }))
+    }
+
+    /// Parse trait declaration: trait Name { fun required(self) -> T  fun provided(self) { ... } }
+    fn parse_trait_declaration(&mut self, is_public: bool) -> ParseResult<Statement> {
+        self.consume(TokenKind::Trait, "Expected 'trait'")?;
+        let name = self.consume_identifier("Expected trait name")?;
+
+        self.consume(TokenKind::LeftBrace, "Expected '{' after trait name")?;
+
+        let mut methods = Vec::new();
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let (method_name, parameters, return_type, is_static) = self.parse_method_signature()?;
+
+            // A body makes this a default implementation
+            let default_body = if self.check(&TokenKind::LeftBrace) {
+                Some(BlockStatement::new(vec![self.parse_block_statement()?]))
+            } else {
+                self.match_tokens(&[TokenKind::Semicolon]);
+                None
+            };
+
+            methods.push(TraitMethod {
+                name: method_name,
+                parameters,
+                return_type,
+                default_body,
+                is_static,
+            });
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after trait methods")?;
+
+        Ok(Statement::TraitDeclaration(TraitDeclaration {
+            name: Identifier::new(name),
             methods,
-        )))
+            is_public,
+        }))
+    }
+
+    /// Parse an optional `= value` default after a parameter. Once one parameter has a
+    /// default, every following parameter needs one too (variadics excepted).
+    fn parse_parameter_default(&mut self, param_name: &str, is_variadic: bool, previous: &[Parameter]) -> ParseResult<Option<Expression>> {
+        if self.match_tokens(&[TokenKind::Equal]) {
+            if is_variadic {
+                return Err(ParseError::new(
+                    "Variadic parameter cannot have a default value".to_string(),
+                    self.previous().line,
+                ));
+            }
+            return Ok(Some(self.parse_expression()?));
+        }
+
+        if !is_variadic && previous.iter().any(|p| p.default_value.is_some()) {
+            return Err(ParseError::new(
+                format!("Parameter '{}' must have a default value because it follows a parameter with one", param_name),
+                self.peek().line,
+            ));
+        }
+        Ok(None)
     }
 
     /// Parse method declaration within impl block
     fn parse_method_declaration(&mut self) -> ParseResult<MethodDeclaration> {
+        let (method_name, parameters, return_type, is_static) = self.parse_method_signature()?;
+
+        // Parse method body
+        let body = self.parse_block_statement()?;
+
+        Ok(MethodDeclaration::new(
+            method_name,
+            parameters,
+            return_type,
+            BlockStatement::new(vec![body]),
+            is_static,
+        ))
+    }
+
+    /// Parse a method signature up to its body: fun name(self, params) -> Type
+    fn parse_method_signature(&mut self) -> ParseResult<(Identifier, Vec<Parameter>, Option<TypeAnnotation>, bool)> {
         self.consume(TokenKind::Fun, "Expected 'fun' for method declaration")?;
         let method_name = self.consume_identifier("Expected method name")?;
+        let method_name = Identifier { name: method_name, span: self.previous_span() };
 
         self.consume(TokenKind::LeftParen, "Expected '(' after method name")?;
 
@@ -367,6 +492,8 @@ impl<'a> StatementParser<'a> {
             parameters.push(Parameter {
                 name: Identifier::new("self".to_string()),
                 type_annotation: Some(TypeAnnotation::Custom(Identifier::new("Self".to_string()))),
+                is_variadic: false,
+                default_value: None,
             });
 
             // Check for comma if there are more parameters
@@ -377,15 +504,27 @@ impl<'a> StatementParser<'a> {
 
         // Parse remaining parameters
         while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+            let is_variadic = self.match_tokens(&[TokenKind::DotDotDot]);
             let param_name = self.consume_identifier("Expected parameter name")?;
+            let param_span = self.previous_span();
             self.consume(TokenKind::Colon, "Expected ':' after parameter name")?;
             let param_type = self.parse_type_annotation()?;
+            let default_value = self.parse_parameter_default(&param_name, is_variadic, &parameters)?;
 
             parameters.push(Parameter {
-                name: Identifier::new(param_name),
+                name: Identifier { name: param_name, span: param_span },
                 type_annotation: Some(param_type),
+                is_variadic,
+                default_value,
             });
 
+            if is_variadic && !self.check(&TokenKind::RightParen) {
+                return Err(ParseError::new(
+                    "Variadic parameter must be the last parameter".to_string(),
+                    self.peek().line,
+                ));
+            }
+
             if !self.check(&TokenKind::RightParen) {
                 self.consume(TokenKind::Comma, "Expected ',' between parameters")?;
             }
@@ -399,16 +538,7 @@ impl<'a> StatementParser<'a> {
             return_type = Some(self.parse_type_annotation()?);
         }
 
-        // Parse method body
-        let body = self.parse_block_statement()?;
-
-        Ok(MethodDeclaration::new(
-            Identifier::new(method_name),
-            parameters,
-            return_type,
-            BlockStatement::new(vec![body]),
-            is_static,
-        ))
+        Ok((method_name, parameters, return_type, is_static))
     }
 
     /// Parse if statement: if condition { then } elif condition { then } else { else }
@@ -440,28 +570,58 @@ impl<'a> StatementParser<'a> {
         }))
     }
 
+    /// `label: for ...` or `label: while ...`; labeled `loop` expressions are
+    /// handled by the expression parser
+    fn is_labeled_loop(&self) -> bool {
+        self.check(&TokenKind::Identifier)
+            && matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::Colon))
+            && matches!(
+                self.tokens.get(self.current + 2).map(|t| &t.kind),
+                Some(TokenKind::For) | Some(TokenKind::While)
+            )
+    }
+
+    /// Parse labeled loop: label: for ... | label: while ...
+    fn parse_labeled_loop(&mut self) -> ParseResult<Statement> {
+        let label = Identifier::new(self.consume_identifier("Expected loop label")?);
+        self.consume(TokenKind::Colon, "Expected ':' after loop label")?;
+        if self.check(&TokenKind::While) {
+            self.parse_while_statement(Some(label))
+        } else {
+            self.parse_for_statement(Some(label))
+        }
+    }
+
     /// Parse while statement: while condition { body }
-    fn parse_while_statement(&mut self) -> ParseResult<Statement> {
+    fn parse_while_statement(&mut self, label: Option<Identifier>) -> ParseResult<Statement> {
         self.consume(TokenKind::While, "Expected 'while'")?;
         let condition = self.parse_expression()?;
         let body = Box::new(self.parse_statement()?);
 
         Ok(Statement::WhileStatement(WhileStatement {
+            label,
             condition,
             body,
         }))
     }
 
     /// Parse for statement: for variable in iterable { body }
-    fn parse_for_statement(&mut self) -> ParseResult<Statement> {
+    fn parse_for_statement(&mut self, label: Option<Identifier>) -> ParseResult<Statement> {
         self.consume(TokenKind::For, "Expected 'for'")?;
-        let variable = self.consume_identifier("Expected loop variable")?;
+        let destructure = self.parse_destructure_names()?;
+        let variable = if destructure.is_empty() {
+            self.consume_identifier("Expected loop variable")?
+        } else {
+            Self::destructure_text(&destructure)
+        };
         self.consume(TokenKind::In, "Expected 'in' after loop variable")?;
         let iterable = self.parse_expression()?;
         let body = Box::new(self.parse_statement()?);
 
         Ok(Statement::ForStatement(ForStatement {
+            label,
             variable: Identifier::new(variable),
+            destructure,
             iterable,
             body,
         }))
@@ -469,28 +629,15 @@ impl<'a> StatementParser<'a> {
 
     /// Parse match statement: match expression { pattern => body, ... }
     fn parse_match_statement(&mut self) -> ParseResult<Statement> {
-        self.consume(TokenKind::Match, "Expected 'match'")?;
-        let expression = self.parse_expression()?;
-
-        self.consume(TokenKind::LeftBrace, "Expected '{' after match expression")?;
-
-        let mut arms = Vec::new();
-        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-            let pattern = self.parse_pattern()?;
-            self.consume(TokenKind::FatArrow, "Expected '=>' after match pattern")?;
-            let body = self.parse_expression()?;
-
-            arms.push(MatchArm { pattern, body });
-
-            // Optional comma
-            self.match_tokens(&[TokenKind::Comma]);
-        }
-
-        self.consume(TokenKind::RightBrace, "Expected '}' after match arms")?;
+        // Match parsing is shared with match expressions
+        let mut expr_parser = ExpressionParser::new(&self.tokens[self.current..]);
+        expr_parser.set_debug(self.debug);
+        let match_expr = expr_parser.parse_match()?;
+        self.current += expr_parser.current;
 
         Ok(Statement::MatchStatement(MatchStatement {
-            expression,
-            arms,
+            expression: *match_expr.expression,
+            arms: match_expr.arms,
         }))
     }
 
@@ -547,16 +694,56 @@ impl<'a> StatementParser<'a> {
         Ok(Statement::ReturnStatement(ReturnStatement { value }))
     }
 
-    /// Parse break statement: break
+    /// Parse break statement: break label? value?
     fn parse_break_statement(&mut self) -> ParseResult<Statement> {
         self.consume(TokenKind::Break, "Expected 'break'")?;
-        Ok(Statement::BreakStatement(BreakStatement))
+        let line = self.previous().line;
+        let label = self.parse_jump_label(line, true)?;
+
+        // A value has to start on the same line as the `break`
+        let mut value = None;
+        if self.peek().line == line
+            && !self.is_at_end()
+            && !self.check(&TokenKind::RightBrace)
+            && !self.check(&TokenKind::Semicolon)
+            && !self.check(&TokenKind::Comma)
+        {
+            value = Some(self.parse_expression()?);
+        }
+
+        Ok(Statement::BreakStatement(BreakStatement { label, value }))
     }
 
-    /// Parse continue statement: continue
+    /// Parse continue statement: continue label?
     fn parse_continue_statement(&mut self) -> ParseResult<Statement> {
         self.consume(TokenKind::Continue, "Expected 'continue'")?;
-        Ok(Statement::ContinueStatement(ContinueStatement))
+        let line = self.previous().line;
+        let label = self.parse_jump_label(line, false)?;
+        Ok(Statement::ContinueStatement(ContinueStatement { label }))
+    }
+
+    /// Parse the label after break/continue: an identifier on the same line. After
+    /// `break`, an identifier continued by an operator, call or index starts the value
+    /// instead (`break total + 1`); `break (total)` breaks with a variable's value.
+    fn parse_jump_label(&mut self, line: usize, can_take_value: bool) -> ParseResult<Option<Identifier>> {
+        if !self.check(&TokenKind::Identifier) || self.peek().line != line {
+            return Ok(None);
+        }
+        let starts_value = can_take_value && self.tokens.get(self.current + 1).is_some_and(|next| {
+            next.line == line
+                && !matches!(
+                    next.kind,
+                    TokenKind::RightBrace | TokenKind::Semicolon | TokenKind::Comma | TokenKind::Eof
+                        | TokenKind::Identifier | TokenKind::Integer(_) | TokenKind::BigInteger(_)
+                        | TokenKind::Float(_) | TokenKind::Decimal(_) | TokenKind::String(_)
+                        | TokenKind::FString(_) | TokenKind::Character(_) | TokenKind::True
+                        | TokenKind::False | TokenKind::Null | TokenKind::Self_
+                )
+        });
+        if starts_value {
+            return Ok(None);
+        }
+        Ok(Some(Identifier::new(self.consume_identifier("Expected loop label")?)))
     }
 
     /// Parse throw statement: throw expression
@@ -566,8 +753,31 @@ impl<'a> StatementParser<'a> {
         Ok(Statement::ThrowStatement(ThrowStatement { value }))
     }
 
+    /// Parse defer statement: defer { statements } | defer expression
+    fn parse_defer_statement(&mut self) -> ParseResult<Statement> {
+        self.consume(TokenKind::Defer, "Expected 'defer'")?;
+
+        let body = if self.check(&TokenKind::LeftBrace) {
+            if let Statement::BlockStatement(block) = self.parse_block_statement()? {
+                block
+            } else {
+                return Err(ParseError::new(
+                    "Expected block statement for defer body".to_string(),
+                    self.peek().line,
+                ));
+            }
+        } else {
+            let expression = self.parse_expression()?;
+            BlockStatement::new(vec![Statement::ExpressionStatement(ExpressionStatement {
+                expression,
+            })])
+        };
+
+        Ok(Statement::DeferStatement(DeferStatement { body }))
+    }
+
     /// Parse block statement: { statements }
-    fn parse_block_statement(&mut self) -> ParseResult<Statement> {
+    pub(crate) fn parse_block_statement(&mut self) -> ParseResult<Statement> {
         self.consume(TokenKind::LeftBrace, "Expected '{'")?;
 
         let mut statements = Vec::new();
@@ -590,24 +800,21 @@ impl<'a> StatementParser<'a> {
         }))
     }
 
-    /// Parse a pattern for match statements
-    fn parse_pattern(&mut self) -> ParseResult<Pattern> {
-        if self.match_tokens(&[TokenKind::Identifier]) {
-            let name = self.previous().lexeme.clone();
-            if name == "_" {
-                Ok(Pattern::Wildcard)
-            } else {
-                Ok(Pattern::Identifier(Identifier::new(name)))
+    /// Parse type annotation
+    pub(crate) fn parse_type_annotation(&mut self) -> ParseResult<TypeAnnotation> {
+        // Handle tuple syntax: (type, type)
+        if self.match_tokens(&[TokenKind::LeftParen]) {
+            let mut element_types = Vec::new();
+            while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+                element_types.push(self.parse_type_annotation()?);
+                if !self.match_tokens(&[TokenKind::Comma]) {
+                    break;
+                }
             }
-        } else {
-            // Try to parse as literal
-            let expr = self.parse_expression()?;
-            Ok(Pattern::Literal(expr))
+            self.consume(TokenKind::RightParen, "Expected ')' after tuple element types")?;
+            return Ok(TypeAnnotation::Tuple(element_types));
         }
-    }
 
-    /// Parse type annotation
-    fn parse_type_annotation(&mut self) -> ParseResult<TypeAnnotation> {
         // Handle array syntax: [type]
         if self.match_tokens(&[TokenKind::LeftBracket]) {
             let element_type = self.parse_type_annotation()?;
@@ -648,6 +855,17 @@ impl<'a> StatementParser<'a> {
             ))
         } else if self.match_tokens(&[TokenKind::Identifier]) {
             let name = self.previous().lexeme.clone();
+            // `bigint` and `decimal` are not keywords, so the `decimal()` constructor and
+            // variables with these names still work
+            match name.as_str() {
+                "bigint" => return Ok(TypeAnnotation::BigInt),
+                "decimal" => return Ok(TypeAnnotation::Decimal),
+                _ => {}
+            }
+            if self.match_tokens(&[TokenKind::Less]) {
+                let arguments = self.parse_type_arguments()?;
+                return Ok(TypeAnnotation::Generic(Identifier::new(name), arguments));
+            }
             Ok(TypeAnnotation::Custom(Identifier::new(name)))
         } else {
             Err(ParseError::new(
@@ -657,6 +875,69 @@ impl<'a> StatementParser<'a> {
         }
     }
 
+    /// Parse an optional destructuring target: (a, b). Returns no names for a plain identifier.
+    fn parse_destructure_names(&mut self) -> ParseResult<Vec<Identifier>> {
+        let mut names = Vec::new();
+        if !self.match_tokens(&[TokenKind::LeftParen]) {
+            return Ok(names);
+        }
+
+        loop {
+            let name = self.consume_identifier("Expected variable name in destructuring pattern")?;
+            names.push(Identifier { name, span: self.previous_span() });
+            if !self.match_tokens(&[TokenKind::Comma]) || self.check(&TokenKind::RightParen) {
+                break;
+            }
+        }
+        self.consume(TokenKind::RightParen, "Expected ')' after destructured names")?;
+        Ok(names)
+    }
+
+    /// Display name for a destructuring target, e.g. "(q, r)"
+    fn destructure_text(names: &[Identifier]) -> String {
+        let names: Vec<&str> = names.iter().map(|n| n.name.as_str()).collect();
+        format!("({})", names.join(", "))
+    }
+
+    /// Parse optional generic parameters after a declaration name: <T, U>
+    fn parse_type_parameters(&mut self) -> ParseResult<Vec<Identifier>> {
+        let mut type_parameters = Vec::new();
+        if !self.match_tokens(&[TokenKind::Less]) {
+            return Ok(type_parameters);
+        }
+
+        loop {
+            let name = self.consume_identifier("Expected type parameter name")?;
+            type_parameters.push(Identifier::new(name));
+            if !self.match_tokens(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenKind::Greater, "Expected '>' after type parameters")?;
+        Ok(type_parameters)
+    }
+
+    /// Parse the type arguments of a generic type after its '<': int, str>
+    fn parse_type_arguments(&mut self) -> ParseResult<Vec<TypeAnnotation>> {
+        let mut arguments = Vec::new();
+        loop {
+            arguments.push(self.parse_type_annotation()?);
+            if self.split_type_close || !self.match_tokens(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+
+        // A nested list may already have consumed our '>' as part of '>>'
+        if self.split_type_close {
+            self.split_type_close = false;
+        } else if self.match_tokens(&[TokenKind::GreaterGreater]) {
+            self.split_type_close = true;
+        } else {
+            self.consume(TokenKind::Greater, "Expected '>' after type arguments")?;
+        }
+        Ok(arguments)
+    }
+
     /// Parse expression using expression parser
     fn parse_expression(&mut self) -> ParseResult<Expression> {
         // Debug: Show tokens being parsed (only in dev mode)
@@ -726,6 +1007,11 @@ impl<'a> StatementParser<'a> {
         &self.tokens[self.current - 1]
     }
 
+    /// Span of the token just consumed
+    fn previous_span(&self) -> Option<Span> {
+        token_span(self.previous())
+    }
+
     fn consume(&mut self, token_type: TokenKind, message: &str) -> ParseResult<&Token> {
         if self.check(&token_type) {
             Ok(self.advance())
@@ -800,4 +1086,153 @@ mod tests {
             _ => panic!("Expected if statement"),
         }
     }
+
+    #[test]
+    fn test_parse_default_parameters() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("fun connect(host: str, port: int = 8080) { }");
+        let mut parser = StatementParser::new(&tokens);
+
+        let result = parser.parse_statement().unwrap();
+        match result {
+            Statement::FunctionDeclaration(func_decl) => {
+                assert!(func_decl.parameters[0].default_value.is_none());
+                assert!(func_decl.parameters[1].default_value.is_some());
+            }
+            _ => panic!("Expected function declaration"),
+        }
+
+        // A required parameter may not follow one with a default
+        let tokens = lexer.lex("fun connect(port: int = 8080, host: str) { }");
+        let mut parser = StatementParser::new(&tokens);
+        assert!(parser.parse_statement().is_err());
+    }
+
+    #[test]
+    fn test_parse_generic_declarations() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("fun first<T>(xs: [T]) -> T { return xs[0] }");
+        let mut parser = StatementParser::new(&tokens);
+
+        match parser.parse_statement().unwrap() {
+            Statement::FunctionDeclaration(func_decl) => {
+                assert_eq!(func_decl.type_parameters.len(), 1);
+                assert_eq!(func_decl.type_parameters[0].name, "T");
+            }
+            _ => panic!("Expected function declaration"),
+        }
+
+        let tokens = lexer.lex("struct Pair<A, B> { first: A, second: B }");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::StructDeclaration(struct_decl) => assert_eq!(struct_decl.type_parameters.len(), 2),
+            _ => panic!("Expected struct declaration"),
+        }
+
+        // Nested type arguments close with `>>`
+        let tokens = lexer.lex("var b: Box<Pair<int, str>> = x");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::VariableDeclaration(var_decl) => match var_decl.type_annotation {
+                Some(TypeAnnotation::Generic(name, args)) => {
+                    assert_eq!(name.name, "Box");
+                    assert!(matches!(&args[0], TypeAnnotation::Generic(inner, inner_args)
+                        if inner.name == "Pair" && inner_args.len() == 2));
+                }
+                other => panic!("Expected generic annotation, got {:?}", other),
+            },
+            _ => panic!("Expected variable declaration"),
+        }
+    }
+
+    #[test]
+    fn test_parse_trait_and_trait_impl() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("trait Storage { fun load(self, key: str) -> str fun name(self) -> str { return \"store\" } }");
+        let mut parser = StatementParser::new(&tokens);
+
+        match parser.parse_statement().unwrap() {
+            Statement::TraitDeclaration(trait_decl) => {
+                assert_eq!(trait_decl.name.name, "Storage");
+                assert_eq!(trait_decl.methods.len(), 2);
+                assert!(trait_decl.methods[0].default_body.is_none());
+                assert!(trait_decl.methods[1].default_body.is_some());
+                assert!(!trait_decl.methods[0].is_static);
+            }
+            _ => panic!("Expected trait declaration"),
+        }
+
+        let tokens = lexer.lex("impl Storage for MemoryStore { fun load(self, key: str) -> str { return key } }");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::ImplBlock(impl_block) => {
+                assert_eq!(impl_block.trait_name.map(|t| t.name), Some("Storage".to_string()));
+                assert_eq!(impl_block.target_type.name, "MemoryStore");
+            }
+            _ => panic!("Expected impl block"),
+        }
+    }
+
+    #[test]
+    fn test_parse_defer_statement() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("defer { close(file) log(\"done\") }");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::DeferStatement(defer_stmt) => assert_eq!(defer_stmt.body.statements.len(), 2),
+            _ => panic!("Expected defer statement"),
+        }
+
+        // `defer expr` wraps the expression in a block
+        let tokens = lexer.lex("defer unlock(mutex)");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::DeferStatement(defer_stmt) => {
+                assert_eq!(defer_stmt.body.statements.len(), 1);
+                assert!(matches!(defer_stmt.body.statements[0], Statement::ExpressionStatement(_)));
+            }
+            _ => panic!("Expected defer statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_labeled_loops_and_break_values() {
+        let lexer = Lexer::new();
+        let tokens = lexer.lex("outer: for i in items { for j in items { break outer } continue outer }");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::ForStatement(for_stmt) => {
+                assert_eq!(for_stmt.label.map(|l| l.name), Some("outer".to_string()));
+                let Statement::BlockStatement(body) = *for_stmt.body else { panic!("Expected block body") };
+                match &body.statements[1] {
+                    Statement::ContinueStatement(continue_stmt) => {
+                        assert_eq!(continue_stmt.label.as_ref().map(|l| l.name.as_str()), Some("outer"));
+                    }
+                    _ => panic!("Expected continue statement"),
+                }
+            }
+            _ => panic!("Expected for statement"),
+        }
+
+        // A bare name is a label even when it names no loop; an operator makes it a value
+        let tokens = lexer.lex("var x = loop { if done { break total } break total + 1 }");
+        let mut parser = StatementParser::new(&tokens);
+        match parser.parse_statement().unwrap() {
+            Statement::VariableDeclaration(var_decl) => match var_decl.initializer {
+                Some(Expression::LoopExpression(loop_expr)) => match &loop_expr.body.statements[..] {
+                    [Statement::IfStatement(if_stmt), Statement::BreakStatement(break_stmt)] => {
+                        let Statement::BlockStatement(then_block) = &*if_stmt.then_branch else { panic!("Expected block") };
+                        let Statement::BreakStatement(labeled) = &then_block.statements[0] else { panic!("Expected break") };
+                        assert_eq!(labeled.label.as_ref().map(|l| l.name.as_str()), Some("total"));
+                        assert!(labeled.value.is_none());
+                        assert!(break_stmt.label.is_none());
+                        assert!(matches!(break_stmt.value, Some(Expression::BinaryExpression(_))));
+                    }
+                    _ => panic!("Expected if and break statements"),
+                },
+                _ => panic!("Expected loop expression"),
+            },
+            _ => panic!("Expected variable declaration"),
+        }
+    }
 }
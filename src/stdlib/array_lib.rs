@@ -35,10 +35,10 @@ fn push(args: Vec<Value>) -> Result<Value, String> {
         return Err("push() takes exactly 2 arguments".to_string());
     }
     match &args[0] {
+        // Arrays are shared, so the caller's array grows too
         Value::Array(arr) => {
-            let mut new_arr = arr.clone();
-            new_arr.push(args[1].clone());
-            Ok(Value::Array(new_arr))
+            arr.borrow_mut().push(args[1].clone());
+            Ok(Value::Array(arr.clone()))
         }
         _ => Err("push() requires an array as first argument".to_string()),
     }
@@ -50,8 +50,7 @@ fn pop(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
-            let mut new_arr = arr.clone();
-            new_arr.pop().ok_or("Cannot pop from empty array".to_string())
+            arr.borrow_mut().pop().ok_or("Cannot pop from empty array".to_string())
         }
         _ => Err("pop() requires an array argument".to_string()),
     }
@@ -63,6 +62,7 @@ fn first(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             arr.first().cloned().ok_or("Array is empty".to_string())
         }
         _ => Err("first() requires an array argument".to_string()),
@@ -75,6 +75,7 @@ fn last(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             arr.last().cloned().ok_or("Array is empty".to_string())
         }
         _ => Err("last() requires an array argument".to_string()),
@@ -87,9 +88,9 @@ fn reverse(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
-            let mut reversed = arr.clone();
+            let mut reversed = arr.borrow().clone();
             reversed.reverse();
-            Ok(Value::Array(reversed))
+            Ok(Value::array(reversed))
         }
         _ => Err("reverse() requires an array argument".to_string()),
     }
@@ -101,6 +102,7 @@ fn contains(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             let found = arr.iter().any(|v| v.to_string() == args[1].to_string());
             Ok(Value::Boolean(found))
         }
@@ -114,8 +116,9 @@ fn sum(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             let mut total = 0.0;
-            for val in arr {
+            for val in arr.iter() {
                 match val {
                     Value::Integer(n) => total += *n as f64,
                     Value::Number(n) => total += n,
@@ -134,10 +137,11 @@ fn avg(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             if arr.is_empty() {
                 return Ok(Value::Number(0.0));
             }
-            let sum_result = sum(vec![Value::Array(arr.clone())])?;
+            let sum_result = sum(vec![args[0].clone()])?;
             match sum_result {
                 Value::Number(s) => Ok(Value::Number(s / arr.len() as f64)),
                 _ => Err("avg() calculation error".to_string()),
@@ -153,6 +157,7 @@ fn max(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             if arr.is_empty() {
                 return Err("Cannot find max of empty array".to_string());
             }
@@ -183,6 +188,7 @@ fn min(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             if arr.is_empty() {
                 return Err("Cannot find min of empty array".to_string());
             }
@@ -213,7 +219,7 @@ fn sort(args: Vec<Value>) -> Result<Value, String> {
     }
     match &args[0] {
         Value::Array(arr) => {
-            let mut sorted = arr.clone();
+            let mut sorted = arr.borrow().clone();
             sorted.sort_by(|a, b| {
                 match (a, b) {
                     (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
@@ -224,7 +230,7 @@ fn sort(args: Vec<Value>) -> Result<Value, String> {
                     _ => std::cmp::Ordering::Equal,
                 }
             });
-            Ok(Value::Array(sorted))
+            Ok(Value::array(sorted))
         }
         _ => Err("sort() requires an array argument".to_string()),
     }
@@ -235,7 +241,7 @@ fn len(args: Vec<Value>) -> Result<Value, String> {
         return Err("len() takes exactly 1 argument".to_string());
     }
     match &args[0] {
-        Value::Array(arr) => Ok(Value::Integer(arr.len() as i64)),
+        Value::Array(arr) => Ok(Value::Integer(arr.borrow().len() as i64)),
         _ => Err("len() requires an array argument".to_string()),
     }
 }
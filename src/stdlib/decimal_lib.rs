@@ -0,0 +1,79 @@
+// src/stdlib/decimal_lib.rs
+//! Decimal helpers - Native Rust implementation
+//!
+//! Reached as methods on decimal values (`price.round(2, "half_up")`, `price.scale()`);
+//! decimals themselves come from `12.50d` literals or the `decimal()` builtin.
+
+use crate::backend::execution::value::Value;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+pub fn has_function(name: &str) -> bool {
+    matches!(name, "round" | "scale")
+}
+
+pub fn get_function_list() -> Vec<&'static str> {
+    vec!["round", "scale"]
+}
+
+pub fn call_function(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    match name {
+        "round" => round(args),
+        "scale" => scale(args),
+        _ => Err(format!("Unknown decimal function: {}", name)),
+    }
+}
+
+/// Rounding modes accepted by `round()`, named as in most money libraries
+const ROUNDING_MODES: &[(&str, RoundingStrategy)] = &[
+    ("half_even", RoundingStrategy::MidpointNearestEven),
+    ("half_up", RoundingStrategy::MidpointAwayFromZero),
+    ("half_down", RoundingStrategy::MidpointTowardZero),
+    ("up", RoundingStrategy::AwayFromZero),
+    ("down", RoundingStrategy::ToZero),
+    ("ceiling", RoundingStrategy::ToPositiveInfinity),
+    ("floor", RoundingStrategy::ToNegativeInfinity),
+];
+
+fn decimal_arg(name: &str, value: &Value) -> Result<Decimal, String> {
+    match value {
+        Value::Decimal(d) => Ok(*d),
+        _ => Err(format!("{}() requires a decimal argument", name)),
+    }
+}
+
+/// Round to a number of places (default 0) with a rounding mode (default "half_even").
+/// The result always has exactly that many places, so 12.5d.round(2) is 12.50.
+fn round(args: Vec<Value>) -> Result<Value, String> {
+    if args.is_empty() || args.len() > 3 {
+        return Err("round() takes 1 to 3 arguments".to_string());
+    }
+    let value = decimal_arg("round", &args[0])?;
+    let places = match args.get(1) {
+        None => 0,
+        Some(Value::Integer(places)) if (0..=28).contains(places) => *places as u32,
+        Some(other) => return Err(format!("round() places must be an integer from 0 to 28, got {}", other)),
+    };
+    let strategy = match args.get(2) {
+        None => RoundingStrategy::MidpointNearestEven,
+        Some(Value::String(mode)) => ROUNDING_MODES
+            .iter()
+            .find(|(name, _)| name == mode)
+            .map(|(_, strategy)| *strategy)
+            .ok_or_else(|| {
+                let names: Vec<&str> = ROUNDING_MODES.iter().map(|(name, _)| *name).collect();
+                format!("Unknown rounding mode '{}'; expected one of: {}", mode, names.join(", "))
+            })?,
+        Some(other) => return Err(format!("round() mode must be a string, got {}", other)),
+    };
+    let mut rounded = value.round_dp_with_strategy(places, strategy);
+    rounded.rescale(places);
+    Ok(Value::Decimal(rounded))
+}
+
+/// Number of places after the decimal point
+fn scale(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("scale() takes exactly 1 argument".to_string());
+    }
+    Ok(Value::Integer(decimal_arg("scale", &args[0])?.scale() as i64))
+}
@@ -115,7 +115,7 @@ fn read_lines(args: Vec<Value>) -> Result<Value, String> {
                 .map(|line| Value::String(line.to_string()))
                 .collect();
             
-            Ok(Value::Array(lines))
+            Ok(Value::array(lines))
         }
         _ => Err("read_lines() requires a string path".to_string()),
     }
@@ -127,7 +127,7 @@ fn write_lines(args: Vec<Value>) -> Result<Value, String> {
     }
     match (&args[0], &args[1]) {
         (Value::String(path), Value::Array(lines)) => {
-            let content: Vec<String> = lines.iter()
+            let content: Vec<String> = lines.borrow().iter()
                 .map(|v| v.to_string())
                 .collect();
             
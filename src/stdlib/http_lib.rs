@@ -47,7 +47,7 @@ fn get(args: Vec<Value>) -> Result<Value, String> {
                             let mut result = HashMap::new();
                             result.insert("status".to_string(), Value::Integer(200));
                             result.insert("body".to_string(), Value::String(body));
-                            Ok(Value::Map(result))
+                            Ok(Value::map(result))
                         }
                         Err(e) => Err(format!("Failed to read response: {}", e)),
                     }
@@ -76,7 +76,7 @@ fn post(args: Vec<Value>) -> Result<Value, String> {
                             let mut result = HashMap::new();
                             result.insert("status".to_string(), Value::Integer(200));
                             result.insert("body".to_string(), Value::String(response_body));
-                            Ok(Value::Map(result))
+                            Ok(Value::map(result))
                         }
                         Err(e) => Err(format!("Failed to read response: {}", e)),
                     }
@@ -105,7 +105,7 @@ fn put(args: Vec<Value>) -> Result<Value, String> {
                             let mut result = HashMap::new();
                             result.insert("status".to_string(), Value::Integer(200));
                             result.insert("body".to_string(), Value::String(response_body));
-                            Ok(Value::Map(result))
+                            Ok(Value::map(result))
                         }
                         Err(e) => Err(format!("Failed to read response: {}", e)),
                     }
@@ -131,7 +131,7 @@ fn delete(args: Vec<Value>) -> Result<Value, String> {
                             let mut result = HashMap::new();
                             result.insert("status".to_string(), Value::Integer(200));
                             result.insert("body".to_string(), Value::String(body));
-                            Ok(Value::Map(result))
+                            Ok(Value::map(result))
                         }
                         Err(e) => Err(format!("Failed to read response: {}", e)),
                     }
@@ -160,7 +160,7 @@ fn patch(args: Vec<Value>) -> Result<Value, String> {
                             let mut result = HashMap::new();
                             result.insert("status".to_string(), Value::Integer(200));
                             result.insert("body".to_string(), Value::String(response_body));
-                            Ok(Value::Map(result))
+                            Ok(Value::map(result))
                         }
                         Err(e) => Err(format!("Failed to read response: {}", e)),
                     }
@@ -226,7 +226,7 @@ fn parse_url(args: Vec<Value>) -> Result<Value, String> {
                     result.insert("port".to_string(), Value::Integer(url.port().unwrap_or(0) as i64));
                     result.insert("path".to_string(), Value::String(url.path().to_string()));
                     result.insert("query".to_string(), Value::String(url.query().unwrap_or("").to_string()));
-                    Ok(Value::Map(result))
+                    Ok(Value::map(result))
                 }
                 Err(e) => Err(format!("Failed to parse URL: {}", e)),
             }
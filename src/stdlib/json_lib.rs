@@ -77,6 +77,9 @@ fn parse_json_value(s: &str) -> Result<Value, String> {
     if let Ok(n) = s.parse::<i64>() {
         return Ok(Value::Integer(n));
     }
+    if let Ok(n) = s.parse::<num_bigint::BigInt>() {
+        return Ok(Value::bigint(n));
+    }
     if let Ok(n) = s.parse::<f64>() {
         return Ok(Value::Number(n));
     }
@@ -91,7 +94,7 @@ fn parse_json_value(s: &str) -> Result<Value, String> {
     if s.starts_with('[') && s.ends_with(']') {
         let content = &s[1..s.len()-1].trim();
         if content.is_empty() {
-            return Ok(Value::Array(vec![]));
+            return Ok(Value::array(vec![]));
         }
         
         let mut elements = Vec::new();
@@ -118,14 +121,14 @@ fn parse_json_value(s: &str) -> Result<Value, String> {
             elements.push(parse_json_value(current.trim())?);
         }
         
-        return Ok(Value::Array(elements));
+        return Ok(Value::array(elements));
     }
     
     // Parse object (as map)
     if s.starts_with('{') && s.ends_with('}') {
         let content = &s[1..s.len()-1].trim();
         if content.is_empty() {
-            return Ok(Value::Map(HashMap::new()));
+            return Ok(Value::map(HashMap::new()));
         }
         
         let mut map = HashMap::new();
@@ -169,7 +172,7 @@ fn parse_json_value(s: &str) -> Result<Value, String> {
             }
         }
         
-        return Ok(Value::Map(map));
+        return Ok(Value::map(map));
     }
     
     Err(format!("Invalid JSON: {}", s))
@@ -181,20 +184,34 @@ fn value_to_json(value: &Value) -> String {
         Value::Null => "null".to_string(),
         Value::Boolean(b) => b.to_string(),
         Value::Integer(n) => n.to_string(),
+        Value::BigInt(n) => n.to_string(),
+        // Decimals are written as strings so that readers keep every digit
+        Value::Decimal(d) => format!("\"{}\"", d),
         Value::Number(n) => n.to_string(),
         Value::String(s) => format!("\"{}\"", s.replace('"', "\\\"")),
         Value::Array(arr) => {
-            let elements: Vec<String> = arr.iter().map(value_to_json).collect();
+            let elements: Vec<String> = arr.borrow().iter().map(value_to_json).collect();
+            format!("[{}]", elements.join(","))
+        }
+        Value::Tuple(elements) => {
+            let elements: Vec<String> = elements.iter().map(value_to_json).collect();
             format!("[{}]", elements.join(","))
         }
+        Value::Range { start, end, inclusive } => {
+            let last = if *inclusive { *end } else { end - 1 };
+            let elements: Vec<String> = (*start..=last).map(|n| n.to_string()).collect();
+            format!("[{}]", elements.join(","))
+        }
+        // Iterators are consumed lazily and have no data to serialize
+        Value::Iterator(_) => "null".to_string(),
         Value::Map(map) => {
-            let pairs: Vec<String> = map.iter()
+            let pairs: Vec<String> = map.borrow().iter()
                 .map(|(k, v)| format!("\"{}\":{}", k, value_to_json(v)))
                 .collect();
             format!("{{{}}}", pairs.join(","))
         }
         Value::Struct { type_name, fields } => {
-            let pairs: Vec<String> = fields.iter()
+            let pairs: Vec<String> = fields.borrow().iter()
                 .map(|(k, v)| format!("\"{}\":{}", k, value_to_json(v)))
                 .collect();
             format!("{{\"_type\":\"{}\",{}}}", type_name, pairs.join(","))
@@ -213,5 +230,11 @@ fn value_to_json(value: &Value) -> String {
                 "null".to_string()
             }
         }
+        Value::Enum { type_name, variant, payload } => {
+            let values: Vec<String> = payload.iter().map(value_to_json).collect();
+            format!("{{\"_type\":\"{}\",\"{}\":[{}]}}", type_name, variant, values.join(","))
+        }
+        // Functions have no JSON representation
+        Value::Function { .. } => "null".to_string(),
     }
 }
@@ -0,0 +1,107 @@
+// src/stdlib/map_lib.rs
+//! Map helpers - Native Rust implementation
+//!
+//! `map` is a type keyword, so these are reached as methods on map values
+//! (`m.keys()`, `m.has("k")`) rather than through a `use` import.
+
+use crate::backend::execution::value::Value;
+
+pub fn has_function(name: &str) -> bool {
+    matches!(name, "keys" | "values" | "has" | "get" | "remove" | "len")
+}
+
+pub fn get_function_list() -> Vec<&'static str> {
+    vec!["keys", "values", "has", "get", "remove", "len"]
+}
+
+pub fn call_function(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    match name {
+        "keys" => keys(args),
+        "values" => values(args),
+        "has" => has(args),
+        "get" => get(args),
+        "remove" => remove(args),
+        "len" => len(args),
+        _ => Err(format!("Unknown map function: {}", name)),
+    }
+}
+
+/// Keys in sorted order, matching the order `for (k, v) in m` visits them
+fn keys(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("keys() takes exactly 1 argument".to_string());
+    }
+    match &args[0] {
+        Value::Map(map) => {
+            let map = map.borrow();
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            Ok(Value::array(keys.into_iter().map(|k| Value::String(k.clone())).collect()))
+        }
+        _ => Err("keys() requires a map argument".to_string()),
+    }
+}
+
+fn values(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("values() takes exactly 1 argument".to_string());
+    }
+    match &args[0] {
+        Value::Map(map) => {
+            let map = map.borrow();
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Ok(Value::array(entries.into_iter().map(|(_, v)| v.clone()).collect()))
+        }
+        _ => Err("values() requires a map argument".to_string()),
+    }
+}
+
+fn has(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("has() takes exactly 2 arguments".to_string());
+    }
+    match &args[0] {
+        Value::Map(map) => Ok(Value::Boolean(map.borrow().contains_key(&args[1].to_string()))),
+        _ => Err("has() requires a map as first argument".to_string()),
+    }
+}
+
+/// Value for a key, or the default (null when not given) if the key is missing
+fn get(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err("get() takes 2 or 3 arguments".to_string());
+    }
+    match &args[0] {
+        Value::Map(map) => Ok(map
+            .borrow()
+            .get(&args[1].to_string())
+            .cloned()
+            .unwrap_or_else(|| args.get(2).cloned().unwrap_or(Value::Null))),
+        _ => Err("get() requires a map as first argument".to_string()),
+    }
+}
+
+/// Remove a key in place, returning the map
+fn remove(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("remove() takes exactly 2 arguments".to_string());
+    }
+    match &args[0] {
+        Value::Map(map) => {
+            map.borrow_mut().remove(&args[1].to_string());
+            Ok(Value::Map(map.clone()))
+        }
+        _ => Err("remove() requires a map as first argument".to_string()),
+    }
+}
+
+fn len(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("len() takes exactly 1 argument".to_string());
+    }
+    match &args[0] {
+        Value::Map(map) => Ok(Value::Integer(map.borrow().len() as i64)),
+        _ => Err("len() requires a map argument".to_string()),
+    }
+}
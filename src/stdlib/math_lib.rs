@@ -2,14 +2,18 @@
 //! Math functions standard library - Native Rust implementation
 
 use crate::backend::execution::value::Value;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
 
 pub fn has_function(name: &str) -> bool {
     matches!(name, "abs" | "max" | "min" | "pow" | "sqrt" | "floor" | "ceil" | 
-             "round" | "sin" | "cos" | "tan" | "pi" | "e")
+             "round" | "sin" | "cos" | "tan" | "pi" | "e" | "gcd" | "lcm" | "modpow" | "isqrt")
 }
 
 pub fn get_function_list() -> Vec<&'static str> {
-    vec!["abs", "max", "min", "pow", "sqrt", "floor", "ceil", "round", "sin", "cos", "tan", "pi", "e"]
+    vec!["abs", "max", "min", "pow", "sqrt", "floor", "ceil", "round", "sin", "cos", "tan", "pi", "e",
+         "gcd", "lcm", "modpow", "isqrt"]
 }
 
 pub fn call_function(name: &str, args: Vec<Value>) -> Result<Value, String> {
@@ -27,6 +31,10 @@ pub fn call_function(name: &str, args: Vec<Value>) -> Result<Value, String> {
         "tan" => tan(args),
         "pi" => Ok(Value::Number(std::f64::consts::PI)),
         "e" => Ok(Value::Number(std::f64::consts::E)),
+        "gcd" => gcd(args),
+        "lcm" => lcm(args),
+        "modpow" => modpow(args),
+        "isqrt" => isqrt(args),
         _ => Err(format!("Unknown math function: {}", name)),
     }
 }
@@ -36,7 +44,10 @@ fn abs(args: Vec<Value>) -> Result<Value, String> {
         return Err("abs() takes exactly 1 argument".to_string());
     }
     match &args[0] {
-        Value::Integer(n) => Ok(Value::Integer(n.abs())),
+        Value::Integer(n) => n.checked_abs().map(Value::Integer).ok_or_else(|| {
+            format!("Integer overflow: abs({}) does not fit in int (use tobigint() for larger values)", n)
+        }),
+        Value::BigInt(n) => Ok(Value::bigint(n.abs())),
         Value::Number(n) => Ok(Value::Number(n.abs())),
         _ => Err("abs() requires a numeric argument".to_string()),
     }
@@ -153,3 +164,58 @@ fn tan(args: Vec<Value>) -> Result<Value, String> {
         _ => Err("tan() requires a numeric argument".to_string()),
     }
 }
+
+/// Integer arguments as bigints, and whether any of them was a bigint
+fn integer_args(name: &str, args: &[Value], count: usize) -> Result<(Vec<BigInt>, bool), String> {
+    if args.len() != count {
+        return Err(format!("{}() takes exactly {} argument{}", name, count, if count == 1 { "" } else { "s" }));
+    }
+    let mut values = Vec::new();
+    for arg in args {
+        match arg {
+            Value::Integer(_) | Value::BigInt(_) => values.extend(arg.to_bigint()),
+            _ => return Err(format!("{}() requires integer arguments", name)),
+        }
+    }
+    Ok((values, args.iter().any(|arg| matches!(arg, Value::BigInt(_)))))
+}
+
+/// Result of an integer function: a bigint if any argument was one, otherwise an int
+fn integer_result(name: &str, value: BigInt, big: bool) -> Result<Value, String> {
+    if big {
+        return Ok(Value::bigint(value));
+    }
+    value.to_i64().map(Value::Integer).ok_or_else(|| {
+        format!("Integer overflow: {}() result {} does not fit in int (use tobigint() for larger values)", name, value)
+    })
+}
+
+fn gcd(args: Vec<Value>) -> Result<Value, String> {
+    let (values, big) = integer_args("gcd", &args, 2)?;
+    integer_result("gcd", values[0].gcd(&values[1]), big)
+}
+
+fn lcm(args: Vec<Value>) -> Result<Value, String> {
+    let (values, big) = integer_args("lcm", &args, 2)?;
+    integer_result("lcm", values[0].lcm(&values[1]), big)
+}
+
+fn modpow(args: Vec<Value>) -> Result<Value, String> {
+    let (values, big) = integer_args("modpow", &args, 3)?;
+    let (base, exponent, modulus) = (&values[0], &values[1], &values[2]);
+    if exponent.is_negative() {
+        return Err("modpow() requires a non-negative exponent".to_string());
+    }
+    if modulus.is_zero() {
+        return Err("modpow() requires a non-zero modulus".to_string());
+    }
+    integer_result("modpow", base.modpow(exponent, modulus), big)
+}
+
+fn isqrt(args: Vec<Value>) -> Result<Value, String> {
+    let (values, big) = integer_args("isqrt", &args, 1)?;
+    if values[0].is_negative() {
+        return Err("isqrt() requires a non-negative argument".to_string());
+    }
+    integer_result("isqrt", values[0].sqrt(), big)
+}
@@ -25,6 +25,8 @@ pub mod os_lib;
 pub mod regex_lib;
 pub mod random_lib;
 pub mod server_lib;
+pub mod map_lib;
+pub mod decimal_lib;
 
 use crate::backend::execution::value::Value;
 
@@ -91,6 +93,105 @@ define_stdlib_modules! {
     ("server", server_lib, "HTTP web server like Go and Node.js"),
 }
 
+// BUILT-IN TYPE METHODS
+// Stdlib functions callable as methods on values of a built-in type, e.g. "abc".upper()
+// or xs.sum(). The receiver is passed as the first argument. Types are named as `typeof`
+// reports them.
+const STRING_METHODS: &[(&str, &str)] = &[
+    ("upper", "string"), ("lower", "string"), ("trim", "string"), ("split", "string"),
+    ("contains", "string"), ("starts_with", "string"), ("ends_with", "string"),
+    ("replace", "string"), ("reverse", "string"), ("repeat", "string"), ("char_at", "string"),
+];
+
+const ARRAY_METHODS: &[(&str, &str)] = &[
+    ("push", "arr"), ("pop", "arr"), ("first", "arr"), ("last", "arr"), ("reversed", "arr"),
+    ("contains", "arr"), ("sum", "arr"), ("avg", "arr"), ("max", "arr"), ("min", "arr"),
+    ("sorted", "arr"), ("join", "string"),
+];
+
+/// Methods named apart from the stdlib function behind them; `xs.sorted()` returns a sorted
+/// copy, unlike `push`/`pop`, which change the array in place
+const METHOD_FUNCTIONS: &[(&str, &str)] = &[
+    ("sorted", "sort"), ("reversed", "reverse"),
+];
+
+const MAP_METHODS: &[(&str, &str)] = &[
+    ("keys", "map"), ("values", "map"), ("has", "map"), ("get", "map"), ("remove", "map"),
+];
+
+const DECIMAL_METHODS: &[(&str, &str)] = &[
+    ("round", "decimal"), ("scale", "decimal"),
+];
+
+/// Runtime builtins that also work as methods on each built-in type
+const BUILTIN_METHODS: &[(&str, &[&str])] = &[
+    ("str", &["len", "toint", "tobigint", "tofloat", "tostr", "tobool", "clone", "enumerate", "map", "filter", "take", "zip"]),
+    ("array", &["len", "append", "remove", "tostr", "clone", "enumerate", "map", "filter", "take", "zip"]),
+    ("map", &["len", "tostr", "clone", "enumerate", "map", "filter", "take", "zip"]),
+    ("decimal", &["toint", "tofloat", "tostr", "tobool", "clone"]),
+    ("Result", &["is_ok", "is_err", "unwrap", "unwrap_or", "tostr", "clone"]),
+    ("Option", &["is_some", "is_none", "unwrap", "unwrap_or", "tostr", "clone"]),
+];
+
+/// Methods that change their receiver in place rather than returning a new value
+const MUTATING_METHODS: &[(&str, &[&str])] = &[
+    ("array", &["push", "pop"]),
+    ("map", &["remove"]),
+];
+
+fn type_methods(type_name: &str) -> &'static [(&'static str, &'static str)] {
+    match type_name {
+        "str" => STRING_METHODS,
+        "array" => ARRAY_METHODS,
+        "map" => MAP_METHODS,
+        "decimal" => DECIMAL_METHODS,
+        _ => &[],
+    }
+}
+
+/// Whether `module.function(value, ...)` modifies `value` in place, as `arr.push(xs, 1)`
+/// does for `xs.push(1)`
+pub fn mutates_first_argument(module: &str, function: &str) -> bool {
+    MUTATING_METHODS.iter().any(|(type_name, methods)| {
+        methods.contains(&function) && method_module(type_name, function) == Some(module)
+    })
+}
+
+/// Stdlib module implementing `method` for values of `type_name`, if any
+pub fn method_module(type_name: &str, method: &str) -> Option<&'static str> {
+    type_methods(type_name).iter().find(|(name, _)| *name == method).map(|(_, module)| *module)
+}
+
+/// Call a stdlib-backed method with the receiver as the first argument
+pub fn call_method(type_name: &str, method: &str, args: Vec<Value>) -> Result<Value, String> {
+    let function = METHOD_FUNCTIONS.iter()
+        .find(|(name, _)| *name == method)
+        .map_or(method, |(_, function)| *function);
+    match method_module(type_name, method) {
+        Some("map") => map_lib::call_function(function, args),
+        Some("decimal") => decimal_lib::call_function(function, args),
+        Some(module) => call_stdlib_function(module, function, args),
+        None => Err(format!("No method named '{}' for type '{}'", method, type_name)),
+    }
+}
+
+/// Whether `method` modifies a value of `type_name` in place
+pub fn is_mutating_method(type_name: &str, method: &str) -> bool {
+    MUTATING_METHODS.iter().any(|(name, methods)| *name == type_name && methods.contains(&method))
+}
+
+/// Every method available on values of a built-in type, or None for other types
+pub fn method_names(type_name: &str) -> Option<Vec<&'static str>> {
+    let builtins = BUILTIN_METHODS.iter().find(|(name, _)| *name == type_name)?.1;
+    let mut names: Vec<&'static str> = type_methods(type_name).iter().map(|(name, _)| *name).collect();
+    for name in builtins {
+        if !names.contains(name) {
+            names.push(name);
+        }
+    }
+    Some(names)
+}
+
 #[derive(Debug, Clone)]
 pub struct ModuleInfo {
     pub name: &'static str,
@@ -99,10 +99,10 @@ fn env_vars(_args: Vec<Value>) -> Result<Value, String> {
             let mut map = std::collections::HashMap::new();
             map.insert("key".to_string(), Value::String(key));
             map.insert("value".to_string(), Value::String(value));
-            Value::Map(map)
+            Value::map(map)
         })
         .collect();
-    Ok(Value::Array(vars))
+    Ok(Value::array(vars))
 }
 
 fn current_dir(_args: Vec<Value>) -> Result<Value, String> {
@@ -165,7 +165,7 @@ fn get_args(_args: Vec<Value>) -> Result<Value, String> {
     let args: Vec<Value> = env::args()
         .map(|arg| Value::String(arg))
         .collect();
-    Ok(Value::Array(args))
+    Ok(Value::array(args))
 }
 
 fn exit(args: Vec<Value>) -> Result<Value, String> {
@@ -203,7 +203,7 @@ fn exec(args: Vec<Value>) -> Result<Value, String> {
                     result.insert("stderr".to_string(), Value::String(
                         String::from_utf8_lossy(&output.stderr).to_string()
                     ));
-                    Ok(Value::Map(result))
+                    Ok(Value::map(result))
                 }
                 Err(e) => Err(format!("Failed to execute command: {}", e)),
             }
@@ -125,6 +125,7 @@ fn choice(args: Vec<Value>) -> Result<Value, String> {
     
     match &args[0] {
         Value::Array(arr) => {
+            let arr = arr.borrow();
             if arr.is_empty() {
                 return Err("random.choice() cannot choose from empty array".to_string());
             }
@@ -146,10 +147,10 @@ fn shuffle(args: Vec<Value>) -> Result<Value, String> {
     
     match &args[0] {
         Value::Array(arr) => {
-            let mut shuffled = arr.clone();
+            let mut shuffled = arr.borrow().clone();
             let mut rng = rand::thread_rng();
             shuffled.shuffle(&mut rng);
-            Ok(Value::Array(shuffled))
+            Ok(Value::array(shuffled))
         }
         _ => Err("random.shuffle() requires an array argument".to_string()),
     }
@@ -176,7 +177,7 @@ fn random_bytes(args: Vec<Value>) -> Result<Value, String> {
                 .map(|_| Value::Integer(rng.gen_range(0..256)))
                 .collect();
             
-            Ok(Value::Array(bytes))
+            Ok(Value::array(bytes))
         }
         _ => Err("random.bytes() requires an integer argument".to_string()),
     }
@@ -46,9 +46,9 @@ fn regex_match(args: Vec<Value>) -> Result<Value, String> {
                             .iter()
                             .filter_map(|m| m.map(|m| Value::String(m.as_str().to_string())))
                             .collect();
-                        Ok(Value::Array(matches))
+                        Ok(Value::array(matches))
                     } else {
-                        Ok(Value::Array(vec![]))
+                        Ok(Value::array(vec![]))
                     }
                 }
                 Err(e) => Err(format!("Invalid regex pattern: {}", e)),
@@ -71,7 +71,7 @@ fn matches(args: Vec<Value>) -> Result<Value, String> {
                         .find_iter(text)
                         .map(|m| Value::String(m.as_str().to_string()))
                         .collect();
-                    Ok(Value::Array(all_matches))
+                    Ok(Value::array(all_matches))
                 }
                 Err(e) => Err(format!("Invalid regex pattern: {}", e)),
             }
@@ -157,7 +157,7 @@ fn split(args: Vec<Value>) -> Result<Value, String> {
                         .split(text)
                         .map(|s| Value::String(s.to_string()))
                         .collect();
-                    Ok(Value::Array(parts))
+                    Ok(Value::array(parts))
                 }
                 Err(e) => Err(format!("Invalid regex pattern: {}", e)),
             }
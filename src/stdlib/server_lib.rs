@@ -64,7 +64,7 @@ fn create(_args: Vec<Value>) -> Result<Value, String> {
     server_config.insert("port".to_string(), Value::Integer(8080));
     server_config.insert("running".to_string(), Value::Boolean(false));
     
-    Ok(Value::Map(server_config))
+    Ok(Value::map(server_config))
 }
 
 // Register a GET route
@@ -385,7 +385,7 @@ fn text_response(args: Vec<Value>) -> Result<Value, String> {
             let mut response = HashMap::new();
             response.insert("type".to_string(), Value::String("text/plain".to_string()));
             response.insert("body".to_string(), Value::String(text.clone()));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("text() requires a string argument".to_string()),
     }
@@ -401,7 +401,7 @@ fn html_response(args: Vec<Value>) -> Result<Value, String> {
             let mut response = HashMap::new();
             response.insert("type".to_string(), Value::String("text/html".to_string()));
             response.insert("body".to_string(), Value::String(html.clone()));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("html() requires a string argument".to_string()),
     }
@@ -417,7 +417,7 @@ fn json_response(args: Vec<Value>) -> Result<Value, String> {
             let mut response = HashMap::new();
             response.insert("type".to_string(), Value::String("application/json".to_string()));
             response.insert("body".to_string(), Value::String(json.clone()));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("json() requires a string argument".to_string()),
     }
@@ -432,7 +432,7 @@ fn status_response(args: Vec<Value>) -> Result<Value, String> {
         Value::Integer(code) => {
             let mut response = HashMap::new();
             response.insert("status".to_string(), Value::Integer(*code));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("status() requires an integer status code".to_string()),
     }
@@ -48,10 +48,10 @@ fn create(_args: Vec<Value>) -> Result<Value, String> {
     let mut server_config = HashMap::new();
     server_config.insert("host".to_string(), Value::String("127.0.0.1".to_string()));
     server_config.insert("port".to_string(), Value::Integer(8080));
-    server_config.insert("routes".to_string(), Value::Array(vec![]));
+    server_config.insert("routes".to_string(), Value::array(vec![]));
     server_config.insert("running".to_string(), Value::Boolean(false));
     
-    Ok(Value::Map(server_config))
+    Ok(Value::map(server_config))
 }
 
 fn listen(args: Vec<Value>) -> Result<Value, String> {
@@ -220,7 +220,7 @@ fn text_response(args: Vec<Value>) -> Result<Value, String> {
             let mut response = HashMap::new();
             response.insert("type".to_string(), Value::String("text/plain".to_string()));
             response.insert("body".to_string(), Value::String(text.clone()));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("text() requires a string argument".to_string()),
     }
@@ -236,7 +236,7 @@ fn html_response(args: Vec<Value>) -> Result<Value, String> {
             let mut response = HashMap::new();
             response.insert("type".to_string(), Value::String("text/html".to_string()));
             response.insert("body".to_string(), Value::String(html.clone()));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("html() requires a string argument".to_string()),
     }
@@ -252,7 +252,7 @@ fn json_response(args: Vec<Value>) -> Result<Value, String> {
             let mut response = HashMap::new();
             response.insert("type".to_string(), Value::String("application/json".to_string()));
             response.insert("body".to_string(), Value::String(json.clone()));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("json() requires a string argument".to_string()),
     }
@@ -267,7 +267,7 @@ fn status_response(args: Vec<Value>) -> Result<Value, String> {
         Value::Integer(code) => {
             let mut response = HashMap::new();
             response.insert("status".to_string(), Value::Integer(*code));
-            Ok(Value::Map(response))
+            Ok(Value::map(response))
         }
         _ => Err("status() requires an integer status code".to_string()),
     }
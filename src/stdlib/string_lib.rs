@@ -75,7 +75,7 @@ fn split(args: Vec<Value>) -> Result<Value, String> {
             let parts: Vec<Value> = s.split(delimiter.as_str())
                 .map(|part| Value::String(part.to_string()))
                 .collect();
-            Ok(Value::Array(parts))
+            Ok(Value::array(parts))
         }
         _ => Err("split() requires two string arguments".to_string()),
     }
@@ -87,7 +87,7 @@ fn join(args: Vec<Value>) -> Result<Value, String> {
     }
     match (&args[0], &args[1]) {
         (Value::Array(arr), Value::String(separator)) => {
-            let strings: Vec<String> = arr.iter()
+            let strings: Vec<String> = arr.borrow().iter()
                 .map(|v| v.to_string())
                 .collect();
             Ok(Value::String(strings.join(separator)))